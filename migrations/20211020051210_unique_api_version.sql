@@ -0,0 +1,7 @@
+-- Add migration script here
+
+/* An API version is identified by its service and vers alone,
+* two base urls cannot share a version of the same service
+*/
+alter table daysquare.api
+    add constraint api_service_id_vers_key unique(service_id, vers);
@@ -0,0 +1,10 @@
+-- Add migration script here
+
+/* How vector queries of a request are put in the url e.g.:
+* repeat    ids=1&ids=2
+* comma     ids=1,2
+* brackets  ids[]=1&ids[]=2
+*/
+alter table daysquare.request
+    add column query_vec_style text not null default 'repeat'
+    check (query_vec_style in ('repeat', 'comma', 'brackets'));
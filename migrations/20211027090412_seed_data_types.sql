@@ -0,0 +1,24 @@
+-- Add migration script here
+
+/* The url DSL refers to data types by label alone
+* e.g. {id,spotify_artist_id} so labels have to be unique
+*/
+alter table daysquare.data_type
+    add constraint data_type_label_key unique(label);
+
+/* Built-in primitives. Ids are derived from the name so they
+* are the same in every database
+*/
+insert into daysquare.data_primitive (id, primitive)
+select md5('data_primitive:' || p)::uuid, p
+from unnest(array['string', 'int', 'float', 'bool', 'uuid', 'datetime', 'const']) as p
+on conflict do nothing;
+
+/* Every built-in primitive is also a data type of the same name
+* e.g. {projectId,string}
+*/
+insert into daysquare.data_type (id, data_primitive_id, label)
+select md5('data_type:' || p.primitive)::uuid, p.id, p.primitive
+from daysquare.data_primitive p
+where p.primitive in ('string', 'int', 'float', 'bool', 'uuid', 'datetime', 'const')
+on conflict do nothing;
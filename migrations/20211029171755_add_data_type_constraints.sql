@@ -0,0 +1,17 @@
+-- Add migration script here
+
+/* Optional checks on the values of a data type
+* e.g. a spotify artist id is 22 alphanumeric characters
+* pattern has to match the whole value
+* min_value and max_value only apply to int and float
+*/
+alter table daysquare.data_type
+    add column pattern text,
+    add column min_length integer check (min_length >= 0),
+    add column max_length integer check (max_length >= 0),
+    add column min_value double precision,
+    add column max_value double precision,
+    add column enum_values text[] check (cardinality(enum_values) > 0),
+
+    add constraint data_type_length_check check (min_length <= max_length),
+    add constraint data_type_value_check check (min_value <= max_value);
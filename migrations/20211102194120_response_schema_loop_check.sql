@@ -0,0 +1,37 @@
+-- Add migration script here
+
+/* A schema cannot be nested in itself, directly or through
+* its children. Rendering a schema tree would never end.
+* The api checks this before linking schemas, the trigger
+* catches anything else e.g.:
+*   Artist.albums -> Album.artist -> Artist
+*/
+create function daysquare.response_schema_data_loop_check() returns trigger as $$
+begin
+    -- two links inserted at the same time can form a loop that neither sees
+    perform pg_advisory_xact_lock(hashtext('daysquare.response_schema_data'));
+
+    if exists (
+        with recursive descendant(id) as (
+            select new.child_response_schema_id
+            union
+            select d.child_response_schema_id
+            from daysquare.response_schema_data d
+            join descendant on descendant.id = d.parent_response_schema_id
+        )
+        select 1 from descendant where id = new.parent_response_schema_id
+    ) then
+        raise exception 'response schema % cannot be nested in itself',
+            new.parent_response_schema_id
+            using errcode = 'check_violation',
+                constraint = 'response_schema_data_loop_check';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger response_schema_data_loop_check
+    before insert or update of parent_response_schema_id, child_response_schema_id
+    on daysquare.response_schema_data
+    for each row execute function daysquare.response_schema_data_loop_check();
@@ -0,0 +1,28 @@
+-- Add migration script here
+
+/* The loop check took one advisory lock for the whole table,
+* so every link waited on every other one. The api links schemas
+* in serializable transactions instead and retries the one that
+* loses, which then sees the other link and reports the loop.
+*/
+create or replace function daysquare.response_schema_data_loop_check() returns trigger as $$
+begin
+    if exists (
+        with recursive descendant(id) as (
+            select new.child_response_schema_id
+            union
+            select d.child_response_schema_id
+            from daysquare.response_schema_data d
+            join descendant on descendant.id = d.parent_response_schema_id
+        )
+        select 1 from descendant where id = new.parent_response_schema_id
+    ) then
+        raise exception 'response schema % cannot be nested in itself',
+            new.parent_response_schema_id
+            using errcode = 'check_violation',
+                constraint = 'response_schema_data_loop_check';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
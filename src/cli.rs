@@ -0,0 +1,66 @@
+//! Subcommands of the server binary.
+//!
+//! Without arguments the binary serves the api, a subcommand runs
+//! once against the configured database and prints its result.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::export::rust;
+use crate::store;
+
+const USAGE: &str = "usage: daysquare-backend [rust-client <api id>]";
+
+/// What the binary was asked to do
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Serve,
+    /// Print the Rust client module of an API version
+    RustClient(Uuid),
+}
+
+impl Command {
+    /// Read the command from the arguments that follow the binary name
+    pub fn parse(args: &[String]) -> Result<Command, String> {
+        match args {
+            [] => Ok(Command::Serve),
+            [command, api_id] if command == "rust-client" => Uuid::parse_str(api_id)
+                .map(Command::RustClient)
+                .map_err(|_| format!("{} is not an api id\n{}", api_id, USAGE)),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+}
+
+/// The Rust client module of the API with id `api_id`
+pub async fn rust_client(pool: &PgPool, api_id: Uuid) -> Result<String, String> {
+    match store::load_api_export(pool, api_id).await {
+        Ok(Some(export)) => Ok(rust::module(&export)),
+        Ok(None) => Err(format!("there is no api with id {}", api_id)),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        Command::parse(&args)
+    }
+
+    #[test]
+    fn commands_are_read_from_the_arguments() {
+        let api_id = Uuid::new_v4();
+
+        assert_eq!(parse(&[]), Ok(Command::Serve));
+        assert_eq!(
+            parse(&["rust-client", &api_id.to_string()]),
+            Ok(Command::RustClient(api_id))
+        );
+        assert!(parse(&["rust-client", "spotify"]).is_err());
+        assert!(parse(&["rust-client"]).is_err());
+        assert!(parse(&["typescript-client", &api_id.to_string()]).is_err());
+    }
+}
@@ -1,10 +1,13 @@
 use std::rc::Rc;
+use std::time::Duration;
 use serde::Deserialize;
 
 #[derive(Deserialize)]
 pub struct SettingsInner {
     pub database: DatabaseSettings,
     pub server: ServerSettingsInner,
+    #[serde(default)]
+    pub outbound: OutboundSettings,
 }
 
 #[derive(Deserialize)]
@@ -23,9 +26,20 @@ pub struct ServerSettingsInner {
     pub secure: bool,
 }
 
+/// Calls the server makes to catalogued APIs
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct OutboundSettings {
+    /// Longest a call may take, a request can only ask for less
+    pub timeout_milliseconds: u64,
+    /// Largest response body read from an upstream
+    pub max_body_bytes: usize,
+}
+
 pub struct Settings {
     pub database: DatabaseSettings,
     pub server: ServerSettings,
+    pub outbound: OutboundSettings,
 }
 
 #[derive(Clone)]
@@ -49,6 +63,21 @@ impl ServerSettings {
     }
 }
 
+impl OutboundSettings {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_milliseconds)
+    }
+}
+
+impl Default for OutboundSettings {
+    fn default() -> Self {
+        OutboundSettings {
+            timeout_milliseconds: 10_000,
+            max_body_bytes: 1_048_576,
+        }
+    }
+}
+
 impl DatabaseSettings {
     pub fn connection_string(&self) -> String {
         format!(
@@ -83,5 +112,6 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
         server: ServerSettings {
             0: Rc::new(declared_settings.server),
         },
+        outbound: declared_settings.outbound,
     })
 }
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A row of `daysquare.api`.
+#[derive(Serialize, Debug)]
+pub struct ApiRecord {
+    pub id: Uuid,
+    pub service_id: Uuid,
+    pub url: String,
+    pub vers: String,
+}
+
+/// Body of a request registering a new version of an API
+#[derive(Deserialize, Debug)]
+pub struct NewApi {
+    pub url: String,
+    pub vers: String,
+}
+
+/// Partial update of an API. Fields left out are unchanged.
+#[derive(Deserialize, Debug)]
+pub struct ApiPatch {
+    pub url: Option<String>,
+    pub vers: Option<String>,
+}
+
+/// Version of an API as it appears in request urls e.g. `v1`
+pub fn parse_version(vers: String) -> Result<String, String> {
+    let vers = vers.trim();
+
+    if vers.is_empty() {
+        return Err("version cannot be empty".to_string());
+    }
+
+    if vers.contains(|c: char| c == '/' || c == '|' || c.is_whitespace()) {
+        return Err(format!(
+            "version {} cannot contain `/`, `|` or whitespace",
+            vers
+        ));
+    }
+
+    Ok(vers.to_string())
+}
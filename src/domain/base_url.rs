@@ -0,0 +1,75 @@
+use axum::http::Uri;
+
+/// Absolute http(s) url that requests of an API are built on top of
+/// e.g. `https://api.spotify.com` or `https://www.googleapis.com/youtube`
+///
+/// A trailing `/` is dropped so paths can always be appended with one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaseUrl(String);
+
+impl BaseUrl {
+    pub fn parse(s: String) -> Result<BaseUrl, String> {
+        let trimmed = s.trim().trim_end_matches('/');
+        let uri: Uri = trimmed
+            .parse()
+            .map_err(|_| format!("{} is not a valid url", s))?;
+
+        match uri.scheme_str() {
+            Some("http") | Some("https") => (),
+            _ => return Err(format!("{} must start with http:// or https://", s)),
+        }
+
+        match uri.host() {
+            Some(host) if !host.is_empty() => (),
+            _ => return Err(format!("{} is missing a host", s)),
+        }
+
+        if uri.query().is_some() {
+            return Err(format!("{} cannot have a query", s));
+        }
+
+        Ok(BaseUrl(trimmed.to_string()))
+    }
+}
+
+impl AsRef<str> for BaseUrl {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BaseUrl;
+
+    #[test]
+    fn absolute_http_urls_are_accepted() {
+        for url in &[
+            "https://api.spotify.com",
+            "http://localhost:8080",
+            "https://www.googleapis.com/youtube",
+        ] {
+            assert!(BaseUrl::parse(url.to_string()).is_ok(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn trailing_slash_is_dropped() {
+        let url = BaseUrl::parse("https://api.spotify.com/".to_string()).unwrap();
+        assert_eq!(url.as_ref(), "https://api.spotify.com");
+    }
+
+    #[test]
+    fn relative_and_non_http_urls_are_rejected() {
+        for url in &[
+            "",
+            "api.spotify.com",
+            "/v1/artists",
+            "ftp://spotify.com",
+            "https://",
+            "https://api.spotify.com?market=US",
+        ] {
+            assert!(BaseUrl::parse(url.to_string()).is_err(), "{}", url);
+        }
+    }
+}
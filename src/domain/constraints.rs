@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// How values of a data primitive are checked.
+/// Primitives added through the api are checked as strings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Primitive {
+    String,
+    Int,
+    Float,
+    Bool,
+    Uuid,
+    Datetime,
+    Const,
+}
+
+impl Primitive {
+    pub fn from_name(primitive: &str) -> Primitive {
+        match primitive {
+            "int" => Primitive::Int,
+            "float" => Primitive::Float,
+            "bool" => Primitive::Bool,
+            "uuid" => Primitive::Uuid,
+            "datetime" => Primitive::Datetime,
+            "const" => Primitive::Const,
+            _ => Primitive::String,
+        }
+    }
+
+    pub fn is_numeric(self) -> bool {
+        matches!(self, Primitive::Int | Primitive::Float)
+    }
+
+    /// Whether `value` is written the way this primitive expects
+    fn accepts(self, value: &str) -> bool {
+        match self {
+            Primitive::String | Primitive::Const => true,
+            Primitive::Int => i64::from_str(value).is_ok(),
+            Primitive::Float => matches!(f64::from_str(value), Ok(n) if n.is_finite()),
+            Primitive::Bool => value == "true" || value == "false",
+            Primitive::Uuid => Uuid::parse_str(value).is_ok(),
+            Primitive::Datetime => chrono::DateTime::parse_from_rfc3339(value).is_ok(),
+        }
+    }
+
+    pub(crate) fn describe(self) -> &'static str {
+        match self {
+            Primitive::String | Primitive::Const => "a string",
+            Primitive::Int => "an integer",
+            Primitive::Float => "a number",
+            Primitive::Bool => "true or false",
+            Primitive::Uuid => "a uuid",
+            Primitive::Datetime => "an RFC 3339 datetime",
+        }
+    }
+}
+
+/// Optional checks on the values of a data type.
+/// `pattern` has to match the whole value.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Constraints {
+    pub pattern: Option<String>,
+    pub min_length: Option<i32>,
+    pub max_length: Option<i32>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub enum_values: Option<Vec<String>>,
+}
+
+/// A constraint a value failed
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Violation {
+    pub constraint: &'static str,
+    pub message: String,
+}
+
+/// Outcome of checking a value against a data type
+#[derive(Serialize, Debug)]
+pub struct Validation {
+    pub valid: bool,
+    pub violations: Vec<Violation>,
+}
+
+impl Validation {
+    pub fn new(violations: Vec<Violation>) -> Validation {
+        Validation {
+            valid: violations.is_empty(),
+            violations,
+        }
+    }
+}
+
+impl Violation {
+    fn new(constraint: &'static str, message: String) -> Violation {
+        Violation {
+            constraint,
+            message,
+        }
+    }
+}
+
+impl Constraints {
+    /// Check the constraints make sense for `primitive` before they are stored
+    pub fn parse(self, primitive: Primitive) -> Result<Constraints, String> {
+        if let Some(pattern) = &self.pattern {
+            anchored(pattern).map_err(|e| format!("pattern {} is invalid: {}", pattern, e))?;
+        }
+
+        if matches!(self.min_length, Some(l) if l < 0)
+            || matches!(self.max_length, Some(l) if l < 0)
+        {
+            return Err("lengths cannot be negative".to_string());
+        }
+
+        if let (Some(min), Some(max)) = (self.min_length, self.max_length) {
+            if min > max {
+                return Err(format!("min_length {} is above max_length {}", min, max));
+            }
+        }
+
+        if (self.min_value.is_some() || self.max_value.is_some()) && !primitive.is_numeric() {
+            return Err("min_value and max_value only apply to int and float".to_string());
+        }
+
+        if let (Some(min), Some(max)) = (self.min_value, self.max_value) {
+            if min > max {
+                return Err(format!("min_value {} is above max_value {}", min, max));
+            }
+        }
+
+        if let Some(values) = &self.enum_values {
+            if values.is_empty() {
+                return Err("enum_values cannot be empty".to_string());
+            }
+
+            if let Some(value) = values.iter().find(|v| !primitive.accepts(v)) {
+                return Err(format!(
+                    "enum value {} is not {}",
+                    value,
+                    primitive.describe()
+                ));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Every constraint `value` fails, including not being a valid `primitive`.
+    /// Empty when the value is valid.
+    pub fn validate(&self, primitive: Primitive, value: &str) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let length = value.chars().count();
+
+        if !primitive.accepts(value) {
+            violations.push(Violation::new(
+                "primitive",
+                format!("{} is not {}", value, primitive.describe()),
+            ));
+        }
+
+        if let Some(pattern) = &self.pattern {
+            // Patterns are checked when stored
+            if !matches!(anchored(pattern), Ok(re) if re.is_match(value)) {
+                violations.push(Violation::new(
+                    "pattern",
+                    format!("{} does not match {}", value, pattern),
+                ));
+            }
+        }
+
+        if let Some(min) = self.min_length {
+            if length < min as usize {
+                violations.push(Violation::new(
+                    "min_length",
+                    format!("{} characters is shorter than {}", length, min),
+                ));
+            }
+        }
+
+        if let Some(max) = self.max_length {
+            if length > max as usize {
+                violations.push(Violation::new(
+                    "max_length",
+                    format!("{} characters is longer than {}", length, max),
+                ));
+            }
+        }
+
+        // A value that isn't a number already failed the primitive check
+        if let Ok(number) = f64::from_str(value) {
+            if let Some(min) = self.min_value {
+                if number < min {
+                    violations.push(Violation::new(
+                        "min_value",
+                        format!("{} is below {}", value, min),
+                    ));
+                }
+            }
+
+            if let Some(max) = self.max_value {
+                if number > max {
+                    violations.push(Violation::new(
+                        "max_value",
+                        format!("{} is above {}", value, max),
+                    ));
+                }
+            }
+        }
+
+        if let Some(values) = &self.enum_values {
+            if !values.iter().any(|v| v == value) {
+                violations.push(Violation::new(
+                    "enum_values",
+                    format!("{} is not one of {}", value, values.join(", ")),
+                ));
+            }
+        }
+
+        violations
+    }
+}
+
+fn anchored(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    regex::Regex::new(&format!("^(?:{})$", pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spotify_id() -> Constraints {
+        Constraints {
+            pattern: Some("[0-9A-Za-z]+".to_string()),
+            min_length: Some(22),
+            max_length: Some(22),
+            ..Constraints::default()
+        }
+    }
+
+    #[test]
+    fn valid_values_have_no_violations() {
+        assert!(spotify_id()
+            .validate(Primitive::String, "0OdUWJ0sBjDrqHygGUXeCF")
+            .is_empty());
+        assert!(Constraints::default()
+            .validate(Primitive::Datetime, "2021-10-27T09:04:12Z")
+            .is_empty());
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let violations = spotify_id().validate(Primitive::String, "not-an-id");
+        let constraints: Vec<_> = violations.iter().map(|v| v.constraint).collect();
+
+        assert_eq!(constraints, ["pattern", "min_length"]);
+    }
+
+    #[test]
+    fn numeric_ranges_and_primitives_are_checked() {
+        let limit = Constraints {
+            min_value: Some(1.0),
+            max_value: Some(50.0),
+            ..Constraints::default()
+        };
+
+        assert!(limit.validate(Primitive::Int, "20").is_empty());
+        assert_eq!(
+            limit.validate(Primitive::Int, "51")[0].constraint,
+            "max_value"
+        );
+        assert_eq!(
+            limit.validate(Primitive::Int, "1.5e1")[0].constraint,
+            "primitive"
+        );
+        assert_eq!(
+            limit.validate(Primitive::Int, "ten")[0].constraint,
+            "primitive"
+        );
+    }
+
+    #[test]
+    fn enum_values_are_checked() {
+        let market = Constraints {
+            enum_values: Some(vec!["US".to_string(), "GB".to_string()]),
+            ..Constraints::default()
+        };
+
+        assert!(market.validate(Primitive::String, "GB").is_empty());
+        assert_eq!(
+            market.validate(Primitive::String, "FR")[0].message,
+            "FR is not one of US, GB"
+        );
+    }
+
+    #[test]
+    fn inconsistent_constraints_are_rejected() {
+        let bad = [
+            Constraints {
+                pattern: Some("[a-z".to_string()),
+                ..Constraints::default()
+            },
+            Constraints {
+                min_length: Some(5),
+                max_length: Some(2),
+                ..Constraints::default()
+            },
+            Constraints {
+                min_value: Some(0.0),
+                ..Constraints::default()
+            },
+            Constraints {
+                enum_values: Some(vec![]),
+                ..Constraints::default()
+            },
+        ];
+
+        for constraints in bad.iter() {
+            assert!(
+                constraints.clone().parse(Primitive::String).is_err(),
+                "{:?}",
+                constraints
+            );
+        }
+        assert!(spotify_id().parse(Primitive::String).is_ok());
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Constraints;
+
+/// Primitives seeded by the migrations. They can't be deleted.
+pub const BUILTIN_PRIMITIVES: [&str; 7] = [
+    "string", "int", "float", "bool", "uuid", "datetime", "const",
+];
+
+/// Data type of path segments that are written as is, e.g. `artists` in `/artists/{id}`
+pub const CONST_TYPE: &str = "const";
+
+/// Primitive given to data types created from an unknown label
+pub const DEFAULT_PRIMITIVE: &str = "string";
+
+/// A row of `daysquare.data_primitive`.
+#[derive(Serialize, Debug)]
+pub struct DataPrimitiveRecord {
+    pub id: Uuid,
+    pub primitive: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NewDataPrimitive {
+    pub primitive: String,
+}
+
+/// A row of `daysquare.data_type` along with the name of its primitive.
+#[derive(Serialize, Debug)]
+pub struct DataTypeRecord {
+    pub id: Uuid,
+    pub label: String,
+    pub data_primitive_id: Uuid,
+    pub primitive: String,
+    pub constraints: Constraints,
+}
+
+/// Body of a request registering a data type. `primitive` is the primitive's name.
+#[derive(Deserialize, Debug)]
+pub struct NewDataType {
+    pub label: String,
+    pub primitive: String,
+    #[serde(default)]
+    pub constraints: Constraints,
+}
+
+/// Partial update of a data type. Fields left out are unchanged.
+/// `constraints` replaces every constraint at once.
+#[derive(Deserialize, Debug)]
+pub struct DataTypePatch {
+    pub label: Option<String>,
+    pub primitive: Option<String>,
+    pub constraints: Option<Constraints>,
+}
+
+/// Sample value to check against a data type
+#[derive(Deserialize, Debug)]
+pub struct SampleValue {
+    pub value: String,
+}
+
+/// What to do with a data type label that isn't in `daysquare.data_type`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownTypes {
+    /// Register it with the `string` primitive
+    Create,
+    /// Fail, listing every unknown label
+    Reject,
+}
+
+impl Default for UnknownTypes {
+    fn default() -> Self {
+        UnknownTypes::Reject
+    }
+}
+
+/// Data type label as written in the url DSL e.g. `spotify_artist_id`
+pub fn parse_label(label: String) -> Result<String, String> {
+    let label = label.trim();
+
+    if label.is_empty() {
+        return Err("data type label cannot be empty".to_string());
+    }
+
+    if label.contains(|c: char| "{},=&?#|/[]".contains(c) || c.is_whitespace()) {
+        return Err(format!(
+            "data type label {} cannot contain whitespace or any of {{}},=&?#|/[]",
+            label
+        ));
+    }
+
+    Ok(label.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_is_trimmed() {
+        assert_eq!(
+            parse_label(" spotify_artist_id ".to_string()).unwrap(),
+            "spotify_artist_id"
+        );
+    }
+
+    #[test]
+    fn label_with_dsl_syntax_is_rejected() {
+        for label in &["", "  ", "artist id", "id}", "[int]", "a,b", "a=b"] {
+            assert!(parse_label(label.to_string()).is_err(), "{:?}", label);
+        }
+    }
+}
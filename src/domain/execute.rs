@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::{DataTypeRecord, Primitive, RequestDefinition, Slot, CONST_TYPE};
+use crate::parsers::render::SlotValue;
+
+/// Body of a request calling a stored request, e.g.:
+/// `{ "values": { "id": "0OdUWJ0sBjDrqHygGUXeCF", "market": "SE" }, "timeout_ms": 2000 }`
+///
+/// `values` is keyed by slot name, vector queries take a list.
+/// Without `timeout_ms` the configured outbound timeout applies.
+#[derive(Deserialize, Debug, Default)]
+pub struct ExecuteInput {
+    #[serde(default)]
+    pub values: HashMap<String, SlotValue>,
+    pub timeout_ms: Option<u64>,
+}
+
+/// A checked call, ready to be sent upstream
+#[derive(Debug, PartialEq)]
+pub struct PreparedCall {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+/// What the upstream answered
+#[derive(Serialize, Debug)]
+pub struct ExecuteOutput {
+    pub url: String,
+    pub status: u16,
+    /// Repeated headers are joined with `, `
+    pub headers: BTreeMap<String, String>,
+    /// The body as JSON when it parses as such, otherwise as a string
+    pub body: Value,
+}
+
+impl RequestDefinition {
+    /// Check `values` against the data types of the slots they fill and build the call.
+    ///
+    /// `data_types` holds the data types of the request's slots by id.
+    /// Every problem is reported, one per line.
+    pub fn prepare(
+        &self,
+        values: &HashMap<String, SlotValue>,
+        data_types: &HashMap<Uuid, DataTypeRecord>,
+    ) -> Result<PreparedCall, String> {
+        let slots: Vec<&Slot> = self
+            .paths
+            .iter()
+            .filter(|p| p.data_type != CONST_TYPE)
+            .chain(&self.queries)
+            .chain(&self.headers)
+            .collect();
+        let mut problems = Vec::new();
+        let mut headers = Vec::new();
+
+        let mut unknown: Vec<&str> = values
+            .keys()
+            .filter(|name| !slots.iter().any(|s| &s.name == *name))
+            .map(String::as_str)
+            .collect();
+        unknown.sort_unstable();
+        for name in unknown {
+            problems.push(format!("{}: the request has no slot with this name", name));
+        }
+
+        for slot in &slots {
+            let given = match values.get(&slot.name) {
+                Some(SlotValue::Single(value)) => std::slice::from_ref(value),
+                Some(SlotValue::List(values)) => values.as_slice(),
+                None => continue,
+            };
+            let data_type = match data_types.get(&slot.data_type_id) {
+                Some(data_type) => data_type,
+                None => continue,
+            };
+            let primitive = Primitive::from_name(&data_type.primitive);
+
+            for value in given {
+                for violation in data_type.constraints.validate(primitive, value) {
+                    problems.push(format!("{}: {}", slot.name, violation.message));
+                }
+            }
+        }
+
+        for header in &self.headers {
+            let value = match values.get(&header.name) {
+                Some(SlotValue::Single(value)) => value,
+                Some(SlotValue::List(values)) if values.len() == 1 => &values[0],
+                Some(SlotValue::List(_)) => {
+                    problems.push(format!("header {} takes a single value", header.name));
+                    continue;
+                }
+                None => continue,
+            };
+
+            if value
+                .bytes()
+                .all(|b| b == b'\t' || (b' '..=b'~').contains(&b))
+            {
+                headers.push((header.name.clone(), value.clone()));
+            } else {
+                problems.push(format!(
+                    "{}: a header value can only hold visible ASCII characters, spaces and tabs",
+                    header.name
+                ));
+            }
+        }
+
+        let url = self
+            .api_get()
+            .instantiate(values, self.query_vec_style)
+            .map_err(|e| e.to_string());
+
+        match (url, problems.is_empty()) {
+            (Ok(url), true) => Ok(PreparedCall { url, headers }),
+            (Ok(_), false) => Err(problems.join("\n")),
+            (Err(e), _) => {
+                problems.push(e);
+                Err(problems.join("\n"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Constraints, QueryVecStyle};
+
+    fn slot(name: &str, data_type: &DataTypeRecord, is_vec: bool) -> Slot {
+        Slot {
+            name: name.to_string(),
+            data_type_id: data_type.id,
+            data_type: data_type.label.clone(),
+            is_vec,
+        }
+    }
+
+    fn data_type(label: &str, primitive: &str, constraints: Constraints) -> DataTypeRecord {
+        DataTypeRecord {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+            data_primitive_id: Uuid::new_v4(),
+            primitive: primitive.to_string(),
+            constraints,
+        }
+    }
+
+    fn request() -> (RequestDefinition, HashMap<Uuid, DataTypeRecord>) {
+        let constant = data_type("const", "const", Constraints::default());
+        let artist_id = data_type(
+            "spotify_artist_id",
+            "string",
+            Constraints {
+                pattern: Some("[0-9A-Za-z]{22}".to_string()),
+                ..Constraints::default()
+            },
+        );
+        let limit = data_type("int", "int", Constraints::default());
+        let token = data_type("string", "string", Constraints::default());
+        let request = RequestDefinition {
+            id: Uuid::new_v4(),
+            api_id: Uuid::new_v4(),
+            response_schema_id: Uuid::new_v4(),
+            description: "Get an artist's albums".to_string(),
+            query_vec_style: QueryVecStyle::Comma,
+            url: "https://api.spotify.com".to_string(),
+            vers: "v1".to_string(),
+            paths: vec![
+                slot("artists", &constant, false),
+                slot("id", &artist_id, false),
+                slot("albums", &constant, false),
+            ],
+            queries: vec![slot("limit", &limit, false), slot("include", &token, true)],
+            headers: vec![slot("Authorization", &token, false)],
+        };
+        let data_types = vec![constant, artist_id, limit, token]
+            .into_iter()
+            .map(|t| (t.id, t))
+            .collect();
+
+        (request, data_types)
+    }
+
+    fn values(pairs: &[(&str, SlotValue)]) -> HashMap<String, SlotValue> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn prepare_builds_the_url_and_headers() {
+        let (request, data_types) = request();
+        let values = values(&[
+            (
+                "id",
+                SlotValue::Single("0OdUWJ0sBjDrqHygGUXeCF".to_string()),
+            ),
+            ("limit", SlotValue::Single("10".to_string())),
+            (
+                "include",
+                SlotValue::List(vec!["album".to_string(), "single".to_string()]),
+            ),
+            (
+                "Authorization",
+                SlotValue::Single("Bearer token".to_string()),
+            ),
+        ]);
+
+        assert_eq!(
+            request.prepare(&values, &data_types),
+            Ok(PreparedCall {
+                url: "https://api.spotify.com/v1/artists/0OdUWJ0sBjDrqHygGUXeCF/albums?limit=10&include=album,single".to_string(),
+                headers: vec![("Authorization".to_string(), "Bearer token".to_string())],
+            })
+        );
+    }
+
+    #[test]
+    fn prepare_reports_every_problem() {
+        let (request, data_types) = request();
+        let values = values(&[
+            ("id", SlotValue::Single("radiohead".to_string())),
+            ("limit", SlotValue::Single("ten".to_string())),
+            ("market", SlotValue::Single("SE".to_string())),
+        ]);
+
+        assert_eq!(
+            request.prepare(&values, &data_types),
+            Err([
+                "market: the request has no slot with this name",
+                "id: radiohead does not match [0-9A-Za-z]{22}",
+                "limit: ten is not an integer",
+            ]
+            .join("\n"))
+        );
+    }
+
+    #[test]
+    fn prepare_rejects_header_values_that_cannot_be_sent() {
+        let (request, data_types) = request();
+        let values = values(&[
+            (
+                "id",
+                SlotValue::Single("0OdUWJ0sBjDrqHygGUXeCF".to_string()),
+            ),
+            (
+                "Authorization",
+                SlotValue::Single("Bearer token\r\nX-Injected: 1".to_string()),
+            ),
+        ]);
+
+        assert_eq!(
+            request.prepare(&values, &data_types),
+            Err("Authorization: a header value can only hold visible ASCII characters, spaces and tabs".to_string())
+        );
+    }
+
+    #[test]
+    fn prepare_rejects_a_list_for_a_header() {
+        let (request, data_types) = request();
+        let values = values(&[
+            (
+                "id",
+                SlotValue::Single("0OdUWJ0sBjDrqHygGUXeCF".to_string()),
+            ),
+            (
+                "Authorization",
+                SlotValue::List(vec!["Bearer a".to_string(), "Bearer b".to_string()]),
+            ),
+        ]);
+
+        assert_eq!(
+            request.prepare(&values, &data_types),
+            Err("header Authorization takes a single value".to_string())
+        );
+    }
+
+    #[test]
+    fn prepare_needs_every_path_slot() {
+        let (request, data_types) = request();
+
+        assert_eq!(
+            request.prepare(&HashMap::new(), &data_types),
+            Err("missing value for path parameter id".to_string())
+        );
+    }
+}
@@ -1,2 +1,30 @@
+mod api;
+mod base_url;
+mod constraints;
+mod data_type;
+mod execute;
+mod pagination;
+mod payload;
+mod plan;
 mod query;
+mod request;
+mod response_schema;
 mod service;
+
+pub use api::{parse_version, ApiPatch, ApiRecord, NewApi};
+pub use base_url::BaseUrl;
+pub use constraints::{Constraints, Primitive, Validation, Violation};
+pub use data_type::{
+    parse_label, DataPrimitiveRecord, DataTypePatch, DataTypeRecord, NewDataPrimitive, NewDataType,
+    SampleValue, UnknownTypes, BUILTIN_PRIMITIVES, CONST_TYPE, DEFAULT_PRIMITIVE,
+};
+pub use execute::{ExecuteInput, ExecuteOutput, PreparedCall};
+pub use pagination::{Page, Pagination};
+pub use payload::{check_payload, PayloadProblem, PayloadValidation};
+pub use plan::{plan, Plan, PlanQuery};
+pub use request::{QueryVecStyle, RequestDefinition, RequestSummary, Slot};
+pub use response_schema::{
+    find_loop, InferredSchema, NewSchemaLink, ResponseChild, ResponseField, ResponseSchema,
+    SchemaLink, SchemaSamples,
+};
+pub use service::{ServicePatch, ServiceRecord};
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PER_PAGE: i64 = 25;
+const MAX_PER_PAGE: i64 = 100;
+
+/// Query parameters accepted by list endpoints e.g.:
+/// `?page=2&per_page=50`
+///
+/// Pages start at 1. `per_page` is clamped to `1..=100`.
+#[derive(Deserialize, Debug, Default)]
+pub struct Pagination {
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+impl Pagination {
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> i64 {
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
+    }
+
+    /// Number of rows to skip, for use in a sql `offset`
+    pub fn offset(&self) -> i64 {
+        (self.page() - 1) * self.per_page()
+    }
+}
+
+/// A single page of results returned from a list endpoint
+#[derive(Serialize, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, pagination: &Pagination, total: i64) -> Self {
+        Page {
+            items,
+            page: pagination.page(),
+            per_page: pagination.per_page(),
+            total,
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::{DataTypeRecord, Primitive, ResponseSchema};
+
+/// A way a payload differs from its response schema, e.g.:
+/// `{ "pointer": "/images/0/height", "kind": "type", "message": "expected an integer, found a string" }`
+///
+/// `kind` is `missing`, `unexpected`, `null`, `type`, `is_vec`
+/// or the data type constraint that failed.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PayloadProblem {
+    /// JSON pointer to the offending value, or to the object missing a field
+    pub pointer: String,
+    pub kind: &'static str,
+    pub message: String,
+}
+
+/// Outcome of checking a payload against a response schema
+#[derive(Serialize, Debug)]
+pub struct PayloadValidation {
+    pub valid: bool,
+    pub problems: Vec<PayloadProblem>,
+}
+
+impl PayloadValidation {
+    pub fn new(problems: Vec<PayloadProblem>) -> PayloadValidation {
+        PayloadValidation {
+            valid: problems.is_empty(),
+            problems,
+        }
+    }
+}
+
+/// Every way `payload` differs from `schema`, in the order the schema lists its fields.
+///
+/// `data_types` holds the data types of the schema's labels. A payload that is an array
+/// is checked item by item, the way schemas of list responses are imported.
+pub fn check_payload(
+    schema: &ResponseSchema,
+    data_types: &HashMap<String, DataTypeRecord>,
+    payload: &Value,
+) -> Vec<PayloadProblem> {
+    let mut checker = Checker {
+        data_types,
+        problems: Vec::new(),
+    };
+
+    match payload {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                checker.object(schema, item, &format!("/{}", i));
+            }
+        }
+        payload => checker.object(schema, payload, ""),
+    }
+
+    checker.problems
+}
+
+struct Checker<'a> {
+    data_types: &'a HashMap<String, DataTypeRecord>,
+    problems: Vec<PayloadProblem>,
+}
+
+impl Checker<'_> {
+    fn object(&mut self, schema: &ResponseSchema, value: &Value, pointer: &str) {
+        let object = match value {
+            Value::Object(object) => object,
+            other => return self.wrong_type(pointer, "an object", other),
+        };
+
+        for field in &schema.data {
+            if let Some(value) = self.field(object, &field.identifier, field.is_vec, pointer) {
+                self.each(
+                    value,
+                    field.is_vec,
+                    &child(pointer, &field.identifier),
+                    |c, v, p| c.scalar(&field.data_type, v, p),
+                );
+            }
+        }
+
+        for nested in &schema.schemas {
+            if let Some(value) = self.field(object, &nested.identifier, nested.is_vec, pointer) {
+                self.each(
+                    value,
+                    nested.is_vec,
+                    &child(pointer, &nested.identifier),
+                    |c, v, p| c.object(&nested.schema, v, p),
+                );
+            }
+        }
+
+        for key in object.keys() {
+            let known = schema.data.iter().any(|f| &f.identifier == key)
+                || schema.schemas.iter().any(|c| &c.identifier == key);
+
+            if !known {
+                self.push(
+                    child(pointer, key),
+                    "unexpected",
+                    format!("{} is not in the schema", key),
+                );
+            }
+        }
+    }
+
+    /// The value of `identifier`, reporting it when it's missing or null
+    fn field<'v>(
+        &mut self,
+        object: &'v Map<String, Value>,
+        identifier: &str,
+        is_vec: bool,
+        pointer: &str,
+    ) -> Option<&'v Value> {
+        match object.get(identifier) {
+            None => {
+                self.push(
+                    pointer.to_string(),
+                    "missing",
+                    format!("{} is missing", identifier),
+                );
+                None
+            }
+            Some(Value::Null) => {
+                self.push(
+                    child(pointer, identifier),
+                    "null",
+                    format!("expected {}, found null", expected_shape(is_vec)),
+                );
+                None
+            }
+            Some(value) => Some(value),
+        }
+    }
+
+    /// Check a value, or each item of it for `is_vec` fields, with `check`
+    fn each(
+        &mut self,
+        value: &Value,
+        is_vec: bool,
+        pointer: &str,
+        check: impl Fn(&mut Self, &Value, &str),
+    ) {
+        match (value, is_vec) {
+            (Value::Array(items), true) => {
+                for (i, item) in items.iter().enumerate() {
+                    let pointer = format!("{}/{}", pointer, i);
+
+                    match item {
+                        Value::Null => {
+                            self.push(pointer, "null", "expected a value, found null".to_string())
+                        }
+                        item => check(self, item, &pointer),
+                    }
+                }
+            }
+            (Value::Array(_), false) => self.push(
+                pointer.to_string(),
+                "is_vec",
+                "expected a single value, found an array".to_string(),
+            ),
+            (value, true) => self.push(
+                pointer.to_string(),
+                "is_vec",
+                format!("expected an array, found {}", describe(value)),
+            ),
+            (value, false) => check(self, value, pointer),
+        }
+    }
+
+    fn scalar(&mut self, label: &str, value: &Value, pointer: &str) {
+        let data_type = self.data_types.get(label);
+        let primitive = data_type
+            .map(|t| Primitive::from_name(&t.primitive))
+            .unwrap_or(Primitive::String);
+
+        let text = match (primitive, value) {
+            (Primitive::Int, Value::Number(n)) if n.is_i64() || n.is_u64() => n.to_string(),
+            (Primitive::Float, Value::Number(n)) => n.to_string(),
+            (Primitive::Bool, Value::Bool(b)) => b.to_string(),
+            (primitive, Value::String(s))
+                if !primitive.is_numeric() && primitive != Primitive::Bool =>
+            {
+                s.clone()
+            }
+            (primitive, value) => return self.wrong_type(pointer, primitive.describe(), value),
+        };
+
+        for violation in data_type
+            .map(|t| t.constraints.validate(primitive, &text))
+            .unwrap_or_default()
+        {
+            let kind = match violation.constraint {
+                "primitive" => "type",
+                constraint => constraint,
+            };
+            self.push(pointer.to_string(), kind, violation.message);
+        }
+    }
+
+    fn wrong_type(&mut self, pointer: &str, expected: &str, found: &Value) {
+        self.push(
+            pointer.to_string(),
+            "type",
+            format!("expected {}, found {}", expected, describe(found)),
+        );
+    }
+
+    fn push(&mut self, pointer: String, kind: &'static str, message: String) {
+        self.problems.push(PayloadProblem {
+            pointer,
+            kind,
+            message,
+        });
+    }
+}
+
+/// `pointer` extended with `key`, escaped as RFC 6901 requires
+fn child(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn expected_shape(is_vec: bool) -> &'static str {
+    match is_vec {
+        true => "an array",
+        false => "a value",
+    }
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "true or false",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Constraints, ResponseChild, ResponseField};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn data_types() -> HashMap<String, DataTypeRecord> {
+        let data_type = |label: &str, primitive: &str, constraints| DataTypeRecord {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+            data_primitive_id: Uuid::new_v4(),
+            primitive: primitive.to_string(),
+            constraints,
+        };
+
+        vec![
+            data_type("string", "string", Constraints::default()),
+            data_type("int", "int", Constraints::default()),
+            data_type(
+                "popularity",
+                "int",
+                Constraints {
+                    min_value: Some(0.0),
+                    max_value: Some(100.0),
+                    ..Constraints::default()
+                },
+            ),
+        ]
+        .into_iter()
+        .map(|t| (t.label.clone(), t))
+        .collect()
+    }
+
+    fn artist() -> ResponseSchema {
+        let field = |identifier: &str, data_type: &str, is_vec| ResponseField {
+            identifier: identifier.to_string(),
+            data_type: data_type.to_string(),
+            is_vec,
+        };
+
+        ResponseSchema {
+            id: None,
+            description: "Artist".to_string(),
+            data: vec![
+                field("genres", "string", true),
+                field("name", "string", false),
+                field("popularity", "popularity", false),
+            ],
+            schemas: vec![ResponseChild {
+                identifier: "images".to_string(),
+                is_vec: true,
+                schema: ResponseSchema {
+                    id: None,
+                    description: "Image".to_string(),
+                    data: vec![
+                        field("height", "int", false),
+                        field("url/path", "string", false),
+                    ],
+                    schemas: Vec::new(),
+                },
+            }],
+        }
+    }
+
+    fn problems(payload: Value) -> Vec<(String, &'static str)> {
+        check_payload(&artist(), &data_types(), &payload)
+            .into_iter()
+            .map(|p| (p.pointer, p.kind))
+            .collect()
+    }
+
+    #[test]
+    fn a_matching_payload_has_no_problems() {
+        assert!(problems(json!({
+            "genres": ["rock"],
+            "name": "Radiohead",
+            "popularity": 80,
+            "images": [{"height": 640, "url/path": "a.jpg"}]
+        }))
+        .is_empty());
+    }
+
+    #[test]
+    fn every_problem_is_reported_with_its_pointer() {
+        assert_eq!(
+            problems(json!({
+                "genres": "rock",
+                "popularity": 101,
+                "images": [{"height": "640", "url/path": null}, null],
+                "followers": 10
+            })),
+            vec![
+                ("/genres".to_string(), "is_vec"),
+                ("".to_string(), "missing"),
+                ("/popularity".to_string(), "max_value"),
+                ("/images/0/height".to_string(), "type"),
+                ("/images/0/url~1path".to_string(), "null"),
+                ("/images/1".to_string(), "null"),
+                ("/followers".to_string(), "unexpected"),
+            ]
+        );
+    }
+
+    #[test]
+    fn array_payloads_are_checked_item_by_item() {
+        assert_eq!(
+            problems(json!([
+                {"genres": [], "name": "Radiohead", "popularity": 80, "images": []},
+                "Björk"
+            ])),
+            vec![("/1".to_string(), "type")]
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+use super::{RequestDefinition, ResponseSchema, BUILTIN_PRIMITIVES, CONST_TYPE};
+
+/// Longest chain of requests a plan is searched for
+pub const MAX_STEPS: usize = 6;
+/// Most sets of known data types a search visits before it gives up
+pub const MAX_STATES: usize = 10_000;
+
+/// Query parameters of a plan search, e.g.:
+/// `?have=spotify_artist_id&want=8c7f…`
+///
+/// `have` is a comma separated list of data type labels, `want` the id of a response schema.
+#[derive(Deserialize, Debug)]
+pub struct PlanQuery {
+    #[serde(default)]
+    pub have: String,
+    pub want: Uuid,
+}
+
+impl PlanQuery {
+    /// The labels in `have`, without blanks or repeats
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = Vec::new();
+
+        for label in self.have.split(',').map(str::trim) {
+            if !label.is_empty() && !labels.iter().any(|l| l == label) {
+                labels.push(label.to_string());
+            }
+        }
+
+        labels
+    }
+}
+
+/// The shortest chain of requests from the `have` data types to the `want` response schema
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Plan {
+    pub have: Vec<String>,
+    pub want: Uuid,
+    pub steps: Vec<PlanStep>,
+    /// Where the wanted schema sits in the response of the last step, e.g. `albums.items[]`.
+    /// Empty when it is the whole response.
+    pub found_at: String,
+}
+
+/// A request of a plan and where each of its parameters comes from
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PlanStep {
+    pub request_id: Uuid,
+    pub api_id: Uuid,
+    pub description: String,
+    pub bindings: Vec<Binding>,
+}
+
+/// A parameter of a step filled from a known value.
+/// Path parameters are always bound, queries and headers when a value is known.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Binding {
+    pub parameter: String,
+    #[serde(rename = "in")]
+    pub location: Location,
+    pub data_type: String,
+    pub source: Source,
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Location {
+    Path,
+    Query,
+    Header,
+}
+
+/// Where the value of a binding comes from
+#[derive(Serialize, Debug, PartialEq, Clone)]
+#[serde(tag = "from", rename_all = "snake_case")]
+pub enum Source {
+    /// One of the `have` data types
+    Have { data_type: String },
+    /// A field of the response of an earlier step, e.g. `artists.items[].id`
+    Step { step: usize, field: String },
+}
+
+/// Search for the fewest requests that lead from the `have` labels to the `want` schema.
+///
+/// A request can be made once a value of every data type in its path is known,
+/// its queries and headers are optional. Every custom data type in its response
+/// is known afterwards, a built-in one like `string` says nothing about what the
+/// value is for, so it never links two requests. The wanted schema is found in a
+/// response that is, or nests, it.
+///
+/// `schemas` holds the response schema of each request by id. Ties go to the
+/// request that comes first in `requests`. `None` when no chain of at most
+/// [`MAX_STEPS`] requests gets there within [`MAX_STATES`] sets of known data types.
+pub fn plan(
+    requests: &[RequestDefinition],
+    schemas: &HashMap<Uuid, ResponseSchema>,
+    have: &[String],
+    want: Uuid,
+) -> Option<Plan> {
+    let outputs: Vec<Vec<(&str, String)>> = requests
+        .iter()
+        .map(|r| {
+            schemas
+                .get(&r.response_schema_id)
+                .map(ResponseSchema::field_paths)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(label, _)| !BUILTIN_PRIMITIVES.contains(label))
+                .collect()
+        })
+        .collect();
+    let start: BTreeSet<&str> = have.iter().map(String::as_str).collect();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    seen.insert(start.clone());
+    queue.push_back((start, Vec::new()));
+
+    while let Some((known, chain)) = queue.pop_front() {
+        for (index, request) in requests.iter().enumerate() {
+            if !callable(request, &known, have) {
+                continue;
+            }
+
+            let mut chain: Vec<usize> = chain.clone();
+            chain.push(index);
+
+            if let Some(found_at) = schemas
+                .get(&request.response_schema_id)
+                .and_then(|s| s.path_to(want))
+            {
+                return Some(Plan {
+                    have: have.to_vec(),
+                    want,
+                    steps: steps(requests, &outputs, have, &chain),
+                    found_at,
+                });
+            }
+
+            let mut next = known.clone();
+            next.extend(outputs[index].iter().map(|(label, _)| *label));
+
+            if chain.len() < MAX_STEPS && seen.len() < MAX_STATES && seen.insert(next.clone()) {
+                queue.push_back((next, chain));
+            }
+        }
+    }
+
+    None
+}
+
+/// Whether a value of every data type in the path of `request` is known.
+/// A built-in data type in the path is only filled by one of the `have` data types.
+fn callable(request: &RequestDefinition, known: &BTreeSet<&str>, have: &[String]) -> bool {
+    request
+        .paths
+        .iter()
+        .filter(|p| p.data_type != CONST_TYPE)
+        .all(
+            |p| match BUILTIN_PRIMITIVES.contains(&p.data_type.as_str()) {
+                true => have.contains(&p.data_type),
+                false => known.contains(p.data_type.as_str()),
+            },
+        )
+}
+
+/// The bindings of each request of `chain`, from the first source of each data type
+fn steps(
+    requests: &[RequestDefinition],
+    outputs: &[Vec<(&str, String)>],
+    have: &[String],
+    chain: &[usize],
+) -> Vec<PlanStep> {
+    let mut sources: HashMap<&str, Source> = have
+        .iter()
+        .map(|label| {
+            (
+                label.as_str(),
+                Source::Have {
+                    data_type: label.clone(),
+                },
+            )
+        })
+        .collect();
+    let mut steps = Vec::new();
+
+    for (step, &index) in chain.iter().enumerate() {
+        let request = &requests[index];
+        let slots = request
+            .paths
+            .iter()
+            .filter(|p| p.data_type != CONST_TYPE)
+            .map(|p| (p, Location::Path))
+            .chain(request.queries.iter().map(|q| (q, Location::Query)))
+            .chain(request.headers.iter().map(|h| (h, Location::Header)));
+
+        steps.push(PlanStep {
+            request_id: request.id,
+            api_id: request.api_id,
+            description: request.description.clone(),
+            bindings: slots
+                .filter_map(|(slot, location)| {
+                    sources.get(slot.data_type.as_str()).map(|source| Binding {
+                        parameter: slot.name.clone(),
+                        location,
+                        data_type: slot.data_type.clone(),
+                        source: source.clone(),
+                    })
+                })
+                .collect(),
+        });
+
+        for (label, field) in &outputs[index] {
+            sources.entry(label).or_insert_with(|| Source::Step {
+                step,
+                field: field.clone(),
+            });
+        }
+    }
+
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{QueryVecStyle, ResponseChild, ResponseField, Slot};
+
+    fn slot(name: &str, data_type: &str) -> Slot {
+        Slot {
+            name: name.to_string(),
+            data_type_id: Uuid::new_v4(),
+            data_type: data_type.to_string(),
+            is_vec: false,
+        }
+    }
+
+    fn field(identifier: &str, data_type: &str, is_vec: bool) -> ResponseField {
+        ResponseField {
+            identifier: identifier.to_string(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    fn schema(description: &str, data: Vec<ResponseField>) -> ResponseSchema {
+        ResponseSchema {
+            id: Some(Uuid::new_v4()),
+            description: description.to_string(),
+            data,
+            schemas: Vec::new(),
+        }
+    }
+
+    fn request(description: &str, paths: Vec<Slot>, schema: &ResponseSchema) -> RequestDefinition {
+        RequestDefinition {
+            id: Uuid::new_v4(),
+            api_id: Uuid::nil(),
+            response_schema_id: schema.id.unwrap(),
+            description: description.to_string(),
+            query_vec_style: QueryVecStyle::Repeat,
+            url: "https://api.spotify.com".to_string(),
+            vers: "v1".to_string(),
+            paths,
+            queries: Vec::new(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Artist -> albums of the artist -> tracks of an album
+    struct Spotify {
+        requests: Vec<RequestDefinition>,
+        schemas: HashMap<Uuid, ResponseSchema>,
+        album: Uuid,
+        track: Uuid,
+    }
+
+    fn spotify() -> Spotify {
+        let artist = schema(
+            "Artist",
+            vec![
+                field("id", "spotify_artist_id", false),
+                field("name", "string", false),
+            ],
+        );
+        let album = schema("Album", vec![field("id", "spotify_album_id", false)]);
+        let albums = ResponseSchema {
+            schemas: vec![ResponseChild {
+                identifier: "items".to_string(),
+                is_vec: true,
+                schema: album.clone(),
+            }],
+            ..schema("Albums", vec![field("total", "int", false)])
+        };
+        let track = schema(
+            "Track",
+            vec![
+                field("id", "spotify_track_id", false),
+                field("artists", "spotify_artist_id", true),
+            ],
+        );
+        let mut artist_albums = request(
+            "Get an artist's albums",
+            vec![
+                slot("artists", "const"),
+                slot("id", "spotify_artist_id"),
+                slot("albums", "const"),
+            ],
+            &albums,
+        );
+        artist_albums.queries = vec![slot("market", "market"), slot("limit", "int")];
+
+        Spotify {
+            requests: vec![
+                request(
+                    "Get an album's tracks",
+                    vec![
+                        slot("albums", "const"),
+                        slot("id", "spotify_album_id"),
+                        slot("tracks", "const"),
+                    ],
+                    &track,
+                ),
+                artist_albums,
+                request(
+                    "Get an artist",
+                    vec![slot("artists", "const"), slot("id", "spotify_artist_id")],
+                    &artist,
+                ),
+            ],
+            album: album.id.unwrap(),
+            track: track.id.unwrap(),
+            schemas: vec![artist, albums, track]
+                .into_iter()
+                .map(|s| (s.id.unwrap(), s))
+                .collect(),
+        }
+    }
+
+    fn have(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn outputs_of_a_step_fill_the_parameters_of_the_next() {
+        let spotify = spotify();
+        let plan = plan(
+            &spotify.requests,
+            &spotify.schemas,
+            &have(&["spotify_artist_id"]),
+            spotify.track,
+        )
+        .unwrap();
+
+        assert_eq!(
+            plan.steps
+                .iter()
+                .map(|s| s.description.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Get an artist's albums", "Get an album's tracks"]
+        );
+        assert_eq!(
+            plan.steps[0].bindings,
+            vec![Binding {
+                parameter: "id".to_string(),
+                location: Location::Path,
+                data_type: "spotify_artist_id".to_string(),
+                source: Source::Have {
+                    data_type: "spotify_artist_id".to_string()
+                },
+            }]
+        );
+        assert_eq!(
+            plan.steps[1].bindings[0].source,
+            Source::Step {
+                step: 0,
+                field: "items[].id".to_string()
+            }
+        );
+        assert_eq!(plan.found_at, "");
+    }
+
+    #[test]
+    fn known_values_fill_optional_queries() {
+        let spotify = spotify();
+        let plan = plan(
+            &spotify.requests,
+            &spotify.schemas,
+            &have(&["spotify_artist_id", "market"]),
+            spotify.album,
+        )
+        .unwrap();
+
+        assert_eq!(plan.steps.len(), 1);
+        assert_eq!(
+            plan.steps[0]
+                .bindings
+                .iter()
+                .map(|b| (b.parameter.as_str(), b.location))
+                .collect::<Vec<_>>(),
+            vec![("id", Location::Path), ("market", Location::Query)]
+        );
+        assert_eq!(plan.found_at, "items[]");
+    }
+
+    #[test]
+    fn no_plan_without_a_way_in() {
+        let spotify = spotify();
+
+        assert_eq!(
+            plan(
+                &spotify.requests,
+                &spotify.schemas,
+                &have(&["spotify_playlist_id"]),
+                spotify.track,
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn built_in_data_types_do_not_link_requests() {
+        let mut spotify = spotify();
+        let results = schema("Results", vec![field("total", "int", false)]);
+        spotify.requests.push(request(
+            "Search artists by name",
+            vec![slot("search", "const"), slot("name", "string")],
+            &results,
+        ));
+        spotify.schemas.insert(results.id.unwrap(), results.clone());
+
+        assert_eq!(
+            plan(
+                &spotify.requests,
+                &spotify.schemas,
+                &have(&["spotify_artist_id"]),
+                results.id.unwrap(),
+            ),
+            None
+        );
+        assert_eq!(
+            plan(
+                &spotify.requests,
+                &spotify.schemas,
+                &have(&["string"]),
+                results.id.unwrap(),
+            )
+            .map(|plan| plan.steps.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn have_is_split_on_commas() {
+        let query = PlanQuery {
+            have: " spotify_artist_id,market,,spotify_artist_id".to_string(),
+            want: Uuid::nil(),
+        };
+
+        assert_eq!(query.labels(), vec!["spotify_artist_id", "market"]);
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::parsers::header::HeaderParam;
+use crate::parsers::url::{ApiGet, PathParam, QueryParam};
+
+/// How a vector query is serialised into a url.
+///
+/// Stored in `daysquare.request.query_vec_style`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryVecStyle {
+    /// `ids=1&ids=2`
+    Repeat,
+    /// `ids=1,2`
+    Comma,
+    /// `ids[]=1&ids[]=2`
+    Brackets,
+}
+
+impl Default for QueryVecStyle {
+    fn default() -> Self {
+        QueryVecStyle::Repeat
+    }
+}
+
+impl QueryVecStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryVecStyle::Repeat => "repeat",
+            QueryVecStyle::Comma => "comma",
+            QueryVecStyle::Brackets => "brackets",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<QueryVecStyle, String> {
+        match s {
+            "repeat" => Ok(QueryVecStyle::Repeat),
+            "comma" => Ok(QueryVecStyle::Comma),
+            "brackets" => Ok(QueryVecStyle::Brackets),
+            other => Err(format!("{} is not a query vector style", other)),
+        }
+    }
+}
+
+/// A stored request with its api and path, query and header data.
+#[derive(Debug, Clone)]
+pub struct RequestDefinition {
+    pub id: Uuid,
+    pub api_id: Uuid,
+    pub response_schema_id: Uuid,
+    pub description: String,
+    pub query_vec_style: QueryVecStyle,
+    pub url: String,
+    pub vers: String,
+    /// In path sequence order
+    pub paths: Vec<Slot>,
+    pub queries: Vec<Slot>,
+    pub headers: Vec<Slot>,
+}
+
+/// A path segment, query or header of a stored request
+#[derive(Debug, Clone)]
+pub struct Slot {
+    pub name: String,
+    pub data_type_id: Uuid,
+    pub data_type: String,
+    pub is_vec: bool,
+}
+
+impl RequestDefinition {
+    /// The request as if it was parsed from DSL text
+    pub fn api_get(&self) -> ApiGet<'_> {
+        ApiGet {
+            url: &self.url,
+            ver: &self.vers,
+            paths: self
+                .paths
+                .iter()
+                .map(|p| PathParam {
+                    name: &p.name,
+                    data_type: &p.data_type,
+                })
+                .collect(),
+            queries: non_empty(&self.queries, |q| QueryParam {
+                name: &q.name,
+                data_type: &q.data_type,
+                is_vec: q.is_vec,
+            }),
+            headers: non_empty(&self.headers, |h| HeaderParam {
+                name: &h.name,
+                data_type: &h.data_type,
+            }),
+        }
+    }
+}
+
+fn non_empty<'a, T>(slots: &'a [Slot], f: impl Fn(&'a Slot) -> T) -> Option<Vec<T>> {
+    match slots.is_empty() {
+        true => None,
+        false => Some(slots.iter().map(f).collect()),
+    }
+}
+
+/// A stored request as returned by the request endpoints
+#[derive(Serialize, Debug)]
+pub struct RequestSummary {
+    pub id: Uuid,
+    pub api_id: Uuid,
+    pub response_schema_id: Uuid,
+    pub description: String,
+    pub query_vec_style: QueryVecStyle,
+    /// The request in the url DSL
+    pub dsl: String,
+}
+
+impl From<&RequestDefinition> for RequestSummary {
+    fn from(request: &RequestDefinition) -> Self {
+        RequestSummary {
+            id: request.id,
+            api_id: request.api_id,
+            response_schema_id: request.response_schema_id,
+            description: request.description.clone(),
+            query_vec_style: request.query_vec_style,
+            dsl: request.api_get().to_dsl(),
+        }
+    }
+}
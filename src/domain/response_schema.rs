@@ -0,0 +1,343 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+use super::parse_label;
+
+/// Nested description of a response e.g.:
+/// ```json
+/// {
+///   "description": "Artist",
+///   "data": [{ "identifier": "name", "data_type": "string" }],
+///   "schemas": [{
+///     "identifier": "images",
+///     "is_vec": true,
+///     "schema": { "data": [{ "identifier": "url", "data_type": "string" }] }
+///   }]
+/// }
+/// ```
+///
+/// `id` is only filled in on schemas read back from the database
+/// and is ignored when storing one. Fields are read back ordered by identifier.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ResponseSchema {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub data: Vec<ResponseField>,
+    #[serde(default)]
+    pub schemas: Vec<ResponseChild>,
+}
+
+/// A field holding a data type, referred to by label
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ResponseField {
+    pub identifier: String,
+    pub data_type: String,
+    #[serde(default)]
+    pub is_vec: bool,
+}
+
+/// A field holding a nested schema
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ResponseChild {
+    pub identifier: String,
+    #[serde(default)]
+    pub is_vec: bool,
+    pub schema: ResponseSchema,
+}
+
+impl ResponseSchema {
+    /// Check every identifier and data type label in the tree.
+    /// Identifiers have to be unique among the fields of a schema.
+    pub fn parse(self) -> Result<ResponseSchema, String> {
+        self.check("")?;
+        Ok(self)
+    }
+
+    fn check(&self, path: &str) -> Result<(), String> {
+        let mut seen = HashSet::new();
+
+        let identifiers = self
+            .data
+            .iter()
+            .map(|d| &d.identifier)
+            .chain(self.schemas.iter().map(|s| &s.identifier));
+
+        for identifier in identifiers {
+            if identifier.trim().is_empty() {
+                return Err(format!("{}: field identifier cannot be empty", at(path)));
+            }
+
+            if !seen.insert(identifier) {
+                return Err(format!(
+                    "{}: duplicate field identifier {}",
+                    at(path),
+                    identifier
+                ));
+            }
+        }
+
+        for field in &self.data {
+            parse_label(field.data_type.clone())
+                .map_err(|e| format!("{}: {}", join(path, &field.identifier), e))?;
+        }
+
+        for child in &self.schemas {
+            child.schema.check(&join(path, &child.identifier))?;
+        }
+
+        Ok(())
+    }
+
+    /// Every data type label used in the tree
+    pub fn labels(&self) -> Vec<&str> {
+        let mut labels = Vec::new();
+        let mut stack = vec![self];
+
+        while let Some(schema) = stack.pop() {
+            labels.extend(schema.data.iter().map(|d| d.data_type.as_str()));
+            stack.extend(schema.schemas.iter().map(|c| &c.schema));
+        }
+
+        labels
+    }
+
+    /// Every field in the tree with its data type label and path,
+    /// shallowest first, e.g. `("spotify_album_id", "items[].id")`
+    pub fn field_paths(&self) -> Vec<(&str, String)> {
+        let mut fields = Vec::new();
+        let mut queue = VecDeque::from(vec![(self, String::new())]);
+
+        while let Some((schema, path)) = queue.pop_front() {
+            for field in &schema.data {
+                fields.push((
+                    field.data_type.as_str(),
+                    item(join(&path, &field.identifier), field.is_vec),
+                ));
+            }
+            for child in &schema.schemas {
+                queue.push_back((
+                    &child.schema,
+                    item(join(&path, &child.identifier), child.is_vec),
+                ));
+            }
+        }
+
+        fields
+    }
+
+    /// Path to the shallowest schema in the tree with id `id`, empty for the root
+    pub fn path_to(&self, id: Uuid) -> Option<String> {
+        let mut queue = VecDeque::from(vec![(self, String::new())]);
+
+        while let Some((schema, path)) = queue.pop_front() {
+            if schema.id == Some(id) {
+                return Some(path);
+            }
+            for child in &schema.schemas {
+                queue.push_back((
+                    &child.schema,
+                    item(join(&path, &child.identifier), child.is_vec),
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+/// Body of a request inferring a schema from example responses
+#[derive(Deserialize, Debug)]
+pub struct SchemaSamples {
+    #[serde(default)]
+    pub description: String,
+    /// Objects, or arrays of objects, whose shapes are merged
+    pub samples: Vec<serde_json::Value>,
+}
+
+/// A schema guessed from samples, ready to be edited and stored
+#[derive(Serialize, Debug)]
+pub struct InferredSchema {
+    pub schema: ResponseSchema,
+    /// Parts of the samples the schema leaves out
+    pub warnings: Vec<String>,
+}
+
+/// Body of a request nesting an existing schema under another one
+#[derive(Deserialize, Debug)]
+pub struct NewSchemaLink {
+    pub identifier: String,
+    #[serde(default)]
+    pub is_vec: bool,
+    pub schema_id: Uuid,
+}
+
+/// A row of `daysquare.response_schema_data`: `child` is nested in `parent` as `identifier`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaLink {
+    pub parent: Uuid,
+    pub child: Uuid,
+    pub identifier: String,
+}
+
+/// The loop adding `link` to `links` would create, starting with `link` itself
+/// and ending with the link back to `link.parent`. `None` if there is no loop.
+pub fn find_loop<'a>(links: &'a [SchemaLink], link: &'a SchemaLink) -> Option<Vec<&'a SchemaLink>> {
+    let mut reached_by: HashMap<Uuid, &SchemaLink> = HashMap::new();
+    let mut queue = VecDeque::from(vec![link.child]);
+    let mut path;
+    let mut id;
+
+    // breadth first so the shortest loop is reported
+    while let Some(schema) = queue.pop_front() {
+        if schema == link.parent {
+            break;
+        }
+
+        for next in links.iter().filter(|l| l.parent == schema) {
+            if next.child != link.child && !reached_by.contains_key(&next.child) {
+                reached_by.insert(next.child, next);
+                queue.push_back(next.child);
+            }
+        }
+    }
+
+    if link.child != link.parent && !reached_by.contains_key(&link.parent) {
+        return None;
+    }
+
+    path = Vec::new();
+    id = link.parent;
+    while id != link.child {
+        let previous = reached_by[&id];
+
+        path.push(previous);
+        id = previous.parent;
+    }
+    path.push(link);
+    path.reverse();
+
+    Some(path)
+}
+
+/// Dotted path of a field e.g. `artist.images.url`
+fn join(path: &str, identifier: &str) -> String {
+    match path {
+        "" => identifier.to_string(),
+        _ => format!("{}.{}", path, identifier),
+    }
+}
+
+/// `path[]` for vectors
+fn item(path: String, is_vec: bool) -> String {
+    match is_vec {
+        true => path + "[]",
+        false => path,
+    }
+}
+
+fn at(path: &str) -> &str {
+    match path {
+        "" => "response schema",
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artist() -> ResponseSchema {
+        serde_json::from_str(
+            r#"{
+                "description": "Artist",
+                "data": [{ "identifier": "name", "data_type": "string" }],
+                "schemas": [{
+                    "identifier": "images",
+                    "is_vec": true,
+                    "schema": { "data": [{ "identifier": "url", "data_type": "string" }] }
+                }]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn nested_schema_is_accepted() {
+        let schema = artist().parse().unwrap();
+
+        assert_eq!(schema.schemas[0].schema.data[0].identifier, "url");
+        assert!(schema.schemas[0].is_vec);
+        assert!(!schema.data[0].is_vec);
+        assert_eq!(schema.labels(), ["string", "string"]);
+    }
+
+    #[test]
+    fn duplicate_identifiers_are_rejected_with_their_path() {
+        let mut schema = artist();
+        schema.schemas[0].schema.data.push(ResponseField {
+            identifier: "url".to_string(),
+            data_type: "int".to_string(),
+            is_vec: false,
+        });
+
+        assert_eq!(
+            schema.parse().unwrap_err(),
+            "images: duplicate field identifier url"
+        );
+    }
+
+    #[test]
+    fn a_field_and_a_child_cannot_share_an_identifier() {
+        let mut schema = artist();
+        schema.data[0].identifier = "images".to_string();
+
+        assert!(schema.parse().is_err());
+    }
+
+    fn link(parent: Uuid, child: Uuid, identifier: &str) -> SchemaLink {
+        SchemaLink {
+            parent,
+            child,
+            identifier: identifier.to_string(),
+        }
+    }
+
+    #[test]
+    fn loops_are_found() {
+        let (artist, album, track, image) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let links = vec![
+            link(artist, image, "images"),
+            link(album, image, "images"),
+            link(album, track, "tracks"),
+            link(track, artist, "artist"),
+        ];
+        let new = link(artist, album, "albums");
+        let found: Vec<_> = find_loop(&links, &new)
+            .unwrap()
+            .into_iter()
+            .map(|l| l.identifier.as_str())
+            .collect();
+
+        assert_eq!(found, ["albums", "tracks", "artist"]);
+        assert!(find_loop(&links, &link(artist, track, "top_tracks")).is_some());
+        assert!(find_loop(&links, &link(track, image, "cover")).is_none());
+        assert!(find_loop(&[], &link(image, image, "thumbnail")).is_some());
+    }
+
+    #[test]
+    fn bad_labels_are_rejected() {
+        let mut schema = artist();
+        schema.schemas[0].schema.data[0].data_type = "{url}".to_string();
+
+        assert!(schema.parse().unwrap_err().starts_with("images.url: "));
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A row of `daysquare.service`.
+///
+/// Same shape as `daysquare_shared::Service` plus the id
+/// it was stored under.
+#[derive(Serialize, Debug)]
+pub struct ServiceRecord {
+    pub id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub url: String,
+}
+
+/// Partial update of a service. Fields left out are unchanged.
+#[derive(Deserialize, Debug)]
+pub struct ServicePatch {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+}
@@ -0,0 +1,209 @@
+use axum::http::{header, HeaderValue, Response, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde_json::{json, Map, Value};
+use sqlx::postgres::PgDatabaseError;
+use std::fmt;
+use thiserror::Error;
+
+use super::constraint::{self, Constraint};
+use super::debug::error_chain_fmt;
+use crate::tracelog::RequestId;
+
+// sqlstate codes from https://www.postgresql.org/docs/current/errcodes-appendix.html
+const UNIQUE_VIOLATION: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+const CHECK_VIOLATION: &str = "23514";
+const SERIALIZATION_FAILURE: &str = "40001";
+
+// https://datatracker.ietf.org/doc/html/rfc7807#section-3
+const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Error returned by request handlers.
+///
+/// Rendered as an RFC 7807 `application/problem+json` body.
+/// Database errors are inspected when converted with `From<sqlx::Error>`
+/// so violations of the constraints in `migrations/` are reported
+/// to the client rather than as an internal error.
+#[derive(Error)]
+pub enum AppError {
+    #[error("{0}")]
+    Validation(String),
+    #[error("{resource} not found")]
+    NotFound { resource: &'static str },
+    #[error("{message}")]
+    Conflict {
+        resource: &'static str,
+        field: &'static str,
+        message: String,
+    },
+    #[error("{0}")]
+    Parse(String),
+    #[error("failed to execute query")]
+    Database(#[source] sqlx::Error),
+    #[error("upstream request failed: {0}")]
+    Upstream(#[source] reqwest::Error),
+    #[error("upstream response is larger than {limit} bytes")]
+    UpstreamTooLarge { limit: usize },
+}
+
+impl fmt::Debug for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl AppError {
+    pub fn not_found(resource: &'static str) -> Self {
+        AppError::NotFound { resource }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation(message.into())
+    }
+
+    /// Whether a concurrent transaction made this one fail
+    /// and running it again may succeed.
+    pub fn is_serialization_failure(&self) -> bool {
+        match self {
+            AppError::Database(sqlx::Error::Database(db_error)) => {
+                db_error.code().as_deref() == Some(SERIALIZATION_FAILURE)
+            }
+            _ => false,
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Parse(_) => StatusCode::BAD_REQUEST,
+            AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Upstream(e) if e.is_timeout() => StatusCode::GATEWAY_TIMEOUT,
+            AppError::Upstream(_) | AppError::UpstreamTooLarge { .. } => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    /// Problem details object.
+    ///
+    /// Uses `about:blank` as the problem type so `title` is the
+    /// status' reason phrase, with specifics in `detail` and
+    /// extension members.
+    fn problem(&self) -> Value {
+        let status = self.status();
+        let mut problem = Map::new();
+
+        problem.insert("type".into(), json!("about:blank"));
+        problem.insert("title".into(), json!(status.canonical_reason()));
+        problem.insert("status".into(), json!(status.as_u16()));
+        // Database errors only ever say "failed to execute query",
+        // the cause chain is logged instead of sent to the client
+        problem.insert("detail".into(), json!(self.to_string()));
+
+        match self {
+            AppError::NotFound { resource } => {
+                problem.insert("resource".into(), json!(resource));
+            }
+            AppError::Conflict {
+                resource, field, ..
+            } => {
+                problem.insert("resource".into(), json!(resource));
+                problem.insert("field".into(), json!(field));
+            }
+            AppError::Database(_) => {
+                problem.insert(
+                    "request_id".into(),
+                    json!(RequestId::current().map(|id| id.to_string())),
+                );
+            }
+            AppError::Validation(_)
+            | AppError::Parse(_)
+            | AppError::Upstream(_)
+            | AppError::UpstreamTooLarge { .. } => (),
+        }
+
+        Value::Object(problem)
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        let db_error;
+        let constraint;
+
+        db_error = match &e {
+            sqlx::Error::Database(db_error) => db_error.try_downcast_ref::<PgDatabaseError>(),
+            _ => None,
+        };
+        constraint = db_error
+            .and_then(|db_error| db_error.constraint())
+            .and_then(constraint::lookup);
+
+        match (db_error, constraint) {
+            (Some(db_error), Some(Constraint::Unique { resource, field }))
+                if db_error.code() == UNIQUE_VIOLATION =>
+            {
+                AppError::Conflict {
+                    resource,
+                    field,
+                    message: format!("a {} with this {} already exists", resource, field),
+                }
+            }
+            (Some(db_error), Some(Constraint::ForeignKey { resource, field }))
+                if db_error.code() == FOREIGN_KEY_VIOLATION =>
+            {
+                // The detail names the table and key values, so it is only logged
+                tracing::info!(detail = ?db_error.detail(), "Foreign key violation");
+
+                AppError::Conflict {
+                    resource,
+                    field,
+                    message: match db_error.detail() {
+                        Some(detail) if detail.contains("still referenced") => {
+                            format!("a {} still refers to it through {}", resource, field)
+                        }
+                        _ => format!("{} {} does not refer to an existing row", resource, field),
+                    },
+                }
+            }
+            (Some(db_error), Some(Constraint::Check { resource, field }))
+                if db_error.code() == CHECK_VIOLATION =>
+            {
+                AppError::Conflict {
+                    resource,
+                    field,
+                    message: db_error.message().to_string(),
+                }
+            }
+            (Some(db_error), Some(Constraint::Invalid)) if db_error.code() == CHECK_VIOLATION => {
+                AppError::Validation(db_error.message().to_string())
+            }
+            _ => AppError::Database(e),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    type Body = <Json<Value> as IntoResponse>::Body;
+    type BodyError = <Json<Value> as IntoResponse>::BodyError;
+
+    fn into_response(self) -> Response<Self::Body> {
+        let mut response;
+
+        if let AppError::Database(_) = &self {
+            tracing::error!(
+                request_id = %RequestId::current().map(|id| id.to_string()).unwrap_or_default(),
+                "{:?}",
+                self
+            );
+        }
+
+        response = Json(self.problem()).into_response();
+        *response.status_mut() = self.status();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
@@ -0,0 +1,183 @@
+/// A database constraint that can be reported back to the client
+/// instead of surfacing as an internal error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Constraint {
+    /// A unique constraint. `field` is the column that tells
+    /// the conflicting rows apart from the client's point of view.
+    Unique {
+        resource: &'static str,
+        field: &'static str,
+    },
+    /// A foreign key. `resource` is the referencing table
+    /// and `field` the referencing column.
+    ForeignKey {
+        resource: &'static str,
+        field: &'static str,
+    },
+    /// A check constraint or a trigger raising a check violation.
+    /// `field` is the column the check is about.
+    Check {
+        resource: &'static str,
+        field: &'static str,
+    },
+    /// A trigger raising a check violation for a request the api
+    /// itself rejects as invalid, so it is reported the same way.
+    Invalid,
+}
+
+/// Look up a constraint by the name postgres gave it in `migrations/`
+pub fn lookup(name: &str) -> Option<Constraint> {
+    use Constraint::*;
+
+    let constraint = match name {
+        // unique constraints
+        "service_url_key" => Unique {
+            resource: "service",
+            field: "url",
+        },
+        "api_service_id_url_vers_key" | "api_service_id_vers_key" => Unique {
+            resource: "api",
+            field: "vers",
+        },
+        "data_primitive_primitive_key" => Unique {
+            resource: "data_primitive",
+            field: "primitive",
+        },
+        "data_type_data_primitive_id_label_key" | "data_type_label_key" => Unique {
+            resource: "data_type",
+            field: "label",
+        },
+        "response_data_response_schema_id_identifier_key" => Unique {
+            resource: "response_data",
+            field: "identifier",
+        },
+        "response_schema_data_parent_response_schema_id_identifier_key" => Unique {
+            resource: "response_schema_data",
+            field: "identifier",
+        },
+        "path_data_request_id_sequence_key" => Unique {
+            resource: "path_data",
+            field: "sequence",
+        },
+        "query_data_request_id_name_key" => Unique {
+            resource: "query_data",
+            field: "name",
+        },
+        "header_data_request_id_name_key" => Unique {
+            resource: "header_data",
+            field: "name",
+        },
+
+        // foreign keys
+        "api_service_id_fkey" => ForeignKey {
+            resource: "api",
+            field: "service_id",
+        },
+        "data_type_data_primitive_id_fkey" => ForeignKey {
+            resource: "data_type",
+            field: "data_primitive_id",
+        },
+        "response_data_response_schema_id_fkey" => ForeignKey {
+            resource: "response_data",
+            field: "response_schema_id",
+        },
+        "response_data_data_type_id_fkey" => ForeignKey {
+            resource: "response_data",
+            field: "data_type_id",
+        },
+        "response_schema_data_parent_response_schema_id_fkey" => ForeignKey {
+            resource: "response_schema_data",
+            field: "parent_response_schema_id",
+        },
+        "response_schema_data_child_response_schema_id_fkey" => ForeignKey {
+            resource: "response_schema_data",
+            field: "child_response_schema_id",
+        },
+        "request_api_id_fkey" => ForeignKey {
+            resource: "request",
+            field: "api_id",
+        },
+        "request_response_schema_id_fkey" => ForeignKey {
+            resource: "request",
+            field: "response_schema_id",
+        },
+        "path_data_request_id_fkey" => ForeignKey {
+            resource: "path_data",
+            field: "request_id",
+        },
+        "path_data_data_type_id_fkey" => ForeignKey {
+            resource: "path_data",
+            field: "data_type_id",
+        },
+        "query_data_request_id_fkey" => ForeignKey {
+            resource: "query_data",
+            field: "request_id",
+        },
+        "query_data_data_type_id_fkey" => ForeignKey {
+            resource: "query_data",
+            field: "data_type_id",
+        },
+        "header_data_request_id_fkey" => ForeignKey {
+            resource: "header_data",
+            field: "request_id",
+        },
+        "header_data_data_type_id_fkey" => ForeignKey {
+            resource: "header_data",
+            field: "data_type_id",
+        },
+
+        // checks
+        "data_type_min_length_check" | "data_type_max_length_check" | "data_type_length_check" => {
+            Check {
+                resource: "data_type",
+                field: "min_length",
+            }
+        }
+        "data_type_value_check" => Check {
+            resource: "data_type",
+            field: "min_value",
+        },
+        "data_type_enum_values_check" => Check {
+            resource: "data_type",
+            field: "enum_values",
+        },
+        "response_schema_data_loop_check" => Invalid,
+        _ => return None,
+    };
+
+    Some(constraint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_known_constraints() {
+        assert!(
+            lookup("service_url_key")
+                == Some(Constraint::Unique {
+                    resource: "service",
+                    field: "url"
+                })
+        );
+        assert!(
+            lookup("api_service_id_fkey")
+                == Some(Constraint::ForeignKey {
+                    resource: "api",
+                    field: "service_id"
+                })
+        );
+    }
+
+    #[test]
+    fn lookup_loop_check_as_invalid() {
+        assert!(lookup("response_schema_data_loop_check") == Some(Constraint::Invalid));
+    }
+
+    #[test]
+    fn lookup_unknown_constraint() {
+        assert!(lookup("service_pkey").is_none());
+        assert!(lookup("").is_none());
+    }
+}
@@ -1 +1,5 @@
+mod app;
+mod constraint;
 pub mod debug;
+
+pub use app::AppError;
@@ -0,0 +1,279 @@
+//! Requests and the data types they consume and produce, as a bipartite graph.
+//!
+//! A request consumes the data types of its path segments, queries and headers
+//! and produces those in its response schema, so a data type produced by one
+//! request and consumed by another links the two.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use uuid::Uuid;
+
+use super::path_template;
+use crate::domain::{RequestDefinition, ResponseSchema, CONST_TYPE};
+
+#[derive(Serialize, Debug)]
+pub struct Graph {
+    pub requests: Vec<RequestNode>,
+    /// Labels of the data types with an edge, ordered
+    pub data_types: Vec<String>,
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RequestNode {
+    pub id: Uuid,
+    pub api_id: Uuid,
+    pub description: String,
+    /// e.g. `/artists/{id}/albums`
+    pub path: String,
+}
+
+/// One edge per request, data type and direction
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Edge {
+    pub request: Uuid,
+    pub data_type: String,
+    pub direction: Direction,
+    /// Slot names of a consumed type, field paths of a produced one, e.g. `items[].id`
+    pub via: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Consumes,
+    Produces,
+}
+
+/// The graph of `requests`. `schemas` holds the response schema of each request by id.
+pub fn graph(requests: &[RequestDefinition], schemas: &HashMap<Uuid, ResponseSchema>) -> Graph {
+    let mut data_types = BTreeSet::new();
+    let mut edges = Vec::new();
+
+    for request in requests {
+        let mut via: BTreeMap<(Direction, &str), Vec<String>> = BTreeMap::new();
+
+        for slot in request
+            .paths
+            .iter()
+            .filter(|p| p.data_type != CONST_TYPE)
+            .chain(&request.queries)
+            .chain(&request.headers)
+        {
+            via.entry((Direction::Consumes, &slot.data_type))
+                .or_default()
+                .push(slot.name.clone());
+        }
+        for (label, path) in schemas
+            .get(&request.response_schema_id)
+            .map(ResponseSchema::field_paths)
+            .unwrap_or_default()
+        {
+            via.entry((Direction::Produces, label))
+                .or_default()
+                .push(path);
+        }
+
+        for ((direction, label), via) in via {
+            data_types.insert(label.to_string());
+            edges.push(Edge {
+                request: request.id,
+                data_type: label.to_string(),
+                direction,
+                via,
+            });
+        }
+    }
+
+    Graph {
+        requests: requests
+            .iter()
+            .map(|r| RequestNode {
+                id: r.id,
+                api_id: r.api_id,
+                description: r.description.clone(),
+                path: path_template(r),
+            })
+            .collect(),
+        data_types: data_types.into_iter().collect(),
+        edges,
+    }
+}
+
+/// The graph in Graphviz DOT, requests as boxes and data types as ellipses.
+/// Edges point from a consumed type to its request and from a request to a produced type.
+pub fn dot(graph: &Graph) -> String {
+    let mut out = String::new();
+
+    out.push_str("digraph catalogue {\n    rankdir=LR;\n\n    node [shape=box];\n");
+    for request in &graph.requests {
+        out.push_str(&format!(
+            "    {} [label={}];\n",
+            quoted(&format!("request:{}", request.id)),
+            quoted(&format!("{}\n{}", request.description, request.path))
+        ));
+    }
+
+    out.push_str("\n    node [shape=ellipse];\n");
+    for label in &graph.data_types {
+        out.push_str(&format!(
+            "    {};\n",
+            quoted(&format!("data_type:{}", label))
+        ));
+    }
+
+    out.push('\n');
+    for edge in &graph.edges {
+        let request = quoted(&format!("request:{}", edge.request));
+        let data_type = quoted(&format!("data_type:{}", edge.data_type));
+        let (from, to) = match edge.direction {
+            Direction::Consumes => (data_type, request),
+            Direction::Produces => (request, data_type),
+        };
+        out.push_str(&format!(
+            "    {} -> {} [label={}];\n",
+            from,
+            to,
+            quoted(&edge.via.join(", "))
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// A DOT string literal, newlines become line breaks in a label
+fn quoted(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{QueryVecStyle, ResponseChild, ResponseField, Slot};
+
+    fn slot(name: &str, data_type: &str) -> Slot {
+        Slot {
+            name: name.to_string(),
+            data_type_id: Uuid::new_v4(),
+            data_type: data_type.to_string(),
+            is_vec: false,
+        }
+    }
+
+    fn field(identifier: &str, data_type: &str) -> ResponseField {
+        ResponseField {
+            identifier: identifier.to_string(),
+            data_type: data_type.to_string(),
+            is_vec: false,
+        }
+    }
+
+    fn albums() -> (RequestDefinition, HashMap<Uuid, ResponseSchema>) {
+        let schema = ResponseSchema {
+            id: Some(Uuid::new_v4()),
+            description: "Albums".to_string(),
+            data: vec![field("total", "int")],
+            schemas: vec![ResponseChild {
+                identifier: "items".to_string(),
+                is_vec: true,
+                schema: ResponseSchema {
+                    id: Some(Uuid::new_v4()),
+                    description: "Album".to_string(),
+                    data: vec![field("id", "spotify_album_id"), field("name", "string")],
+                    schemas: Vec::new(),
+                },
+            }],
+        };
+        let request = RequestDefinition {
+            id: Uuid::new_v4(),
+            api_id: Uuid::nil(),
+            response_schema_id: schema.id.unwrap(),
+            description: "Get an artist's \"albums\"".to_string(),
+            query_vec_style: QueryVecStyle::Repeat,
+            url: "https://api.spotify.com".to_string(),
+            vers: "v1".to_string(),
+            paths: vec![
+                slot("artists", "const"),
+                slot("id", "spotify_artist_id"),
+                slot("albums", "const"),
+            ],
+            queries: vec![slot("limit", "int"), slot("offset", "int")],
+            headers: Vec::new(),
+        };
+
+        (
+            request,
+            vec![(schema.id.unwrap(), schema)].into_iter().collect(),
+        )
+    }
+
+    #[test]
+    fn edges_join_slots_and_fields_of_a_data_type() {
+        let (request, schemas) = albums();
+        let graph = graph(std::slice::from_ref(&request), &schemas);
+
+        assert_eq!(graph.requests[0].path, "/artists/{id}/albums");
+        assert_eq!(
+            graph.data_types,
+            vec!["int", "spotify_album_id", "spotify_artist_id", "string"]
+        );
+        assert_eq!(
+            graph.edges[..2],
+            [
+                Edge {
+                    request: request.id,
+                    data_type: "int".to_string(),
+                    direction: Direction::Consumes,
+                    via: vec!["limit".to_string(), "offset".to_string()],
+                },
+                Edge {
+                    request: request.id,
+                    data_type: "spotify_artist_id".to_string(),
+                    direction: Direction::Consumes,
+                    via: vec!["id".to_string()],
+                },
+            ]
+        );
+        assert!(graph.edges.contains(&Edge {
+            request: request.id,
+            data_type: "spotify_album_id".to_string(),
+            direction: Direction::Produces,
+            via: vec!["items[].id".to_string()],
+        }));
+    }
+
+    #[test]
+    fn dot_points_from_inputs_to_outputs() {
+        let (request, schemas) = albums();
+        let dot = dot(&graph(std::slice::from_ref(&request), &schemas));
+        let node = format!("\"request:{}\"", request.id);
+
+        assert!(dot.starts_with("digraph catalogue {\n"));
+        assert!(dot.contains(&format!(
+            "    {} [label=\"Get an artist's \\\"albums\\\"\\n/artists/{{id}}/albums\"];",
+            node
+        )));
+        assert!(dot.contains(&format!(
+            "    \"data_type:spotify_artist_id\" -> {} [label=\"id\"];",
+            node
+        )));
+        assert!(dot.contains(&format!(
+            "    {} -> \"data_type:spotify_album_id\" [label=\"items[].id\"];",
+            node
+        )));
+        assert!(dot.ends_with("}\n"));
+    }
+}
@@ -0,0 +1,193 @@
+//! Conversion of catalogue entries into third party api descriptions.
+//!
+//! `store::load_api_export` reads everything stored about an API version
+//! into an [`ApiExport`], which each format turns into a document
+//! without touching the database.
+
+pub mod graph;
+pub mod openapi;
+pub mod rust;
+pub mod typescript;
+
+use std::collections::HashSet;
+
+use crate::domain::{
+    ApiRecord, DataTypeRecord, RequestDefinition, ResponseSchema, ServiceRecord, CONST_TYPE,
+};
+
+/// An API version with everything its requests refer to
+#[derive(Debug)]
+pub struct ApiExport {
+    pub service: ServiceRecord,
+    pub api: ApiRecord,
+    pub requests: Vec<RequestDefinition>,
+    /// Data types used by the requests and their response schemas, ordered by label
+    pub data_types: Vec<DataTypeRecord>,
+    /// The response schema of each request, read back with their ids
+    pub response_schemas: Vec<ResponseSchema>,
+}
+
+/// Turn `name` into an identifier made of ASCII letters, digits and `_`.
+/// Returns an empty string if nothing is left.
+fn identifier(name: &str) -> String {
+    let mut identifier = String::new();
+
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            identifier.push(c);
+        } else if !identifier.is_empty() && !identifier.ends_with('_') {
+            identifier.push('_');
+        }
+    }
+
+    identifier.trim_end_matches('_').to_string()
+}
+
+/// The request path with typed segments as `{name}` templates
+fn path_template(request: &RequestDefinition) -> String {
+    let segments: Vec<String> = request
+        .paths
+        .iter()
+        .map(|p| match p.data_type.as_str() {
+            CONST_TYPE => p.name.clone(),
+            _ => format!("{{{}}}", p.name),
+        })
+        .collect();
+
+    format!("/{}", segments.join("/"))
+}
+
+/// A literal path segment, percent-encoded the way generated clients do it at runtime
+fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// `text` on a single line
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `name`, or `fallback` when nothing is left of it
+fn or(name: String, fallback: &str) -> String {
+    match name.is_empty() {
+        true => fallback.to_string(),
+        false => name,
+    }
+}
+
+/// The lowercase words of `name`, split at anything but letters
+/// and digits and where the case changes, e.g. `HTTPServer_v2` gives
+/// `http`, `server`, `v2`
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+
+    for part in identifier(name).split('_').filter(|p| !p.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+
+        for (i, &c) in chars.iter().enumerate() {
+            let starts_word = i > 0
+                && c.is_ascii_uppercase()
+                && (!chars[i - 1].is_ascii_uppercase()
+                    || matches!(chars.get(i + 1), Some(n) if n.is_ascii_lowercase()));
+
+            if starts_word {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c.to_ascii_lowercase());
+        }
+        words.push(word);
+    }
+
+    words
+}
+
+/// `name` as an upper camel case type name
+fn pascal_case(name: &str, fallback: &str) -> String {
+    let name: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    match name.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("{}{}", fallback, name),
+        false => or(name, fallback),
+    }
+}
+
+/// Hands out names that haven't been given out yet,
+/// adding `_2`, `_3`... to a taken one
+#[derive(Debug, Default)]
+struct Names {
+    taken: HashSet<String>,
+}
+
+impl Names {
+    fn claim(&mut self, name: String) -> String {
+        self.claim_joined(name, "_")
+    }
+
+    /// Claim `name`, joining it to the number that makes it unique with `separator`
+    fn claim_joined(&mut self, name: String, separator: &str) -> String {
+        let mut candidate = name.clone();
+        let mut n = 1;
+
+        while self.taken.contains(&candidate) {
+            n += 1;
+            candidate = format!("{}{}{}", name, separator, n);
+        }
+
+        self.taken.insert(candidate.clone());
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_keeps_letters_and_digits() {
+        assert_eq!(
+            identifier("Get an artist's albums"),
+            "Get_an_artist_s_albums"
+        );
+        assert_eq!(identifier("  GET /v1/me  "), "GET_v1_me");
+        assert_eq!(identifier("--"), "");
+    }
+
+    #[test]
+    fn names_are_handed_out_once() {
+        let mut names = Names::default();
+
+        assert_eq!(names.claim("Artist".to_string()), "Artist");
+        assert_eq!(names.claim("Artist".to_string()), "Artist_2");
+        assert_eq!(names.claim("Artist".to_string()), "Artist_3");
+        assert_eq!(names.claim_joined("Artist".to_string(), ""), "Artist2");
+    }
+
+    #[test]
+    fn words_split_at_case_changes() {
+        assert_eq!(words("HTTPServer_v2"), ["http", "server", "v2"]);
+        assert_eq!(words("externalUrls"), ["external", "urls"]);
+        assert_eq!(
+            words("Get an artist's albums"),
+            ["get", "an", "artist", "s", "albums"]
+        );
+        assert!(words("--").is_empty());
+    }
+}
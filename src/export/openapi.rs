@@ -0,0 +1,505 @@
+//! OpenAPI 3.1 documents.
+//!
+//! The api's base url and version make up the only server, so
+//! `https://api.spotify.com|v1/artists/{id,spotify_artist_id}` becomes the
+//! path `/artists/{id}` of the server `https://api.spotify.com/v1`.
+//! Data types and response schemas become component schemas that
+//! parameters and responses refer to.
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{identifier, or, path_template, ApiExport, Names};
+use crate::domain::{
+    DataTypeRecord, Primitive, QueryVecStyle, RequestDefinition, ResponseSchema, CONST_TYPE,
+};
+
+const OPENAPI_VERSION: &str = "3.1.0";
+
+/// Build the document describing an API version
+pub fn document(export: &ApiExport) -> Value {
+    let mut components = Components::default();
+    let mut operation_ids = Names::default();
+    let mut paths = Map::new();
+    let mut skipped = Vec::new();
+
+    for data_type in &export.data_types {
+        components.add_data_type(data_type);
+    }
+    for schema in &export.response_schemas {
+        components.add_response_schema(schema);
+    }
+
+    for request in &export.requests {
+        let path = path_template(request);
+
+        if paths.contains_key(&path) {
+            skipped.push(json!({
+                "id": request.id,
+                "description": request.description,
+                "reason": format!("GET {} is already described by another request", path),
+            }));
+            continue;
+        }
+
+        let operation = operation(request, &components, &mut operation_ids);
+        paths.insert(path, json!({ "get": operation }));
+    }
+
+    let mut document = json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": export.service.title,
+            "description": export.service.description,
+            "version": export.api.vers,
+        },
+        "servers": [{ "url": format!("{}/{}", export.api.url, export.api.vers) }],
+        "paths": paths,
+        "components": { "schemas": components.schemas },
+    });
+
+    // OpenAPI has one operation per path and method,
+    // requests differing only in their queries or headers can't all be described
+    if !skipped.is_empty() {
+        document["x-skipped-requests"] = Value::Array(skipped);
+    }
+
+    document
+}
+
+/// Component schemas and the names they were given
+#[derive(Default)]
+struct Components {
+    names: Names,
+    /// Component name of each data type label
+    data_types: HashMap<String, String>,
+    /// Component name of each response schema id
+    response_schemas: HashMap<Uuid, String>,
+    schemas: Map<String, Value>,
+}
+
+impl Components {
+    fn add_data_type(&mut self, data_type: &DataTypeRecord) {
+        let name = self
+            .names
+            .claim(or(identifier(&data_type.label), "DataType"));
+
+        self.schemas
+            .insert(name.clone(), data_type_schema(data_type));
+        self.data_types.insert(data_type.label.clone(), name);
+    }
+
+    /// Add `schema` and the schemas nested in it, each once.
+    /// Children are added first so they are named before their parents.
+    fn add_response_schema(&mut self, schema: &ResponseSchema) {
+        if let Some(id) = schema.id {
+            if self.response_schemas.contains_key(&id) {
+                return;
+            }
+        }
+
+        for child in &schema.schemas {
+            self.add_response_schema(&child.schema);
+        }
+
+        if let Some(id) = schema.id {
+            let name = self
+                .names
+                .claim(or(identifier(&schema.description), "Schema"));
+            let object = self.object(schema);
+
+            self.schemas.insert(name.clone(), object);
+            self.response_schemas.insert(id, name);
+        }
+    }
+
+    fn object(&self, schema: &ResponseSchema) -> Value {
+        let mut properties = Map::new();
+
+        for field in &schema.data {
+            properties.insert(
+                field.identifier.clone(),
+                vec_of(self.data_type_ref(&field.data_type), field.is_vec),
+            );
+        }
+        for child in &schema.schemas {
+            properties.insert(
+                child.identifier.clone(),
+                vec_of(self.response_schema_ref(&child.schema), child.is_vec),
+            );
+        }
+
+        let mut object = json!({ "type": "object", "properties": properties });
+        if !schema.description.is_empty() {
+            object["description"] = json!(schema.description);
+        }
+
+        object
+    }
+
+    fn data_type_ref(&self, label: &str) -> Value {
+        match self.data_types.get(label) {
+            Some(name) => reference(name),
+            // every label of a stored request refers to a data type
+            None => json!({ "type": "string" }),
+        }
+    }
+
+    /// A reference to a schema that was added, or the schema itself
+    fn response_schema_ref(&self, schema: &ResponseSchema) -> Value {
+        match schema.id.and_then(|id| self.response_schemas.get(&id)) {
+            Some(name) => reference(name),
+            None => self.object(schema),
+        }
+    }
+}
+
+fn operation(
+    request: &RequestDefinition,
+    components: &Components,
+    operation_ids: &mut Names,
+) -> Value {
+    let mut parameters = Vec::new();
+
+    for path in request.paths.iter().filter(|p| p.data_type != CONST_TYPE) {
+        parameters.push(json!({
+            "name": path.name,
+            "in": "path",
+            "required": true,
+            "schema": components.data_type_ref(&path.data_type),
+        }));
+    }
+
+    for query in &request.queries {
+        let mut parameter = json!({
+            "name": query.name,
+            "in": "query",
+            "schema": vec_of(components.data_type_ref(&query.data_type), query.is_vec),
+        });
+
+        if query.is_vec {
+            match request.query_vec_style {
+                QueryVecStyle::Repeat => parameter["explode"] = json!(true),
+                QueryVecStyle::Comma => parameter["explode"] = json!(false),
+                QueryVecStyle::Brackets => {
+                    parameter["name"] = json!(format!("{}[]", query.name));
+                    parameter["explode"] = json!(true);
+                }
+            }
+            parameter["style"] = json!("form");
+        }
+
+        parameters.push(parameter);
+    }
+
+    for header in &request.headers {
+        parameters.push(json!({
+            "name": header.name,
+            "in": "header",
+            "schema": components.data_type_ref(&header.data_type),
+        }));
+    }
+
+    let response = match components.response_schemas.get(&request.response_schema_id) {
+        Some(name) => json!({
+            "description": "OK",
+            "content": { "application/json": { "schema": reference(name) } },
+        }),
+        None => json!({ "description": "OK" }),
+    };
+
+    json!({
+        "operationId": operation_ids.claim(or(identifier(&request.description), "request")),
+        "summary": request.description,
+        "parameters": parameters,
+        "responses": { "200": response },
+    })
+}
+
+/// JSON Schema of a data type with its constraints
+fn data_type_schema(data_type: &DataTypeRecord) -> Value {
+    let primitive = Primitive::from_name(&data_type.primitive);
+    let constraints = &data_type.constraints;
+    let mut schema = match primitive {
+        Primitive::Int => json!({ "type": "integer" }),
+        Primitive::Float => json!({ "type": "number" }),
+        Primitive::Bool => json!({ "type": "boolean" }),
+        Primitive::Uuid => json!({ "type": "string", "format": "uuid" }),
+        Primitive::Datetime => json!({ "type": "string", "format": "date-time" }),
+        Primitive::String | Primitive::Const => json!({ "type": "string" }),
+    };
+
+    schema["title"] = json!(data_type.label);
+
+    // a JSON Schema pattern matches anywhere in the value, ours the whole value
+    if let Some(pattern) = &constraints.pattern {
+        schema["pattern"] = json!(format!("^(?:{})$", pattern));
+    }
+    if let Some(min_length) = constraints.min_length {
+        schema["minLength"] = json!(min_length);
+    }
+    if let Some(max_length) = constraints.max_length {
+        schema["maxLength"] = json!(max_length);
+    }
+    if let Some(min_value) = constraints.min_value {
+        schema["minimum"] = number(primitive, min_value);
+    }
+    if let Some(max_value) = constraints.max_value {
+        schema["maximum"] = number(primitive, max_value);
+    }
+    if let Some(enum_values) = &constraints.enum_values {
+        schema["enum"] = enum_values
+            .iter()
+            .map(|value| enum_value(primitive, value))
+            .collect();
+    }
+
+    schema
+}
+
+/// A bound written as an integer for integer types
+fn number(primitive: Primitive, value: f64) -> Value {
+    match primitive {
+        Primitive::Int if value.fract() == 0.0 => json!(value as i64),
+        _ => json!(value),
+    }
+}
+
+/// An enum value as the JSON value of its primitive.
+/// Enum values have been checked to suit the primitive when they were stored.
+fn enum_value(primitive: Primitive, value: &str) -> Value {
+    let parsed = match primitive {
+        Primitive::Int => value.parse::<i64>().ok().map(Value::from),
+        Primitive::Float => value.parse::<f64>().ok().map(Value::from),
+        Primitive::Bool => value.parse::<bool>().ok().map(Value::from),
+        _ => None,
+    };
+
+    parsed.unwrap_or_else(|| json!(value))
+}
+
+fn vec_of(schema: Value, is_vec: bool) -> Value {
+    match is_vec {
+        true => json!({ "type": "array", "items": schema }),
+        false => schema,
+    }
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        ApiRecord, Constraints, ResponseChild, ResponseField, ServiceRecord, Slot,
+    };
+
+    fn data_type(label: &str, primitive: &str, constraints: Constraints) -> DataTypeRecord {
+        DataTypeRecord {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+            data_primitive_id: Uuid::new_v4(),
+            primitive: primitive.to_string(),
+            constraints,
+        }
+    }
+
+    fn slot(name: &str, data_type: &str, is_vec: bool) -> Slot {
+        Slot {
+            name: name.to_string(),
+            data_type_id: Uuid::new_v4(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    fn field(identifier: &str, data_type: &str, is_vec: bool) -> ResponseField {
+        ResponseField {
+            identifier: identifier.to_string(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    fn spotify() -> ApiExport {
+        let api_id = Uuid::new_v4();
+        let artist = ResponseSchema {
+            id: Some(Uuid::new_v4()),
+            description: "Artist".to_string(),
+            data: vec![
+                field("genres", "string", true),
+                field("name", "string", false),
+            ],
+            schemas: vec![ResponseChild {
+                identifier: "images".to_string(),
+                is_vec: true,
+                schema: ResponseSchema {
+                    id: Some(Uuid::new_v4()),
+                    description: "Image".to_string(),
+                    data: vec![field("height", "int", false)],
+                    schemas: Vec::new(),
+                },
+            }],
+        };
+        let request = |description: &str, paths, queries| RequestDefinition {
+            id: Uuid::new_v4(),
+            api_id,
+            response_schema_id: artist.id.unwrap(),
+            description: description.to_string(),
+            query_vec_style: QueryVecStyle::Comma,
+            url: "https://api.spotify.com".to_string(),
+            vers: "v1".to_string(),
+            paths,
+            queries,
+            headers: vec![slot("Authorization", "spotify_token", false)],
+        };
+
+        ApiExport {
+            service: ServiceRecord {
+                id: Uuid::new_v4(),
+                title: "Spotify".to_string(),
+                description: "Music streaming".to_string(),
+                url: "https://api.spotify.com".to_string(),
+            },
+            api: ApiRecord {
+                id: api_id,
+                service_id: Uuid::new_v4(),
+                url: "https://api.spotify.com".to_string(),
+                vers: "v1".to_string(),
+            },
+            requests: vec![
+                request(
+                    "Get an artist",
+                    vec![
+                        slot("artists", "const", false),
+                        slot("id", "spotify_artist_id", false),
+                    ],
+                    vec![slot("market", "country_code", true)],
+                ),
+                request(
+                    "Get an artist again",
+                    vec![
+                        slot("artists", "const", false),
+                        slot("id", "spotify_artist_id", false),
+                    ],
+                    Vec::new(),
+                ),
+            ],
+            data_types: vec![
+                data_type(
+                    "country_code",
+                    "string",
+                    Constraints {
+                        enum_values: Some(vec!["NL".to_string(), "SE".to_string()]),
+                        ..Constraints::default()
+                    },
+                ),
+                data_type("int", "int", Constraints::default()),
+                data_type(
+                    "spotify_artist_id",
+                    "string",
+                    Constraints {
+                        pattern: Some("[0-9A-Za-z]{22}".to_string()),
+                        ..Constraints::default()
+                    },
+                ),
+                data_type("spotify_token", "string", Constraints::default()),
+                data_type("string", "string", Constraints::default()),
+            ],
+            response_schemas: vec![artist],
+        }
+    }
+
+    #[test]
+    fn document_describes_requests_as_paths() {
+        let document = document(&spotify());
+        let get = &document["paths"]["/artists/{id}"]["get"];
+
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(document["servers"][0]["url"], "https://api.spotify.com/v1");
+        assert_eq!(get["operationId"], "Get_an_artist");
+        assert_eq!(
+            get["parameters"],
+            json!([
+                {
+                    "name": "id",
+                    "in": "path",
+                    "required": true,
+                    "schema": {"$ref": "#/components/schemas/spotify_artist_id"}
+                },
+                {
+                    "name": "market",
+                    "in": "query",
+                    "schema": {
+                        "type": "array",
+                        "items": {"$ref": "#/components/schemas/country_code"}
+                    },
+                    "style": "form",
+                    "explode": false
+                },
+                {
+                    "name": "Authorization",
+                    "in": "header",
+                    "schema": {"$ref": "#/components/schemas/spotify_token"}
+                }
+            ])
+        );
+        assert_eq!(
+            get["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({"$ref": "#/components/schemas/Artist"})
+        );
+        assert_eq!(
+            document["x-skipped-requests"][0]["description"],
+            "Get an artist again"
+        );
+    }
+
+    #[test]
+    fn data_types_keep_their_constraints() {
+        let document = document(&spotify());
+        let schemas = &document["components"]["schemas"];
+
+        assert_eq!(
+            schemas["spotify_artist_id"]["pattern"],
+            "^(?:[0-9A-Za-z]{22})$"
+        );
+        assert_eq!(schemas["country_code"]["enum"], json!(["NL", "SE"]));
+        assert_eq!(schemas["int"]["type"], "integer");
+    }
+
+    #[test]
+    fn nested_schemas_become_components() {
+        let document = document(&spotify());
+        let schemas = &document["components"]["schemas"];
+
+        assert_eq!(
+            schemas["Artist"]["properties"]["images"],
+            json!({"type": "array", "items": {"$ref": "#/components/schemas/Image"}})
+        );
+        assert_eq!(
+            schemas["Image"]["properties"]["height"],
+            json!({"$ref": "#/components/schemas/int"})
+        );
+    }
+
+    #[test]
+    fn enum_values_and_bounds_follow_the_primitive() {
+        let schema = data_type_schema(&data_type(
+            "limit",
+            "int",
+            Constraints {
+                min_value: Some(1.0),
+                max_value: Some(50.0),
+                enum_values: Some(vec!["10".to_string(), "20".to_string()]),
+                ..Constraints::default()
+            },
+        ));
+
+        assert_eq!(schema["minimum"], json!(1));
+        assert_eq!(schema["maximum"], json!(50));
+        assert_eq!(schema["enum"], json!([10, 20]));
+    }
+}
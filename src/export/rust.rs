@@ -0,0 +1,677 @@
+//! Rust client modules.
+//!
+//! Every custom data type becomes a newtype over the Rust type of its
+//! primitive, every response schema a serde struct and every request a
+//! method of `Client`, so `https://api.spotify.com|v1/artists/{id,spotify_artist_id}`
+//! becomes `Client::get_an_artist(&self, id: &SpotifyArtistId)`.
+//! The module only needs `reqwest`, with its `json` feature, and `serde`.
+
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{encode_segment, one_line, or, pascal_case, path_template, words, ApiExport, Names};
+use crate::domain::{
+    DataTypeRecord, Primitive, QueryVecStyle, RequestDefinition, ResponseSchema, Slot,
+    BUILTIN_PRIMITIVES, CONST_TYPE,
+};
+
+const KEYWORDS: [&str; 51] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Names the module uses for itself, or that would shadow the prelude
+const RESERVED_TYPES: [&str; 6] = ["Client", "Option", "Result", "String", "Vec", "Value"];
+/// Associated functions `CLIENT` defines
+const RESERVED_METHODS: [&str; 2] = ["new", "with_base_url"];
+/// Locals of the generated methods
+const RESERVED_ARGUMENTS: [&str; 4] = ["query", "request", "self", "url"];
+
+const DERIVES: &str = "#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]";
+
+/// Build the source of a module calling an API version
+pub fn module(export: &ApiExport) -> String {
+    let mut types = Types::default();
+    let mut methods = Names::default();
+    let mut out = String::new();
+    let mut helpers = Helpers::default();
+    let mut client = String::new();
+
+    for name in RESERVED_TYPES.iter() {
+        types.names.claim(name.to_string());
+    }
+    for name in RESERVED_METHODS.iter() {
+        methods.claim(name.to_string());
+    }
+    for data_type in &export.data_types {
+        types.add_data_type(data_type);
+    }
+    for schema in &export.response_schemas {
+        types.add_response_schema(schema);
+    }
+
+    for request in &export.requests {
+        client.push('\n');
+        client.push_str(&method(request, &types, &mut methods, &mut helpers));
+    }
+
+    out.push_str(&format!(
+        "//! Client for {} {}, generated by daysquare.\n",
+        one_line(&export.service.title),
+        one_line(&export.api.vers)
+    ));
+    out.push_str("//!\n//! Needs `reqwest` with its `json` feature and `serde` with `derive`.\n\n");
+    out.push_str(&format!(
+        "pub const BASE_URL: &str = {:?};\n",
+        format!("{}/{}", export.api.url, export.api.vers)
+    ));
+
+    for item in &types.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+
+    out.push_str(CLIENT);
+    out.push_str(&client);
+    out.push_str("}\n");
+
+    if helpers.segment {
+        out.push_str(SEGMENT);
+    }
+    if helpers.join {
+        out.push_str(JOIN);
+    }
+
+    out
+}
+
+const CLIENT: &str = r#"
+/// Calls the API with a `reqwest::Client`
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Client {
+    pub fn new(http: reqwest::Client) -> Client {
+        Client::with_base_url(http, BASE_URL)
+    }
+
+    /// A client calling another server, e.g. a mock
+    pub fn with_base_url(http: reqwest::Client, base_url: impl Into<String>) -> Client {
+        Client {
+            http,
+            base_url: base_url.into(),
+        }
+    }
+"#;
+
+const SEGMENT: &str = r#"
+/// `value` as a percent-encoded path segment
+fn segment(value: impl std::fmt::Display) -> String {
+    let mut encoded = String::new();
+
+    for byte in value.to_string().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+"#;
+
+const JOIN: &str = r#"
+/// `values` separated by commas
+fn join(values: &[impl std::fmt::Display]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+"#;
+
+/// Helper functions the methods use
+#[derive(Default)]
+struct Helpers {
+    segment: bool,
+    join: bool,
+}
+
+/// Type definitions and the names they were given
+#[derive(Default)]
+struct Types {
+    names: Names,
+    /// Rust type of each data type label
+    data_types: HashMap<String, RustType>,
+    /// Struct name of each response schema id
+    response_schemas: HashMap<Uuid, String>,
+    items: Vec<String>,
+}
+
+/// A type a value is held in
+#[derive(Clone)]
+struct RustType {
+    name: String,
+    /// Borrowed as `&str` rather than `&String`
+    is_string: bool,
+}
+
+impl Types {
+    /// Built-in data types are plain Rust types, others are newtypes
+    fn add_data_type(&mut self, data_type: &DataTypeRecord) {
+        let primitive = Primitive::from_name(&data_type.primitive);
+        let inner = primitive_type(primitive);
+
+        if BUILTIN_PRIMITIVES.contains(&data_type.label.as_str())
+            && data_type.label == data_type.primitive
+        {
+            self.data_types
+                .insert(data_type.label.clone(), inner.clone());
+            return;
+        }
+
+        let name = self.claim_type(&data_type.label, "DataType");
+        self.items.push(format!(
+            "{doc}{derives}\n#[serde(transparent)]\npub struct {name}(pub {inner});\n\n\
+             impl std::fmt::Display for {name} {{\n    \
+             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{\n        \
+             std::fmt::Display::fmt(&self.0, f)\n    }}\n}}\n",
+            doc = doc("", &data_type_doc(data_type)),
+            derives = DERIVES,
+            name = name,
+            inner = inner.name,
+        ));
+        self.data_types.insert(
+            data_type.label.clone(),
+            RustType {
+                name,
+                is_string: false,
+            },
+        );
+    }
+
+    /// Add `schema` and the schemas nested in it, each once.
+    /// Children are added first so structs are named before their parents.
+    fn add_response_schema(&mut self, schema: &ResponseSchema) {
+        let id = match schema.id {
+            Some(id) if !self.response_schemas.contains_key(&id) => id,
+            _ => return,
+        };
+
+        for child in &schema.schemas {
+            self.add_response_schema(&child.schema);
+        }
+
+        let name = self.claim_type(&schema.description, "Schema");
+        let item = self.response_struct(&name, schema);
+
+        self.items.push(item);
+        self.response_schemas.insert(id, name);
+    }
+
+    fn response_struct(&self, name: &str, schema: &ResponseSchema) -> String {
+        let mut fields = Names::default();
+        let mut item = doc("", &schema.description);
+
+        item.push_str(&format!("{}\npub struct {} {{\n", DERIVES, name));
+
+        let children = schema.schemas.iter().map(|c| {
+            let child = c
+                .schema
+                .id
+                .and_then(|id| self.response_schemas.get(&id))
+                .cloned()
+                .unwrap_or_else(|| "serde_json::Value".to_string());
+            (&c.identifier, child, c.is_vec)
+        });
+
+        for (identifier, rust_type, is_vec) in schema
+            .data
+            .iter()
+            .map(|f| (&f.identifier, self.label_type(&f.data_type).name, f.is_vec))
+            .chain(children)
+        {
+            let field = fields.claim(snake_case(identifier, "field"));
+
+            if &field != identifier {
+                item.push_str(&format!("    #[serde(rename = {:?})]\n", identifier));
+            }
+            item.push_str(&format!(
+                "    pub {}: {},\n",
+                field,
+                vec_of(rust_type, is_vec)
+            ));
+        }
+
+        item.push_str("}\n");
+        item
+    }
+
+    fn label_type(&self, label: &str) -> RustType {
+        self.data_types
+            .get(label)
+            .cloned()
+            // every label of a stored request refers to a data type
+            .unwrap_or_else(|| primitive_type(Primitive::String))
+    }
+
+    fn claim_type(&mut self, name: &str, fallback: &str) -> String {
+        let name = pascal_case(name, fallback);
+        self.names.claim_joined(name, "")
+    }
+}
+
+fn method(
+    request: &RequestDefinition,
+    types: &Types,
+    methods: &mut Names,
+    helpers: &mut Helpers,
+) -> String {
+    let mut arguments = Names::default();
+    let mut parameters = vec!["&self".to_string()];
+    let mut segments = Vec::new();
+    let mut values = vec!["self.base_url".to_string()];
+    let mut body = Vec::new();
+    let name = methods.claim(snake_case(&request.description, "request"));
+    let response = types
+        .response_schemas
+        .get(&request.response_schema_id)
+        .cloned()
+        .unwrap_or_else(|| "serde_json::Value".to_string());
+
+    for reserved in RESERVED_ARGUMENTS.iter() {
+        arguments.claim(reserved.to_string());
+    }
+
+    for path in &request.paths {
+        if path.data_type == CONST_TYPE {
+            segments.push(encode_segment(&path.name));
+            continue;
+        }
+
+        let argument = arguments.claim(snake_case(&path.name, "path"));
+        let rust_type = types.label_type(&path.data_type);
+
+        parameters.push(format!("{}: {}", argument, borrowed(&rust_type)));
+        segments.push("{}".to_string());
+        values.push(format!("segment({})", argument));
+        helpers.segment = true;
+    }
+
+    let url = match segments.is_empty() {
+        true => "self.base_url.as_str()".to_string(),
+        false => format!(
+            "format!(\"{{}}/{}\", {})",
+            segments.join("/"),
+            values.join(", ")
+        ),
+    };
+    body.push(format!("let url = {};", url));
+
+    if !request.queries.is_empty() {
+        body.push("let mut query: Vec<(&str, String)> = Vec::new();".to_string());
+    }
+    for query in &request.queries {
+        let argument = arguments.claim(snake_case(&query.name, "query"));
+
+        parameters.push(format!("{}: {}", argument, query_type(types, query)));
+        body.push(query_push(
+            &argument,
+            &query.name,
+            query.is_vec,
+            request.query_vec_style,
+        ));
+        if query.is_vec && request.query_vec_style == QueryVecStyle::Comma {
+            helpers.join = true;
+        }
+    }
+
+    let send = match request.queries.is_empty() {
+        true => "self.http.get(url)".to_string(),
+        false => "self.http.get(url).query(&query)".to_string(),
+    };
+    match request.headers.is_empty() {
+        true => body.push(format!("let request = {};", send)),
+        false => body.push(format!("let mut request = {};", send)),
+    }
+
+    for header in &request.headers {
+        let argument = arguments.claim(snake_case(&header.name, "header"));
+        let rust_type = types.label_type(&header.data_type);
+
+        parameters.push(format!("{}: Option<{}>", argument, borrowed(&rust_type)));
+        body.push(format!(
+            "if let Some({a}) = {a} {{\n    request = request.header({:?}, {a}.to_string());\n}}",
+            header.name,
+            a = argument
+        ));
+    }
+
+    body.push("request.send().await?.error_for_status()?.json().await".to_string());
+
+    let mut method = doc(
+        "    ",
+        &format!(
+            "{}\n\n`GET {}`",
+            request.description,
+            path_template(request)
+        ),
+    );
+    method.push_str(&format!(
+        "    pub async fn {}(\n{}    ) -> reqwest::Result<{}> {{\n",
+        name,
+        parameters
+            .iter()
+            .map(|p| format!("        {},\n", p))
+            .collect::<String>(),
+        response
+    ));
+    for statement in body {
+        for line in statement.lines() {
+            method.push_str(&format!("        {}\n", line));
+        }
+    }
+    method.push_str("    }\n");
+
+    method
+}
+
+/// Argument type of a query, optional unless it takes a list
+fn query_type(types: &Types, query: &Slot) -> String {
+    let rust_type = types.label_type(&query.data_type);
+
+    match query.is_vec {
+        true => format!("&[{}]", rust_type.name),
+        false => format!("Option<{}>", borrowed(&rust_type)),
+    }
+}
+
+/// The statement adding a query argument to `query`
+fn query_push(argument: &str, name: &str, is_vec: bool, style: QueryVecStyle) -> String {
+    match (is_vec, style) {
+        (false, _) => format!(
+            "if let Some({a}) = {a} {{\n    query.push(({:?}, {a}.to_string()));\n}}",
+            name,
+            a = argument
+        ),
+        (true, QueryVecStyle::Comma) => format!(
+            "if !{a}.is_empty() {{\n    query.push(({:?}, join({a})));\n}}",
+            name,
+            a = argument
+        ),
+        (true, style) => {
+            let name = match style {
+                QueryVecStyle::Brackets => format!("{}[]", name),
+                _ => name.to_string(),
+            };
+
+            format!(
+                "for value in {} {{\n    query.push(({:?}, value.to_string()));\n}}",
+                argument, name
+            )
+        }
+    }
+}
+
+fn primitive_type(primitive: Primitive) -> RustType {
+    let name = match primitive {
+        Primitive::Int => "i64",
+        Primitive::Float => "f64",
+        Primitive::Bool => "bool",
+        // uuids and datetimes are kept as text to spare the client other dependencies
+        Primitive::String | Primitive::Uuid | Primitive::Datetime | Primitive::Const => "String",
+    };
+
+    RustType {
+        name: name.to_string(),
+        is_string: name == "String",
+    }
+}
+
+fn borrowed(rust_type: &RustType) -> String {
+    match rust_type.is_string {
+        true => "&str".to_string(),
+        false => format!("&{}", rust_type.name),
+    }
+}
+
+fn vec_of(rust_type: String, is_vec: bool) -> String {
+    match is_vec {
+        true => format!("Vec<{}>", rust_type),
+        false => rust_type,
+    }
+}
+
+/// Documentation of a newtype: its label and constraints
+fn data_type_doc(data_type: &DataTypeRecord) -> String {
+    let constraints = &data_type.constraints;
+    let mut doc = format!("`{}`", data_type.label);
+
+    if let Some(pattern) = &constraints.pattern {
+        doc.push_str(&format!(", matches `{}`", pattern));
+    }
+    if let Some(values) = &constraints.enum_values {
+        doc.push_str(&format!(", one of {}", values.join(", ")));
+    }
+
+    doc
+}
+
+/// `text` as doc comment lines indented by `indent`
+fn doc(indent: &str, text: &str) -> String {
+    text.trim()
+        .lines()
+        .map(|line| match line.trim_end() {
+            "" => format!("{}///\n", indent),
+            line => format!("{}/// {}\n", indent, line),
+        })
+        .collect()
+}
+
+/// `name` as a snake case identifier, a keyword gets a trailing `_`
+fn snake_case(name: &str, fallback: &str) -> String {
+    let name = or(words(name).join("_"), fallback);
+
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ApiRecord, Constraints, ResponseChild, ResponseField, ServiceRecord};
+
+    fn data_type(label: &str, primitive: &str, constraints: Constraints) -> DataTypeRecord {
+        DataTypeRecord {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+            data_primitive_id: Uuid::new_v4(),
+            primitive: primitive.to_string(),
+            constraints,
+        }
+    }
+
+    fn slot(name: &str, data_type: &str, is_vec: bool) -> Slot {
+        Slot {
+            name: name.to_string(),
+            data_type_id: Uuid::new_v4(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    fn field(identifier: &str, data_type: &str, is_vec: bool) -> ResponseField {
+        ResponseField {
+            identifier: identifier.to_string(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    fn spotify() -> ApiExport {
+        let api_id = Uuid::new_v4();
+        let artist = ResponseSchema {
+            id: Some(Uuid::new_v4()),
+            description: "Artist".to_string(),
+            data: vec![
+                field("genres", "string", true),
+                field("type", "string", false),
+                field("followerCount", "int", false),
+            ],
+            schemas: vec![ResponseChild {
+                identifier: "images".to_string(),
+                is_vec: true,
+                schema: ResponseSchema {
+                    id: Some(Uuid::new_v4()),
+                    description: "Image".to_string(),
+                    data: vec![field("height", "int", false)],
+                    schemas: Vec::new(),
+                },
+            }],
+        };
+
+        ApiExport {
+            service: ServiceRecord {
+                id: Uuid::new_v4(),
+                title: "Spotify".to_string(),
+                description: "Music streaming".to_string(),
+                url: "https://api.spotify.com".to_string(),
+            },
+            api: ApiRecord {
+                id: api_id,
+                service_id: Uuid::new_v4(),
+                url: "https://api.spotify.com".to_string(),
+                vers: "v1".to_string(),
+            },
+            requests: vec![RequestDefinition {
+                id: Uuid::new_v4(),
+                api_id,
+                response_schema_id: artist.id.unwrap(),
+                description: "Get an artist".to_string(),
+                query_vec_style: QueryVecStyle::Comma,
+                url: "https://api.spotify.com".to_string(),
+                vers: "v1".to_string(),
+                paths: vec![
+                    slot("artists", "const", false),
+                    slot("id", "spotify_artist_id", false),
+                ],
+                queries: vec![
+                    slot("market", "country_code", true),
+                    slot("limit", "int", false),
+                ],
+                headers: vec![slot("Authorization", "string", false)],
+            }],
+            data_types: vec![
+                data_type(
+                    "country_code",
+                    "string",
+                    Constraints {
+                        enum_values: Some(vec!["NL".to_string(), "SE".to_string()]),
+                        ..Constraints::default()
+                    },
+                ),
+                data_type("int", "int", Constraints::default()),
+                data_type(
+                    "spotify_artist_id",
+                    "string",
+                    Constraints {
+                        pattern: Some("[0-9A-Za-z]{22}".to_string()),
+                        ..Constraints::default()
+                    },
+                ),
+                data_type("string", "string", Constraints::default()),
+            ],
+            response_schemas: vec![artist],
+        }
+    }
+
+    #[test]
+    fn data_types_become_newtypes() {
+        let module = module(&spotify());
+
+        assert!(module.contains(
+            "/// `spotify_artist_id`, matches `[0-9A-Za-z]{22}`\n\
+             #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]\n\
+             #[serde(transparent)]\n\
+             pub struct SpotifyArtistId(pub String);\n"
+        ));
+        assert!(module.contains("pub struct CountryCode(pub String);\n"));
+        assert!(!module.contains("pub struct Int("));
+    }
+
+    #[test]
+    fn response_schemas_become_structs() {
+        let module = module(&spotify());
+
+        assert!(module.contains(
+            "pub struct Artist {\n    \
+             pub genres: Vec<String>,\n    \
+             #[serde(rename = \"type\")]\n    \
+             pub type_: String,\n    \
+             #[serde(rename = \"followerCount\")]\n    \
+             pub follower_count: i64,\n    \
+             pub images: Vec<Image>,\n}\n"
+        ));
+        assert!(
+            module.find("pub struct Image").unwrap() < module.find("pub struct Artist").unwrap()
+        );
+    }
+
+    #[test]
+    fn requests_become_methods() {
+        let module = module(&spotify());
+
+        assert!(module.contains(
+            "    pub async fn get_an_artist(\n        \
+             &self,\n        \
+             id: &SpotifyArtistId,\n        \
+             market: &[CountryCode],\n        \
+             limit: Option<&i64>,\n        \
+             authorization: Option<&str>,\n    \
+             ) -> reqwest::Result<Artist> {\n        \
+             let url = format!(\"{}/artists/{}\", self.base_url, segment(id));\n"
+        ));
+        assert!(module.contains("query.push((\"market\", join(market)));"));
+        assert!(module
+            .contains("request = request.header(\"Authorization\", authorization.to_string());"));
+        assert!(module.contains("fn segment(value: impl std::fmt::Display) -> String"));
+    }
+
+    #[test]
+    fn methods_do_not_clash_with_the_constructors() {
+        let mut export = spotify();
+        export.requests[0].description = "New".to_string();
+
+        assert!(module(&export).contains("    pub async fn new_2(\n"));
+    }
+
+    #[test]
+    fn names_become_rust_identifiers() {
+        assert_eq!(
+            snake_case("Get an artist's albums", "request"),
+            "get_an_artist_s_albums"
+        );
+        assert_eq!(snake_case("type", "field"), "type_");
+        assert_eq!(snake_case("2fa", "field"), "_2fa");
+        assert_eq!(snake_case("--", "field"), "field");
+        assert_eq!(
+            pascal_case("spotify_artist_id", "DataType"),
+            "SpotifyArtistId"
+        );
+        assert_eq!(pascal_case("404 page", "Schema"), "Schema404Page");
+    }
+}
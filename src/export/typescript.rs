@@ -0,0 +1,666 @@
+//! TypeScript client modules.
+//!
+//! Data types become type aliases, a union of literals when they have
+//! enum values, response schemas become interfaces and every request a
+//! `fetch` helper taking its slots as one object keyed by slot name, so
+//! `https://api.spotify.com|v1/artists/{id,spotify_artist_id}` becomes
+//! `getAnArtist({ id })` resolving to the request's response schema.
+
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{encode_segment, one_line, or, pascal_case, path_template, ApiExport, Names};
+use crate::domain::{
+    DataTypeRecord, Primitive, QueryVecStyle, RequestDefinition, ResponseSchema, Slot,
+    BUILTIN_PRIMITIVES, CONST_TYPE,
+};
+
+/// Reserved words of JavaScript and TypeScript that can't name a function
+const KEYWORDS: [&str; 38] = [
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "new",
+    "null",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+    "await",
+];
+
+/// Names the module uses for itself, or that would shadow globals it uses
+const RESERVED_TYPES: [&str; 10] = [
+    "Error",
+    "Headers",
+    "HttpError",
+    "Promise",
+    "Record",
+    "Request",
+    "RequestInit",
+    "RequestOptions",
+    "Response",
+    "URL",
+];
+
+/// Globals the generated functions call
+const RESERVED_FUNCTIONS: [&str; 4] = ["String", "URL", "encodeURIComponent", "fetch"];
+
+const PRELUDE: &str = r#"
+/** Where a helper sends its request */
+export interface RequestOptions {
+  /** Server to call instead of `BASE_URL`, e.g. a mock */
+  baseUrl?: string;
+  /** Passed on to `fetch`, e.g. to add a signal */
+  init?: RequestInit;
+}
+
+/** A response whose status isn't 2xx */
+export class HttpError extends Error {
+  readonly response: Response;
+
+  constructor(response: Response) {
+    super(`${response.status} ${response.statusText}`);
+    this.response = response;
+  }
+}
+"#;
+
+/// Build the source of a module calling an API version
+pub fn module(export: &ApiExport) -> String {
+    let mut types = Types::default();
+    let mut functions = Names::default();
+    let mut out = String::new();
+
+    for name in RESERVED_TYPES.iter() {
+        types.names.claim(name.to_string());
+    }
+    for name in RESERVED_FUNCTIONS.iter() {
+        functions.claim(name.to_string());
+    }
+    for data_type in &export.data_types {
+        types.add_data_type(data_type);
+    }
+    for schema in &export.response_schemas {
+        types.add_response_schema(schema);
+    }
+
+    out.push_str(&format!(
+        "// Client for {} {}, generated by daysquare.\n\n",
+        one_line(&export.service.title),
+        one_line(&export.api.vers)
+    ));
+    out.push_str(&format!(
+        "export const BASE_URL = {};\n",
+        string(&format!("{}/{}", export.api.url, export.api.vers))
+    ));
+    out.push_str(PRELUDE);
+
+    for item in &types.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+
+    for request in &export.requests {
+        out.push('\n');
+        out.push_str(&function(request, &types, &mut functions));
+    }
+
+    out
+}
+
+/// Type definitions and the names they were given
+#[derive(Default)]
+struct Types {
+    names: Names,
+    /// TypeScript type of each data type label
+    data_types: HashMap<String, String>,
+    /// Interface name of each response schema id
+    response_schemas: HashMap<Uuid, String>,
+    items: Vec<String>,
+}
+
+impl Types {
+    /// Built-in data types are plain TypeScript types, others are aliases
+    fn add_data_type(&mut self, data_type: &DataTypeRecord) {
+        let primitive = Primitive::from_name(&data_type.primitive);
+
+        if BUILTIN_PRIMITIVES.contains(&data_type.label.as_str())
+            && data_type.label == data_type.primitive
+        {
+            self.data_types.insert(
+                data_type.label.clone(),
+                primitive_type(primitive).to_string(),
+            );
+            return;
+        }
+
+        let name = self.claim_type(&data_type.label, "DataType");
+        let aliased = match &data_type.constraints.enum_values {
+            Some(values) if !values.is_empty() => values
+                .iter()
+                .map(|value| literal(primitive, value))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            _ => primitive_type(primitive).to_string(),
+        };
+
+        self.items.push(format!(
+            "{}export type {} = {};\n",
+            doc("", &data_type_doc(data_type)),
+            name,
+            aliased
+        ));
+        self.data_types.insert(data_type.label.clone(), name);
+    }
+
+    /// Add `schema` and the schemas nested in it, each once.
+    /// Children are added first so interfaces are named before their parents.
+    fn add_response_schema(&mut self, schema: &ResponseSchema) {
+        let id = match schema.id {
+            Some(id) if !self.response_schemas.contains_key(&id) => id,
+            _ => return,
+        };
+
+        for child in &schema.schemas {
+            self.add_response_schema(&child.schema);
+        }
+
+        let name = self.claim_type(&schema.description, "Schema");
+        let item = self.interface(&name, schema);
+
+        self.items.push(item);
+        self.response_schemas.insert(id, name);
+    }
+
+    fn interface(&self, name: &str, schema: &ResponseSchema) -> String {
+        let mut item = doc("", &schema.description);
+
+        item.push_str(&format!("export interface {} {{\n", name));
+
+        for field in &schema.data {
+            item.push_str(&format!(
+                "  {}: {};\n",
+                key(&field.identifier),
+                array_of(self.label_type(&field.data_type), field.is_vec)
+            ));
+        }
+        for child in &schema.schemas {
+            let interface = child
+                .schema
+                .id
+                .and_then(|id| self.response_schemas.get(&id))
+                .map(String::as_str)
+                .unwrap_or("unknown");
+
+            item.push_str(&format!(
+                "  {}: {};\n",
+                key(&child.identifier),
+                array_of(interface, child.is_vec)
+            ));
+        }
+
+        item.push_str("}\n");
+        item
+    }
+
+    fn label_type(&self, label: &str) -> &str {
+        self.data_types
+            .get(label)
+            .map(String::as_str)
+            // every label of a stored request refers to a data type
+            .unwrap_or("string")
+    }
+
+    fn claim_type(&mut self, name: &str, fallback: &str) -> String {
+        let name = pascal_case(name, fallback);
+        self.names.claim_joined(name, "")
+    }
+}
+
+fn function(request: &RequestDefinition, types: &Types, functions: &mut Names) -> String {
+    let mut members = Names::default();
+    // a path, query and header may share a name, each gets its own member of `params`
+    let slots: Vec<(&Slot, String)> = request
+        .paths
+        .iter()
+        .filter(|p| p.data_type != CONST_TYPE)
+        .chain(&request.queries)
+        .chain(&request.headers)
+        .map(|slot| (slot, members.claim(slot.name.clone())))
+        .collect();
+    let member = |slot: &Slot| -> String {
+        slots
+            .iter()
+            .find(|(s, _)| std::ptr::eq(*s, slot))
+            .map(|(_, member)| member.clone())
+            .unwrap_or_default()
+    };
+    let mut body = Vec::new();
+    let mut segments = Vec::new();
+    let name = functions.claim_joined(camel_case(&request.description), "");
+    let response = types
+        .response_schemas
+        .get(&request.response_schema_id)
+        .map(String::as_str)
+        .unwrap_or("unknown");
+    let mut parameters = Vec::new();
+
+    if !slots.is_empty() {
+        let fields: Vec<String> = slots
+            .iter()
+            .map(|(slot, member)| {
+                let optional = match request.paths.iter().any(|p| std::ptr::eq(p, *slot)) {
+                    true => "",
+                    false => "?",
+                };
+                format!(
+                    "{}{}: {}",
+                    key(member),
+                    optional,
+                    array_of(types.label_type(&slot.data_type), slot.is_vec)
+                )
+            })
+            .collect();
+        let default = match request.paths.iter().any(|p| p.data_type != CONST_TYPE) {
+            true => "",
+            false => " = {}",
+        };
+
+        parameters.push(format!("params: {{ {} }}{}", fields.join("; "), default));
+    }
+    parameters.push("options: RequestOptions = {}".to_string());
+
+    for path in &request.paths {
+        segments.push(match path.data_type.as_str() {
+            CONST_TYPE => encode_segment(&path.name),
+            _ => format!("${{encodeURIComponent(String({}))}}", access(&member(path))),
+        });
+    }
+    body.push(match segments.is_empty() {
+        true => "const url = new URL(options.baseUrl ?? BASE_URL);".to_string(),
+        false => format!(
+            "const url = new URL(`${{options.baseUrl ?? BASE_URL}}/{}`);",
+            segments.join("/")
+        ),
+    });
+
+    for query in &request.queries {
+        body.push(query_append(query, &member(query), request.query_vec_style));
+    }
+
+    body.push("const headers = new Headers(options.init?.headers);".to_string());
+    for header in &request.headers {
+        body.push(format!(
+            "if ({a} !== undefined) {{\n  headers.set({}, String({a}));\n}}",
+            string(&header.name),
+            a = access(&member(header))
+        ));
+    }
+
+    body.push(
+        "const response = await fetch(url.toString(), { ...options.init, method: \"GET\", headers });"
+            .to_string(),
+    );
+    body.push("if (!response.ok) {\n  throw new HttpError(response);\n}".to_string());
+    body.push(format!("return (await response.json()) as {};", response));
+
+    let mut function = doc(
+        "",
+        &format!(
+            "{}\n\n`GET {}`",
+            request.description,
+            path_template(request)
+        ),
+    );
+    function.push_str(&format!(
+        "export async function {}(\n{}): Promise<{}> {{\n",
+        name,
+        parameters
+            .iter()
+            .map(|p| format!("  {},\n", p))
+            .collect::<String>(),
+        response
+    ));
+    for statement in body {
+        for line in statement.lines() {
+            function.push_str(&format!("  {}\n", line));
+        }
+    }
+    function.push_str("}\n");
+
+    function
+}
+
+/// The statement adding a query to `url`, its value read from the `params` member called `member`
+fn query_append(query: &Slot, member: &str, style: QueryVecStyle) -> String {
+    let value = access(member);
+    let name = string(&query.name);
+
+    match (query.is_vec, style) {
+        (false, _) => format!(
+            "if ({v} !== undefined) {{\n  url.searchParams.append({}, String({v}));\n}}",
+            name,
+            v = value
+        ),
+        (true, QueryVecStyle::Comma) => format!(
+            "if ({v} !== undefined && {v}.length > 0) {{\n  url.searchParams.append({}, {v}.join(\",\"));\n}}",
+            name,
+            v = value
+        ),
+        (true, style) => {
+            let name = match style {
+                QueryVecStyle::Brackets => string(&format!("{}[]", query.name)),
+                _ => name,
+            };
+
+            format!(
+                "for (const value of {} ?? []) {{\n  url.searchParams.append({}, String(value));\n}}",
+                value, name
+            )
+        }
+    }
+}
+
+fn primitive_type(primitive: Primitive) -> &'static str {
+    match primitive {
+        Primitive::Int | Primitive::Float => "number",
+        Primitive::Bool => "boolean",
+        Primitive::String | Primitive::Uuid | Primitive::Datetime | Primitive::Const => "string",
+    }
+}
+
+/// An enum value as a literal type of its primitive
+fn literal(primitive: Primitive, value: &str) -> String {
+    match primitive {
+        Primitive::Int | Primitive::Float | Primitive::Bool => value.to_string(),
+        _ => string(value),
+    }
+}
+
+fn array_of(type_name: &str, is_vec: bool) -> String {
+    match is_vec {
+        true if type_name.contains(' ') => format!("({})[]", type_name),
+        true => format!("{}[]", type_name),
+        false => type_name.to_string(),
+    }
+}
+
+/// Documentation of an alias: its label and constraints
+fn data_type_doc(data_type: &DataTypeRecord) -> String {
+    match &data_type.constraints.pattern {
+        Some(pattern) => format!("`{}`, matches `{}`", data_type.label, pattern),
+        None => format!("`{}`", data_type.label),
+    }
+}
+
+/// `text` as a doc comment indented by `indent`
+fn doc(indent: &str, text: &str) -> String {
+    let text = text.trim().replace("*/", "*\\/");
+
+    match text.lines().count() {
+        0 => String::new(),
+        1 => format!("{}/** {} */\n", indent, text),
+        _ => {
+            let mut doc = format!("{}/**\n", indent);
+            for line in text.lines() {
+                match line.trim_end() {
+                    "" => doc.push_str(&format!("{} *\n", indent)),
+                    line => doc.push_str(&format!("{} * {}\n", indent, line)),
+                }
+            }
+            doc.push_str(&format!("{} */\n", indent));
+            doc
+        }
+    }
+}
+
+/// A string literal, JSON strings are valid in JavaScript
+fn string(text: &str) -> String {
+    serde_json::Value::String(text.to_string()).to_string()
+}
+
+/// `name` as an object key, quoted unless it is an identifier
+fn key(name: &str) -> String {
+    match is_identifier(name) {
+        true => name.to_string(),
+        false => string(name),
+    }
+}
+
+/// Read the `params` member called `name`
+fn access(name: &str) -> String {
+    match is_identifier(name) {
+        true => format!("params.{}", name),
+        false => format!("params[{}]", string(name)),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// `name` as a lower camel case function name, a keyword gets a trailing `_`
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name, "Request");
+    let mut chars = pascal.chars();
+    let name = chars
+        .next()
+        .map(|c| c.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default();
+
+    match KEYWORDS.contains(&name.as_str()) {
+        true => format!("{}_", name),
+        false => or(name, "request"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ApiRecord, Constraints, ResponseChild, ResponseField, ServiceRecord};
+
+    fn data_type(label: &str, primitive: &str, constraints: Constraints) -> DataTypeRecord {
+        DataTypeRecord {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+            data_primitive_id: Uuid::new_v4(),
+            primitive: primitive.to_string(),
+            constraints,
+        }
+    }
+
+    fn slot(name: &str, data_type: &str, is_vec: bool) -> Slot {
+        Slot {
+            name: name.to_string(),
+            data_type_id: Uuid::new_v4(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    fn field(identifier: &str, data_type: &str, is_vec: bool) -> ResponseField {
+        ResponseField {
+            identifier: identifier.to_string(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    fn spotify() -> ApiExport {
+        let api_id = Uuid::new_v4();
+        let artist = ResponseSchema {
+            id: Some(Uuid::new_v4()),
+            description: "Artist".to_string(),
+            data: vec![
+                field("genres", "string", true),
+                field("popularity", "int", false),
+                field("external-url", "string", false),
+            ],
+            schemas: vec![ResponseChild {
+                identifier: "images".to_string(),
+                is_vec: true,
+                schema: ResponseSchema {
+                    id: Some(Uuid::new_v4()),
+                    description: "Image".to_string(),
+                    data: vec![field("height", "int", false)],
+                    schemas: Vec::new(),
+                },
+            }],
+        };
+
+        ApiExport {
+            service: ServiceRecord {
+                id: Uuid::new_v4(),
+                title: "Spotify".to_string(),
+                description: "Music streaming".to_string(),
+                url: "https://api.spotify.com".to_string(),
+            },
+            api: ApiRecord {
+                id: api_id,
+                service_id: Uuid::new_v4(),
+                url: "https://api.spotify.com".to_string(),
+                vers: "v1".to_string(),
+            },
+            requests: vec![RequestDefinition {
+                id: Uuid::new_v4(),
+                api_id,
+                response_schema_id: artist.id.unwrap(),
+                description: "Get an artist".to_string(),
+                query_vec_style: QueryVecStyle::Comma,
+                url: "https://api.spotify.com".to_string(),
+                vers: "v1".to_string(),
+                paths: vec![
+                    slot("artists", "const", false),
+                    slot("id", "spotify_artist_id", false),
+                ],
+                queries: vec![slot("market", "country_code", true)],
+                headers: vec![slot("Authorization", "string", false)],
+            }],
+            data_types: vec![
+                data_type(
+                    "country_code",
+                    "string",
+                    Constraints {
+                        enum_values: Some(vec!["NL".to_string(), "SE".to_string()]),
+                        ..Constraints::default()
+                    },
+                ),
+                data_type("int", "int", Constraints::default()),
+                data_type(
+                    "spotify_artist_id",
+                    "string",
+                    Constraints {
+                        pattern: Some("[0-9A-Za-z]{22}".to_string()),
+                        ..Constraints::default()
+                    },
+                ),
+                data_type("string", "string", Constraints::default()),
+            ],
+            response_schemas: vec![artist],
+        }
+    }
+
+    #[test]
+    fn data_types_become_aliases() {
+        let module = module(&spotify());
+
+        assert!(module.contains(
+            "/** `spotify_artist_id`, matches `[0-9A-Za-z]{22}` */\n\
+             export type SpotifyArtistId = string;\n"
+        ));
+        assert!(module.contains("export type CountryCode = \"NL\" | \"SE\";\n"));
+        assert!(!module.contains("export type Int "));
+    }
+
+    #[test]
+    fn response_schemas_become_interfaces() {
+        let module = module(&spotify());
+
+        assert!(module.contains(
+            "export interface Artist {\n  \
+             genres: string[];\n  \
+             popularity: number;\n  \
+             \"external-url\": string;\n  \
+             images: Image[];\n}\n"
+        ));
+        assert!(module.find("interface Image").unwrap() < module.find("interface Artist").unwrap());
+    }
+
+    #[test]
+    fn requests_become_fetch_helpers() {
+        let module = module(&spotify());
+
+        assert!(module.contains(
+            "export async function getAnArtist(\n  \
+             params: { id: SpotifyArtistId; market?: CountryCode[]; Authorization?: string },\n  \
+             options: RequestOptions = {},\n\
+             ): Promise<Artist> {\n  \
+             const url = new URL(`${options.baseUrl ?? BASE_URL}/artists/${encodeURIComponent(String(params.id))}`);\n"
+        ));
+        assert!(module.contains("url.searchParams.append(\"market\", params.market.join(\",\"));"));
+        assert!(module.contains("headers.set(\"Authorization\", String(params.Authorization));"));
+        assert!(module.contains("return (await response.json()) as Artist;"));
+    }
+
+    #[test]
+    fn functions_do_not_shadow_the_globals_they_call() {
+        let mut export = spotify();
+        export.requests[0].description = "Fetch".to_string();
+
+        assert!(module(&export).contains("export async function fetch2(\n"));
+    }
+
+    #[test]
+    fn slots_sharing_a_name_get_their_own_member() {
+        let mut export = spotify();
+        export.requests[0]
+            .queries
+            .push(slot("id", "spotify_artist_id", false));
+        let module = module(&export);
+
+        assert!(module.contains("params: { id: SpotifyArtistId; market?: CountryCode[]; id_2?: SpotifyArtistId; Authorization?: string },"));
+        assert!(module.contains("${encodeURIComponent(String(params.id))}"));
+        assert!(module.contains("url.searchParams.append(\"id\", String(params.id_2));"));
+    }
+
+    #[test]
+    fn names_become_typescript_identifiers() {
+        assert_eq!(camel_case("Get an artist's albums"), "getAnArtistSAlbums");
+        assert_eq!(camel_case("delete"), "delete_");
+        assert_eq!(camel_case("--"), "request");
+        assert_eq!(key("external-url"), "\"external-url\"");
+        assert_eq!(access("x-api-key"), "params[\"x-api-key\"]");
+    }
+}
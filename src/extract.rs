@@ -0,0 +1,101 @@
+//! The extractors of axum, with their rejections turned into an [`AppError`]
+//! so a malformed body, path or query is answered with a problem details
+//! body like every other error.
+
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{self, FromRequest, RequestParts};
+use axum::http::Response;
+use axum::response::IntoResponse;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::AppError;
+
+/// A JSON request body, or a JSON response
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+/// An `application/x-www-form-urlencoded` request body
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Form<T>(pub T);
+
+/// The parameters of the route's path
+#[derive(Debug)]
+pub struct Path<T>(pub T);
+
+/// The query string
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query<T>(pub T);
+
+#[async_trait]
+impl<T> FromRequest<Body> for Json<T>
+where
+    T: DeserializeOwned + Send,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        match extract::Json::<T>::from_request(req).await {
+            Ok(extract::Json(value)) => Ok(Json(value)),
+            Err(rejection) => Err(AppError::Parse(rejection.to_string())),
+        }
+    }
+}
+
+impl<T> IntoResponse for Json<T>
+where
+    T: Serialize,
+{
+    type Body = <axum::Json<T> as IntoResponse>::Body;
+    type BodyError = <axum::Json<T> as IntoResponse>::BodyError;
+
+    fn into_response(self) -> Response<Self::Body> {
+        axum::Json(self.0).into_response()
+    }
+}
+
+#[async_trait]
+impl<T> FromRequest<Body> for Form<T>
+where
+    T: DeserializeOwned + Send,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        match extract::Form::<T>::from_request(req).await {
+            Ok(extract::Form(value)) => Ok(Form(value)),
+            Err(rejection) => Err(AppError::Parse(rejection.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> FromRequest<Body> for Path<T>
+where
+    T: DeserializeOwned + Send,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        match extract::Path::<T>::from_request(req).await {
+            Ok(extract::Path(value)) => Ok(Path(value)),
+            Err(rejection) => Err(AppError::Parse(rejection.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> FromRequest<Body> for Query<T>
+where
+    T: DeserializeOwned + Send,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        match extract::Query::<T>::from_request(req).await {
+            Ok(extract::Query(value)) => Ok(Query(value)),
+            Err(rejection) => Err(AppError::Parse(rejection.to_string())),
+        }
+    }
+}
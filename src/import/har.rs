@@ -0,0 +1,99 @@
+//! HAR captures, format 1.1 and 1.2, as saved by browsers and proxies.
+//!
+//! Successful responses with a JSON body are used to infer response schemas.
+
+use serde_json::Value;
+
+use super::capture::{self, Call};
+use super::CapturePlan;
+
+/// Map a capture onto the catalogue, one service per host
+pub fn plan(doc: &Value) -> Result<CapturePlan, String> {
+    let entries = doc["log"]["entries"]
+        .as_array()
+        .ok_or("not a HAR capture: log.entries is missing")?;
+    let mut warnings = Vec::new();
+    let mut calls = Vec::new();
+
+    for entry in entries {
+        let request = &entry["request"];
+        let response = &entry["response"];
+        let content = &response["content"];
+        let url = request["url"].as_str().unwrap_or_default();
+        let mut bodies = Vec::new();
+
+        let is_json = matches!(content["mimeType"].as_str(), Some(m) if m.contains("json"));
+        let succeeded = matches!(response["status"].as_u64(), Some(200..=299));
+        match (is_json && succeeded, content["text"].as_str()) {
+            (true, Some(_)) if content["encoding"] == "base64" => warnings.push(format!(
+                "{}: the base64 encoded response body was left out",
+                url
+            )),
+            (true, Some(text)) => bodies.extend(serde_json::from_str(text).ok()),
+            _ => (),
+        }
+
+        calls.push(Call {
+            name: None,
+            method: request["method"].as_str().unwrap_or_default().to_string(),
+            url: url.to_string(),
+            bodies,
+        });
+    }
+
+    let mut capture = capture::plan(calls, "a HAR capture")?;
+    capture.warnings.extend(warnings);
+
+    Ok(capture)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(method: &str, url: &str, status: u64, text: &str) -> Value {
+        json!({
+            "request": {"method": method, "url": url},
+            "response": {
+                "status": status,
+                "content": {"mimeType": "application/json; charset=utf-8", "text": text}
+            }
+        })
+    }
+
+    #[test]
+    fn json_bodies_of_successful_calls_are_used() {
+        let capture = plan(&json!({"log": {"version": "1.2", "entries": [
+            entry("GET", "https://api.github.com/repos/rust-lang/rust", 200, r#"{"id": 724712, "private": false}"#),
+            entry("GET", "https://api.github.com/repos/tokio-rs/axum", 404, r#"{"message": "Not Found"}"#),
+            entry("OPTIONS", "https://api.github.com/repos/rust-lang/rust", 204, ""),
+        ]}}))
+        .unwrap();
+        let plan = &capture.plans[0];
+
+        assert_eq!(
+            plan.requests
+                .iter()
+                .map(|r| r.dsl.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "https://api.github.com|repos/rust-lang/rust",
+                "https://api.github.com|repos/tokio-rs/axum",
+            ]
+        );
+        assert_eq!(
+            plan.warnings[1],
+            "https://api.github.com/repos/rust-lang/rust and https://api.github.com/repos/tokio-rs/axum \
+             only differ in 2 adjacent segments, which were kept as written"
+        );
+        assert_eq!(plan.requests[1].response, None);
+        assert_eq!(plan.response_schemas[0].schema.data[0].identifier, "id");
+        assert_eq!(plan.response_schemas.len(), 1);
+    }
+
+    #[test]
+    fn other_documents_are_rejected() {
+        assert!(plan(&json!({"openapi": "3.1.0"})).is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//! Response schemas guessed from sample JSON payloads.
+//!
+//! Every sample is merged into one shape, so a field only present in
+//! some of them still ends up in the schema. Scalars get the label of
+//! the narrowest builtin data type all their values fit, `int` and
+//! `float` widen to `float` and any other mix to `string`.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::domain::{ResponseChild, ResponseField, ResponseSchema, DEFAULT_PRIMITIVE};
+
+/// Merge `samples` into one response schema described as `description`.
+/// Each sample is an object, or an array of objects whose shapes are merged.
+///
+/// Parts that can't be described are reported in `warnings`.
+pub fn infer_schema(
+    description: &str,
+    samples: &[Value],
+    warnings: &mut Vec<String>,
+) -> Result<ResponseSchema, String> {
+    let mut shape = Shape::default();
+    let mut inference = Inference { warnings };
+
+    if samples.is_empty() {
+        return Err("at least one sample is needed".to_string());
+    }
+
+    for (i, sample) in samples.iter().enumerate() {
+        match sample {
+            Value::Object(object) => inference.object(&mut shape, object, ""),
+            Value::Array(items) if items.iter().all(Value::is_object) => {
+                inference.warnings.push(format!(
+                    "sample {} is an array, the schema describes its items",
+                    i + 1
+                ));
+                for item in items.iter().filter_map(Value::as_object) {
+                    inference.object(&mut shape, item, "");
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "sample {} is neither an object nor an array of objects",
+                    i + 1
+                ))
+            }
+        }
+    }
+
+    Ok(shape.into_schema(description.to_string()))
+}
+
+/// The label of the narrowest builtin data type every value fits
+pub fn scalar_label<'a>(values: impl IntoIterator<Item = &'a str>) -> &'static str {
+    values
+        .into_iter()
+        .map(text_label)
+        .fold(None, |label, next| Some(widen(label, next)))
+        .unwrap_or(DEFAULT_PRIMITIVE)
+}
+
+/// Label of a value written as text, e.g. in a url
+fn text_label(value: &str) -> &'static str {
+    if value.parse::<i64>().is_ok() {
+        "int"
+    } else if matches!(value.parse::<f64>(), Ok(n) if n.is_finite()) {
+        "float"
+    } else if value == "true" || value == "false" {
+        "bool"
+    } else {
+        string_label(value)
+    }
+}
+
+fn string_label(value: &str) -> &'static str {
+    if Uuid::parse_str(value).is_ok() {
+        "uuid"
+    } else if chrono::DateTime::parse_from_rfc3339(value).is_ok() {
+        "datetime"
+    } else {
+        DEFAULT_PRIMITIVE
+    }
+}
+
+/// The label of a field that held values of both labels
+fn widen(label: Option<&'static str>, next: &'static str) -> &'static str {
+    match (label, next) {
+        (None, next) => next,
+        (Some(label), next) if label == next => label,
+        (Some("int"), "float") | (Some("float"), "int") => "float",
+        _ => DEFAULT_PRIMITIVE,
+    }
+}
+
+/// The fields seen in the samples of one object, ordered by identifier
+#[derive(Debug, Default)]
+struct Shape {
+    fields: BTreeMap<String, Field>,
+}
+
+#[derive(Debug, Default)]
+struct Field {
+    kind: Kind,
+    is_vec: bool,
+}
+
+#[derive(Debug)]
+enum Kind {
+    /// Only `null` has been seen so far
+    Unknown,
+    Scalar(&'static str),
+    Object(Shape),
+}
+
+impl Default for Kind {
+    fn default() -> Self {
+        Kind::Unknown
+    }
+}
+
+impl Shape {
+    fn into_schema(self, description: String) -> ResponseSchema {
+        let mut schema = ResponseSchema {
+            description,
+            ..ResponseSchema::default()
+        };
+
+        for (identifier, field) in self.fields {
+            match field.kind {
+                Kind::Unknown => schema.data.push(ResponseField {
+                    identifier,
+                    data_type: DEFAULT_PRIMITIVE.to_string(),
+                    is_vec: field.is_vec,
+                }),
+                Kind::Scalar(label) => schema.data.push(ResponseField {
+                    identifier,
+                    data_type: label.to_string(),
+                    is_vec: field.is_vec,
+                }),
+                Kind::Object(shape) => schema.schemas.push(ResponseChild {
+                    schema: shape.into_schema(identifier.clone()),
+                    identifier,
+                    is_vec: field.is_vec,
+                }),
+            }
+        }
+
+        schema
+    }
+}
+
+struct Inference<'w> {
+    warnings: &'w mut Vec<String>,
+}
+
+impl Inference<'_> {
+    /// Merge the fields of `object`, found at the dotted `path`, into `shape`
+    fn object(&mut self, shape: &mut Shape, object: &serde_json::Map<String, Value>, path: &str) {
+        for (key, value) in object {
+            let path = match path {
+                "" => key.clone(),
+                path => format!("{}.{}", path, key),
+            };
+
+            if key.trim().is_empty() {
+                self.warn(format!("{}: a field without a name was left out", path));
+                continue;
+            }
+
+            let field = shape.fields.entry(key.clone()).or_default();
+            match value {
+                Value::Array(items) => {
+                    field.is_vec = true;
+                    for item in items {
+                        self.value(&mut field.kind, item, &path);
+                    }
+                }
+                value => self.value(&mut field.kind, value, &path),
+            }
+        }
+    }
+
+    fn value(&mut self, kind: &mut Kind, value: &Value, path: &str) {
+        let label = match value {
+            Value::Null => return,
+            Value::Object(object) => {
+                match kind {
+                    Kind::Object(shape) => self.object(shape, object, path),
+                    _ => {
+                        if let Kind::Scalar(_) = kind {
+                            self.mixed(path);
+                        }
+                        let mut shape = Shape::default();
+                        self.object(&mut shape, object, path);
+                        *kind = Kind::Object(shape);
+                    }
+                }
+                return;
+            }
+            Value::Array(_) => {
+                self.warn(format!(
+                    "{}: arrays of arrays can't be described, the inner arrays were left out",
+                    path
+                ));
+                return;
+            }
+            Value::Bool(_) => "bool",
+            Value::Number(n) if n.is_i64() || n.is_u64() => "int",
+            Value::Number(_) => "float",
+            Value::String(s) => string_label(s),
+        };
+
+        match kind {
+            Kind::Unknown => *kind = Kind::Scalar(label),
+            Kind::Scalar(current) => *current = widen(Some(*current), label),
+            Kind::Object(_) => self.mixed(path),
+        }
+    }
+
+    /// Objects win over scalars found at the same path
+    fn mixed(&mut self, path: &str) {
+        self.warn(format!(
+            "{}: holds both objects and scalars, the scalars were left out",
+            path
+        ));
+    }
+
+    /// Record a warning once
+    fn warn(&mut self, warning: String) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn infer(samples: &[Value]) -> (ResponseSchema, Vec<String>) {
+        let mut warnings = Vec::new();
+        let schema = infer_schema("Artist", samples, &mut warnings).unwrap();
+
+        (schema, warnings)
+    }
+
+    fn field(identifier: &str, data_type: &str, is_vec: bool) -> ResponseField {
+        ResponseField {
+            identifier: identifier.to_string(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    #[test]
+    fn samples_are_merged() {
+        let (schema, warnings) = infer(&[
+            json!({"name": "Radiohead", "popularity": 80, "genres": ["rock"]}),
+            json!({"name": "Björk", "popularity": 75.5, "followers": null}),
+        ]);
+
+        assert!(warnings.is_empty());
+        assert_eq!(
+            schema.data,
+            vec![
+                field("followers", "string", false),
+                field("genres", "string", true),
+                field("name", "string", false),
+                field("popularity", "float", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_objects_become_child_schemas() {
+        let (schema, _) = infer(&[json!({
+            "images": [
+                {"url": "https://i.scdn.co/a", "height": 640},
+                {"url": "https://i.scdn.co/b", "width": 320}
+            ],
+            "id": "0b2B3PwcYzQAhuJacmcYgc",
+            "updated_at": "2021-11-02T19:41:20Z"
+        })]);
+
+        assert_eq!(
+            schema.data,
+            vec![
+                field("id", "string", false),
+                field("updated_at", "datetime", false)
+            ]
+        );
+        assert_eq!(schema.schemas[0].identifier, "images");
+        assert!(schema.schemas[0].is_vec);
+        assert_eq!(
+            schema.schemas[0].schema.data,
+            vec![
+                field("height", "int", false),
+                field("url", "string", false),
+                field("width", "int", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_values_are_reported() {
+        let (schema, warnings) = infer(&[
+            json!({"owner": {"id": 1}, "matrix": [[1, 2]]}),
+            json!({"owner": "me"}),
+        ]);
+
+        assert_eq!(schema.schemas[0].identifier, "owner");
+        assert_eq!(
+            warnings,
+            vec![
+                "matrix: arrays of arrays can't be described, the inner arrays were left out",
+                "owner: holds both objects and scalars, the scalars were left out",
+            ]
+        );
+    }
+
+    #[test]
+    fn scalar_samples_are_rejected() {
+        assert!(infer_schema("Artist", &[json!("Radiohead")], &mut Vec::new()).is_err());
+        assert!(infer_schema("Artist", &[], &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn scalar_labels_fit_every_value() {
+        assert_eq!(scalar_label(vec!["1", "2"]), "int");
+        assert_eq!(scalar_label(vec!["1", "2.5"]), "float");
+        assert_eq!(scalar_label(vec!["true", "1"]), "string");
+        assert_eq!(
+            scalar_label(vec!["7c9e6679-7425-40de-944b-e07fc1f90ae7"]),
+            "uuid"
+        );
+        assert_eq!(scalar_label(Vec::new()), "string");
+    }
+}
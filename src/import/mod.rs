@@ -0,0 +1,121 @@
+//! Conversion of third party api descriptions into rows of the catalogue.
+//!
+//! Each format is turned into an [`ImportPlan`] without touching the
+//! database, so a plan can be reviewed before `store::store_import` writes it.
+//! Recordings of calls to several hosts become a [`CapturePlan`].
+
+mod capture;
+pub mod har;
+pub mod infer;
+pub mod openapi;
+pub mod postman;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domain::{Constraints, ResponseSchema};
+
+/// Everything an imported document maps to
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct ImportPlan {
+    pub service: ServicePlan,
+    /// Data types the document defines, skipped if the label already exists
+    pub data_types: Vec<DataTypePlan>,
+    pub response_schemas: Vec<SchemaPlan>,
+    pub requests: Vec<RequestPlan>,
+    /// Parts of the document that were left out or approximated
+    pub warnings: Vec<String>,
+}
+
+/// Plans of recorded calls, one per host
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct CapturePlan {
+    pub plans: Vec<ImportPlan>,
+    /// Calls that were left out before they could be grouped by host
+    pub warnings: Vec<String>,
+}
+
+/// The service is looked up by `url` and only created if it is missing
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct ServicePlan {
+    pub title: String,
+    pub description: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DataTypePlan {
+    pub label: String,
+    pub primitive: String,
+    pub constraints: Constraints,
+}
+
+/// A response schema tree, referred to by `name` from [`RequestPlan::response`]
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SchemaPlan {
+    pub name: String,
+    pub schema: ResponseSchema,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RequestPlan {
+    pub description: String,
+    /// The request in the url DSL, its api is the DSL's base url and version
+    pub dsl: String,
+    pub response: Option<String>,
+}
+
+/// Turn a name from a document into a data type label
+/// by replacing characters the url DSL reserves
+fn label(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| match c {
+            '{' | '}' | ',' | '=' | '&' | '?' | '#' | '|' | '/' | '[' | ']' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// What storing an [`ImportPlan`] created.
+/// For a dry run the ids are those the rows would have had.
+#[derive(Serialize, Debug)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub service: ImportedService,
+    /// Labels of the data types that didn't exist yet
+    pub data_types: Vec<String>,
+    pub response_schemas: Vec<ImportedSchema>,
+    pub requests: Vec<ImportedRequest>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportedService {
+    pub id: Uuid,
+    pub url: String,
+    pub created: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportedSchema {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportedRequest {
+    pub id: Uuid,
+    pub api_id: Uuid,
+    pub description: String,
+    pub dsl: String,
+}
+
+/// What storing a [`CapturePlan`] created, one report per host
+#[derive(Serialize, Debug)]
+pub struct CaptureReport {
+    pub dry_run: bool,
+    pub services: Vec<ImportReport>,
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,858 @@
+//! OpenAPI 3.0 and 3.1 documents, in YAML or JSON.
+//!
+//! The first server gives the base url and version of every request,
+//! e.g. `https://api.spotify.com/v1` becomes `https://api.spotify.com|v1`.
+//! Each GET operation becomes a request, component schemas holding
+//! a primitive become data types and the others response schemas.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::{label, DataTypePlan, ImportPlan, RequestPlan, SchemaPlan, ServicePlan};
+use crate::domain::{Constraints, Primitive, ResponseChild, ResponseField, ResponseSchema};
+use crate::parsers::url::parse_api_url;
+
+/// `$ref`s followed before giving up on a chain of references
+const MAX_REF_DEPTH: usize = 16;
+
+/// Parse a JSON or YAML document
+pub fn parse_document(body: &str) -> Result<Value, String> {
+    serde_json::from_str(body).or_else(|_| {
+        serde_yaml::from_str(body).map_err(|e| format!("document is neither JSON nor YAML: {}", e))
+    })
+}
+
+/// Map an OpenAPI document onto the catalogue
+pub fn plan(doc: &Value) -> Result<ImportPlan, String> {
+    let version = doc["openapi"]
+        .as_str()
+        .ok_or("not an OpenAPI document: the openapi field is missing")?;
+    if !version.starts_with("3.0") && !version.starts_with("3.1") {
+        return Err(format!(
+            "OpenAPI {} is not supported, only 3.0 and 3.1 are",
+            version
+        ));
+    }
+
+    let mut converter = Converter {
+        doc,
+        warnings: Vec::new(),
+    };
+    let mut plan = ImportPlan::default();
+
+    let server = server_url(doc)?;
+    let (host, _) = split_host(&server)?;
+    plan.service = ServicePlan {
+        title: doc["info"]["title"]
+            .as_str()
+            .ok_or("info.title is missing")?
+            .to_string(),
+        description: doc["info"]["description"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        url: host.to_string(),
+    };
+
+    let (base, vers) = split_version(&server);
+    let vers = match vers {
+        Some(vers) => vers,
+        None => {
+            let vers = doc["info"]["version"]
+                .as_str()
+                .filter(|v| {
+                    !v.is_empty() && !v.contains(|c: char| "/|".contains(c) || c.is_whitespace())
+                })
+                .ok_or(
+                    "the server url has no version segment and info.version can't be used as one",
+                )?;
+            converter.warnings.push(format!(
+                "server {} has no path, info.version {} is used as the version segment",
+                server, vers
+            ));
+            vers
+        }
+    };
+
+    converter.components(&mut plan);
+    converter.operations(&mut plan, base, vers);
+
+    plan.warnings = converter.warnings;
+    Ok(plan)
+}
+
+/// First server's url with its variables replaced by their defaults
+fn server_url(doc: &Value) -> Result<String, String> {
+    let server = &doc["servers"][0];
+    let mut url = server["url"]
+        .as_str()
+        .ok_or("the document has no servers, an absolute server url is needed")?
+        .to_string();
+
+    if let Some(variables) = server["variables"].as_object() {
+        for (name, variable) in variables {
+            if let Some(default) = variable["default"].as_str() {
+                url = url.replace(&format!("{{{}}}", name), default);
+            }
+        }
+    }
+
+    Ok(url.trim_end_matches('/').to_string())
+}
+
+/// `https://api.spotify.com/v1` -> (`api.spotify.com`, `/v1`)
+fn split_host(url: &str) -> Result<(&str, &str), String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| format!("server url {} is not an absolute http(s) url", url))?;
+    let path_start = rest.find('/').unwrap_or(rest.len());
+
+    match &rest[..path_start] {
+        "" => Err(format!("server url {} has no host", url)),
+        host => Ok((host, &rest[path_start..])),
+    }
+}
+
+/// `https://api.spotify.com/v1` -> (`https://api.spotify.com`, `v1`)
+fn split_version(url: &str) -> (&str, Option<&str>) {
+    match url.rfind('/') {
+        Some(i) if !url[..i].ends_with('/') => (&url[..i], Some(&url[i + 1..])),
+        _ => (url, None),
+    }
+}
+
+struct Converter<'a> {
+    doc: &'a Value,
+    warnings: Vec<String>,
+}
+
+impl<'a> Converter<'a> {
+    /// Follow `$ref`s within the document. Also returns the name of the
+    /// last component referenced, e.g. `Artist` for `#/components/schemas/Artist`.
+    fn deref(&self, mut value: &'a Value) -> (&'a Value, Option<&'a str>) {
+        let mut name = None;
+
+        for _ in 0..MAX_REF_DEPTH {
+            let reference = match value["$ref"].as_str() {
+                Some(reference) => reference,
+                None => break,
+            };
+
+            match reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.doc.pointer(pointer))
+            {
+                Some(target) => {
+                    name = reference.rsplit('/').next();
+                    value = target;
+                }
+                None => return (&Value::Null, name),
+            }
+        }
+
+        (value, name)
+    }
+
+    /// Component schemas holding a primitive become data types, objects become response schemas
+    fn components(&mut self, plan: &mut ImportPlan) {
+        let schemas = match self.doc["components"]["schemas"].as_object() {
+            Some(schemas) => schemas,
+            None => return,
+        };
+
+        for (name, schema) in schemas {
+            let (schema, _) = self.deref(schema);
+
+            if is_object(schema) {
+                let mut seen = vec![name.as_str()];
+                let mut tree = self.object(schema, name, &mut seen);
+
+                tree.description = name.clone();
+                plan.response_schemas.push(SchemaPlan {
+                    name: name.clone(),
+                    schema: tree,
+                });
+            } else if schema_type(schema) == Some("array") {
+                // only usable as the items of a field or response
+                continue;
+            } else {
+                let primitive = primitive_label(schema);
+                let constraints = constraints(schema)
+                    .parse(Primitive::from_name(primitive))
+                    .unwrap_or_else(|e| {
+                        self.warnings
+                            .push(format!("{}: constraints left out: {}", name, e));
+                        Constraints::default()
+                    });
+
+                plan.data_types.push(DataTypePlan {
+                    label: label(name),
+                    primitive: primitive.to_string(),
+                    constraints,
+                });
+            }
+        }
+    }
+
+    /// Response schema of an object, `path` names it in warnings.
+    /// `seen` holds the components being converted so recursive references are left out.
+    fn object(&mut self, schema: &'a Value, path: &str, seen: &mut Vec<&'a str>) -> ResponseSchema {
+        let mut tree = ResponseSchema {
+            description: schema["description"]
+                .as_str()
+                .or_else(|| schema["title"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            ..ResponseSchema::default()
+        };
+
+        for (property, value) in self.properties(schema, path, seen) {
+            let field_path = format!("{}.{}", path, property);
+            let (mut value, mut name) = self.deref(value);
+            let mut is_vec = false;
+
+            if schema_type(value) == Some("array") {
+                let (items, items_name) = self.deref(&value["items"]);
+
+                value = items;
+                name = items_name.or(name);
+                is_vec = true;
+            }
+
+            if is_object(value) {
+                if let Some(name) = name {
+                    if seen.contains(&name) {
+                        self.warnings.push(format!(
+                            "{}: recursive reference to {} left out",
+                            field_path, name
+                        ));
+                        continue;
+                    }
+                    seen.push(name);
+                }
+
+                let child = self.object(value, &field_path, seen);
+                if name.is_some() {
+                    seen.pop();
+                }
+
+                tree.schemas.push(ResponseChild {
+                    identifier: property.to_string(),
+                    is_vec,
+                    schema: child,
+                });
+            } else {
+                tree.data.push(ResponseField {
+                    identifier: property.to_string(),
+                    data_type: self.slot_label(value, name),
+                    is_vec,
+                });
+            }
+        }
+
+        tree
+    }
+
+    /// Properties of an object, including those of every `allOf` part.
+    /// Only the first `oneOf` or `anyOf` alternative is used.
+    /// Parts referring to a component in `seen` are left out.
+    fn properties(
+        &mut self,
+        schema: &'a Value,
+        path: &str,
+        seen: &mut Vec<&'a str>,
+    ) -> Vec<(&'a str, &'a Value)> {
+        let mut properties = Vec::new();
+        let mut parts: Vec<&'a Value> = schema["allOf"].as_array().into_iter().flatten().collect();
+
+        if let Some(own) = schema["properties"].as_object() {
+            properties.extend(own.iter().map(|(k, v)| (k.as_str(), v)));
+        }
+
+        for key in &["oneOf", "anyOf"] {
+            if let Some(first) = schema[*key].as_array().and_then(|a| a.first()) {
+                self.warnings.push(format!(
+                    "{}: only the first {} alternative is used",
+                    path, key
+                ));
+                parts.push(first);
+            }
+        }
+
+        for part in parts {
+            let (part, name) = self.deref(part);
+
+            match name {
+                Some(name) if seen.contains(&name) => {
+                    self.warnings.push(format!(
+                        "{}: recursive reference to {} left out",
+                        path, name
+                    ));
+                }
+                Some(name) => {
+                    seen.push(name);
+                    properties.extend(self.properties(part, path, seen));
+                    seen.pop();
+                }
+                None => properties.extend(self.properties(part, path, seen)),
+            }
+        }
+
+        // later parts override earlier ones
+        let mut index = HashMap::new();
+        let mut merged: Vec<(&str, &Value)> = Vec::new();
+        for (property, value) in properties {
+            match index.get(property) {
+                Some(&i) => merged[i] = (property, value),
+                None => {
+                    index.insert(property, merged.len());
+                    merged.push((property, value));
+                }
+            }
+        }
+
+        merged
+    }
+
+    /// Data type label of a primitive schema. Components are referred to by their own label.
+    fn slot_label(&self, schema: &Value, name: Option<&str>) -> String {
+        match name {
+            Some(name) if !is_object(schema) && schema_type(schema) != Some("array") => label(name),
+            _ => primitive_label(schema).to_string(),
+        }
+    }
+
+    /// Label and whether the parameter is a list
+    fn parameter_label(&self, parameter: &'a Value) -> (String, bool) {
+        let (schema, name) = self.deref(&parameter["schema"]);
+
+        if schema_type(schema) == Some("array") {
+            let (items, items_name) = self.deref(&schema["items"]);
+            (self.slot_label(items, items_name), true)
+        } else {
+            (self.slot_label(schema, name), false)
+        }
+    }
+
+    fn operations(&mut self, plan: &mut ImportPlan, base: &str, vers: &str) {
+        let paths = match self.doc["paths"].as_object() {
+            Some(paths) => paths,
+            None => return,
+        };
+        let mut skipped = 0;
+
+        for (path, item) in paths {
+            let (item, _) = self.deref(item);
+
+            skipped += ["put", "post", "delete", "options", "head", "patch", "trace"]
+                .iter()
+                .filter(|method| item.get(**method).is_some())
+                .count();
+
+            if let Some(operation) = item.get("get") {
+                let (operation, _) = self.deref(operation);
+
+                if let Some(request) = self.operation(plan, path, item, operation, base, vers) {
+                    plan.requests.push(request);
+                }
+            }
+        }
+
+        if skipped > 0 {
+            self.warnings
+                .push(format!("skipped {} operations other than GET", skipped));
+        }
+    }
+
+    fn operation(
+        &mut self,
+        plan: &mut ImportPlan,
+        path: &str,
+        item: &'a Value,
+        operation: &'a Value,
+        base: &str,
+        vers: &str,
+    ) -> Option<RequestPlan> {
+        let name = format!("GET {}", path);
+        let mut parameters: Vec<&Value> = Vec::new();
+        let mut segments = Vec::new();
+        let mut queries = Vec::new();
+        let mut headers = Vec::new();
+        let mut dsl;
+
+        // operation parameters override path item ones with the same name and location
+        for parameter in item["parameters"]
+            .as_array()
+            .into_iter()
+            .chain(operation["parameters"].as_array())
+            .flatten()
+        {
+            let (parameter, _) = self.deref(parameter);
+
+            parameters.retain(|p| p["name"] != parameter["name"] || p["in"] != parameter["in"]);
+            parameters.push(parameter);
+        }
+
+        for segment in path.split('/').filter(|s| !s.is_empty()) {
+            let template = segment
+                .strip_prefix('{')
+                .and_then(|s| s.strip_suffix('}'))
+                .filter(|s| !s.contains(&['{', '}', ','][..]));
+
+            match template {
+                Some(param) => {
+                    let label = parameters
+                        .iter()
+                        .find(|p| p["in"] == "path" && p["name"] == param)
+                        .map(|p| self.parameter_label(p).0)
+                        .unwrap_or_else(|| "string".to_string());
+
+                    segments.push(format!("{{{},{}}}", param, label));
+                }
+                None if segment.contains(&['{', '}', ','][..]) => {
+                    self.warnings.push(format!(
+                        "{}: segment {} mixes text and parameters, the operation was skipped",
+                        name, segment
+                    ));
+                    return None;
+                }
+                None => segments.push(segment.to_string()),
+            }
+        }
+
+        if segments.is_empty() {
+            self.warnings.push(format!(
+                "{}: the url DSL needs at least one path segment, the operation was skipped",
+                name
+            ));
+            return None;
+        }
+
+        for parameter in &parameters {
+            let param = parameter["name"].as_str().unwrap_or_default();
+            let (label, is_vec) = self.parameter_label(parameter);
+
+            match parameter["in"].as_str() {
+                Some("query") if param.contains(|c| "=&#[]".contains(c)) || param.is_empty() => {
+                    self.warnings.push(format!(
+                        "{}: query {} can't be written in the url DSL and was left out",
+                        name, param
+                    ));
+                }
+                Some("query") if is_vec => queries.push(format!("{}=[{}]", param, label)),
+                Some("query") => queries.push(format!("{}={}", param, label)),
+                Some("header") => headers.push(format!("{}={}", param, label)),
+                Some("cookie") => self
+                    .warnings
+                    .push(format!("{}: cookie {} was left out", name, param)),
+                _ => (),
+            }
+        }
+
+        dsl = format!("{}|{}/{}", base, vers, segments.join("/"));
+        if !queries.is_empty() {
+            dsl.push('?');
+            dsl.push_str(&queries.join("&"));
+        }
+        if !headers.is_empty() {
+            dsl.push('#');
+            dsl.push_str(&headers.join("&"));
+        }
+
+        if let Err(e) = parse_api_url(&dsl) {
+            self.warnings.push(format!(
+                "{}: {} is not valid in the url DSL ({}), the operation was skipped",
+                name, dsl, e
+            ));
+            return None;
+        }
+
+        Some(RequestPlan {
+            description: operation["summary"]
+                .as_str()
+                .or_else(|| operation["operationId"].as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| name.clone()),
+            response: self.response(plan, &name, operation),
+            dsl,
+        })
+    }
+
+    /// Name of the response schema of the operation's success response.
+    /// Inline schemas are added to the plan under the operation's name.
+    fn response(
+        &mut self,
+        plan: &mut ImportPlan,
+        name: &str,
+        operation: &'a Value,
+    ) -> Option<String> {
+        let responses = operation["responses"].as_object()?;
+        let code = responses
+            .keys()
+            .filter(|code| code.starts_with('2'))
+            .min_by_key(|code| (code.as_str() != "200", code.as_str()))?;
+        let (response, _) = self.deref(&responses[code]);
+        let content = response["content"].as_object()?;
+        let media = content.get("application/json").or_else(|| {
+            content
+                .iter()
+                .find(|(k, _)| k.contains("json"))
+                .map(|(_, v)| v)
+        })?;
+        let (mut schema, mut component) = self.deref(&media["schema"]);
+
+        if schema_type(schema) == Some("array") {
+            let (items, items_name) = self.deref(&schema["items"]);
+
+            self.warnings.push(format!(
+                "{}: the response is a list, its items are stored as the response schema",
+                name
+            ));
+            schema = items;
+            component = items_name;
+        }
+
+        if !is_object(schema) {
+            self.warnings.push(format!(
+                "{}: the response is not an object and was left out",
+                name
+            ));
+            return None;
+        }
+
+        if let Some(component) = component {
+            if plan.response_schemas.iter().any(|s| s.name == component) {
+                return Some(component.to_string());
+            }
+        }
+
+        let mut seen = component.into_iter().collect();
+        let mut tree = self.object(schema, name, &mut seen);
+        tree.description = name.to_string();
+        plan.response_schemas.push(SchemaPlan {
+            name: name.to_string(),
+            schema: tree,
+        });
+
+        Some(name.to_string())
+    }
+}
+
+/// `type` of a schema. In 3.1 it can be a list, of which `null` is ignored.
+fn schema_type(schema: &Value) -> Option<&str> {
+    match &schema["type"] {
+        Value::String(t) => Some(t),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null"),
+        _ if schema.get("properties").is_some() => Some("object"),
+        _ if schema.get("items").is_some() => Some("array"),
+        _ => None,
+    }
+}
+
+fn is_object(schema: &Value) -> bool {
+    schema_type(schema) == Some("object")
+        || ["allOf", "oneOf", "anyOf"]
+            .iter()
+            .any(|key| schema.get(*key).is_some())
+}
+
+/// Built-in data type of a primitive schema
+fn primitive_label(schema: &Value) -> &'static str {
+    match (schema_type(schema), schema["format"].as_str()) {
+        (Some("integer"), _) => "int",
+        (Some("number"), _) => "float",
+        (Some("boolean"), _) => "bool",
+        (Some("string"), Some("uuid")) => "uuid",
+        (Some("string"), Some("date-time")) => "datetime",
+        _ => "string",
+    }
+}
+
+/// JSON schema validation keywords as data type constraints
+fn constraints(schema: &Value) -> Constraints {
+    let length = |key: &str| schema[key].as_u64().map(|l| l.min(i32::MAX as u64) as i32);
+
+    Constraints {
+        // JSON schema patterns match anywhere in the value, ours match all of it
+        pattern: schema["pattern"].as_str().map(|p| {
+            let start = if p.starts_with('^') { "" } else { ".*" };
+            let end = if p.ends_with('$') { "" } else { ".*" };
+            format!("{}{}{}", start, p, end)
+        }),
+        min_length: length("minLength"),
+        max_length: length("maxLength"),
+        min_value: schema["minimum"].as_f64(),
+        max_value: schema["maximum"].as_f64(),
+        enum_values: schema["enum"].as_array().map(|values| {
+            values
+                .iter()
+                .filter_map(|v| match v {
+                    Value::String(s) => Some(s.clone()),
+                    Value::Null => None,
+                    v => Some(v.to_string()),
+                })
+                .collect()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPOTIFY: &str = r#"
+openapi: 3.0.3
+info:
+  title: Spotify Web API
+  description: Music catalogue
+  version: 2021.10.1
+servers:
+  - url: https://{host}/v1
+    variables:
+      host:
+        default: api.spotify.com
+paths:
+  /artists/{id}:
+    parameters:
+      - $ref: '#/components/parameters/ArtistId'
+    get:
+      summary: Get an artist
+      parameters:
+        - name: market
+          in: query
+          schema:
+            $ref: '#/components/schemas/Market'
+        - name: Authorization
+          in: header
+          schema:
+            type: string
+      responses:
+        200:
+          description: An artist
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Artist'
+    delete:
+      responses:
+        204:
+          description: Gone
+  /tracks:
+    get:
+      parameters:
+        - name: ids
+          in: query
+          schema:
+            type: array
+            items:
+              type: string
+        - name: limit
+          in: query
+          schema:
+            type: integer
+      responses:
+        '200':
+          description: Tracks
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  total:
+                    type: integer
+                  items:
+                    type: array
+                    items:
+                      $ref: '#/components/schemas/Artist'
+  /:
+    get:
+      responses:
+        '200':
+          description: Root
+components:
+  parameters:
+    ArtistId:
+      name: id
+      in: path
+      required: true
+      schema:
+        $ref: '#/components/schemas/ArtistId'
+  schemas:
+    ArtistId:
+      type: string
+      pattern: '^[0-9A-Za-z]{22}$'
+    Market:
+      type: string
+      enum: [US, GB]
+    Artist:
+      type: object
+      properties:
+        id:
+          $ref: '#/components/schemas/ArtistId'
+        name:
+          type: string
+        genres:
+          type: array
+          items:
+            type: string
+        related:
+          type: array
+          items:
+            $ref: '#/components/schemas/Artist'
+        images:
+          type: array
+          items:
+            type: object
+            properties:
+              url:
+                type: string
+              height:
+                type: [integer, 'null']
+"#;
+
+    fn spotify() -> ImportPlan {
+        plan(&parse_document(SPOTIFY).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn service_comes_from_info_and_the_first_server() {
+        let plan = spotify();
+
+        assert_eq!(plan.service.title, "Spotify Web API");
+        assert_eq!(plan.service.description, "Music catalogue");
+        assert_eq!(plan.service.url, "api.spotify.com");
+    }
+
+    #[test]
+    fn primitive_components_become_data_types() {
+        let plan = spotify();
+        let artist_id = plan
+            .data_types
+            .iter()
+            .find(|t| t.label == "ArtistId")
+            .unwrap();
+
+        assert_eq!(artist_id.primitive, "string");
+        assert_eq!(
+            artist_id.constraints.pattern.as_deref(),
+            Some("^[0-9A-Za-z]{22}$")
+        );
+        assert!(plan.data_types.iter().any(|t| t.label == "Market"));
+    }
+
+    #[test]
+    fn get_operations_become_requests() {
+        let plan = spotify();
+        let dsls: Vec<_> = plan.requests.iter().map(|r| r.dsl.as_str()).collect();
+
+        assert_eq!(
+            dsls,
+            [
+                "https://api.spotify.com|v1/artists/{id,ArtistId}?market=Market#Authorization=string",
+                "https://api.spotify.com|v1/tracks?ids=[string]&limit=int",
+            ]
+        );
+        assert_eq!(plan.requests[0].description, "Get an artist");
+        assert_eq!(plan.requests[0].response.as_deref(), Some("Artist"));
+        assert_eq!(plan.requests[1].description, "GET /tracks");
+        assert_eq!(plan.requests[1].response.as_deref(), Some("GET /tracks"));
+    }
+
+    #[test]
+    fn object_components_become_response_schemas() {
+        let plan = spotify();
+        let artist = &plan
+            .response_schemas
+            .iter()
+            .find(|s| s.name == "Artist")
+            .unwrap()
+            .schema;
+        let fields: Vec<_> = artist
+            .data
+            .iter()
+            .map(|f| (f.identifier.as_str(), f.data_type.as_str(), f.is_vec))
+            .collect();
+
+        assert_eq!(
+            fields,
+            [
+                ("genres", "string", true),
+                ("id", "ArtistId", false),
+                ("name", "string", false)
+            ]
+        );
+        assert_eq!(artist.schemas.len(), 1);
+        assert_eq!(artist.schemas[0].identifier, "images");
+        assert!(artist.schemas[0].is_vec);
+        assert_eq!(artist.schemas[0].schema.data[0].data_type, "int");
+    }
+
+    #[test]
+    fn left_out_parts_are_reported() {
+        let warnings = spotify().warnings.join("\n");
+
+        assert!(warnings.contains("Artist.related: recursive reference to Artist left out"));
+        assert!(warnings.contains("GET /: the url DSL needs at least one path segment"));
+        assert!(warnings.contains("skipped 1 operations other than GET"));
+    }
+
+    #[test]
+    fn self_referencing_all_of_parts_are_left_out() {
+        let doc = parse_document(
+            r##"{
+                "openapi": "3.1.0",
+                "info": {"title": "Loop", "version": "v1"},
+                "servers": [{"url": "https://api.loop.com/v1"}],
+                "paths": {},
+                "components": {"schemas": {
+                    "A": {"type": "object", "allOf": [
+                        {"$ref": "#/components/schemas/A"},
+                        {"$ref": "#/components/schemas/B"}
+                    ]},
+                    "B": {"type": "object",
+                          "properties": {"name": {"type": "string"}},
+                          "anyOf": [{"$ref": "#/components/schemas/A"}]}
+                }}
+            }"##,
+        )
+        .unwrap();
+        let plan = plan(&doc).unwrap();
+        let warnings = plan.warnings.join("\n");
+
+        assert_eq!(plan.response_schemas[0].schema.data[0].identifier, "name");
+        assert!(warnings.contains("A: recursive reference to A left out"));
+        assert!(warnings.contains("B: recursive reference to A left out"));
+    }
+
+    #[test]
+    fn json_documents_and_servers_without_a_version_segment() {
+        let doc = parse_document(
+            r#"{
+                "openapi": "3.1.0",
+                "info": {"title": "TickTick", "version": "v1"},
+                "servers": [{"url": "https://api.ticktick.com/"}],
+                "paths": {"/project": {"get": {"responses": {}}}}
+            }"#,
+        )
+        .unwrap();
+        let plan = plan(&doc).unwrap();
+
+        assert_eq!(plan.requests[0].dsl, "https://api.ticktick.com|v1/project");
+        assert_eq!(plan.requests[0].response, None);
+    }
+
+    #[test]
+    fn unsupported_documents_are_rejected() {
+        for doc in &[
+            r#"{"swagger": "2.0"}"#,
+            r#"{"openapi": "3.0.0", "info": {"title": "x", "version": "1"}}"#,
+            r#"{"openapi": "3.0.0", "info": {"title": "x", "version": "1"}, "servers": [{"url": "/v1"}]}"#,
+        ] {
+            assert!(plan(&parse_document(doc).unwrap()).is_err(), "{}", doc);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Postman collections, format v2.0 and v2.1.
+//!
+//! Collection variables are filled in, path segments written as
+//! `{{variable}}` or `:variable` that are left become slots. Saved example
+//! responses with a JSON body are used to infer response schemas.
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::capture::{self, Call};
+use super::CapturePlan;
+
+/// Map a collection onto the catalogue, one service per host
+pub fn plan(doc: &Value) -> Result<CapturePlan, String> {
+    let schema = doc["info"]["schema"]
+        .as_str()
+        .ok_or("not a Postman collection: info.schema is missing")?;
+    if !schema.contains("/v2.0.") && !schema.contains("/v2.1.") {
+        return Err(format!(
+            "collection format {} is not supported, only v2.0 and v2.1 are",
+            schema
+        ));
+    }
+
+    let name = doc["info"]["name"].as_str().unwrap_or("without a name");
+    let variables: HashMap<&str, String> = doc["variable"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| {
+            let value = match &v["value"] {
+                Value::String(s) => s.clone(),
+                Value::Null => return None,
+                other => other.to_string(),
+            };
+            Some((v["key"].as_str()?, value))
+        })
+        .collect();
+    let mut calls = Vec::new();
+
+    items(&doc["item"], &variables, &mut calls);
+
+    capture::plan(calls, &format!("Postman collection {}", name))
+}
+
+/// Collect the requests of `items`, descending into folders
+fn items(items: &Value, variables: &HashMap<&str, String>, calls: &mut Vec<Call>) {
+    for item in items.as_array().into_iter().flatten() {
+        if item["item"].is_array() {
+            self::items(&item["item"], variables, calls);
+            continue;
+        }
+
+        let request = &item["request"];
+        let url = match request {
+            Value::String(url) => url.as_str(),
+            request => request["url"]
+                .as_str()
+                .or_else(|| request["url"]["raw"].as_str())
+                .unwrap_or_default(),
+        };
+
+        calls.push(Call {
+            name: item["name"].as_str().map(str::to_string),
+            method: request["method"].as_str().unwrap_or("GET").to_string(),
+            url: resolve(url, variables),
+            bodies: item["response"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|r| matches!(r["code"].as_u64(), None | Some(200..=299)))
+                .filter_map(|r| serde_json::from_str(r["body"].as_str()?).ok())
+                .collect(),
+        });
+    }
+}
+
+/// Fill in the collection's variables and write the others as `:variable`.
+/// Urls without a scheme are taken to be https, as Postman does.
+fn resolve(url: &str, variables: &HashMap<&str, String>) -> String {
+    lazy_static! {
+        static ref VARIABLE_RE: Regex = Regex::new(r"\{\{([A-Za-z_][\w.-]*)\}\}").unwrap();
+    }
+
+    let url = VARIABLE_RE.replace_all(url.trim(), |c: &Captures| match variables.get(&c[1]) {
+        Some(value) => value.clone(),
+        None => format!(":{}", &c[1]),
+    });
+
+    match url.contains("://") {
+        true => url.to_string(),
+        false => format!("https://{}", url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn collection(items: Value) -> Value {
+        json!({
+            "info": {
+                "name": "Spotify",
+                "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+            },
+            "variable": [{"key": "baseUrl", "value": "https://api.spotify.com/v1"}],
+            "item": items
+        })
+    }
+
+    #[test]
+    fn folders_and_variables_are_followed() {
+        let capture = plan(&collection(json!([
+            {"name": "Artists", "item": [{
+                "name": "Get an artist",
+                "request": {
+                    "method": "GET",
+                    "url": {"raw": "{{baseUrl}}/artists/{{artistId}}?market=SE"}
+                },
+                "response": [{
+                    "name": "Radiohead",
+                    "code": 200,
+                    "body": "{\"name\": \"Radiohead\", \"popularity\": 80}"
+                }]
+            }]},
+            {"name": "Me", "request": "api.spotify.com/v1/me"},
+            {"name": "Follow", "request": {"method": "PUT", "url": "{{baseUrl}}/me/following"}}
+        ])))
+        .unwrap();
+        let plan = &capture.plans[0];
+
+        assert_eq!(
+            plan.service.description,
+            "Recorded in Postman collection Spotify"
+        );
+        assert_eq!(
+            plan.requests
+                .iter()
+                .map(|r| r.dsl.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "https://api.spotify.com|v1/artists/{artistId,string}?market=string",
+                "https://api.spotify.com|v1/me",
+            ]
+        );
+        assert_eq!(plan.requests[0].response.as_deref(), Some("Get an artist"));
+        assert_eq!(plan.response_schemas[0].schema.data.len(), 2);
+        assert_eq!(capture.warnings, vec!["skipped 1 requests other than GET"]);
+    }
+
+    #[test]
+    fn other_formats_are_rejected() {
+        assert!(plan(&json!({"info": {"schema": "https://schema.getpostman.com/json/collection/v1.0.0/collection.json"}})).is_err());
+        assert!(plan(&json!({"log": {"entries": []}})).is_err());
+    }
+}
@@ -1,32 +1,51 @@
 use axum::{
-    handler::{get, post},
+    handler::{delete, get, post},
     AddExtensionLayer, Router, Server,
 };
 
 use routes::*;
 
+use configuration::OutboundSettings;
 use sqlx::PgPool;
 use std::future::Future;
 use std::net::TcpListener;
 use tower_http::trace::TraceLayer;
 use tracing::Level;
 
+pub mod cli;
 pub mod configuration;
 mod domain;
+mod error;
+mod export;
+mod extract;
+mod import;
 //mod http;
+mod mock;
+mod parsers;
 pub mod routes;
+mod store;
 pub mod telemetry;
 pub mod tracelog;
 
 pub fn run(
     listener: TcpListener,
     db_pool: PgPool,
+    outbound: OutboundSettings,
 ) -> Result<impl Future<Output = hyper::Result<()>>, hyper::Error> {
     let app;
     let logger;
     let server;
 
     let db_pool = AddExtensionLayer::new(db_pool);
+    let http_client = AddExtensionLayer::new(
+        reqwest::Client::builder()
+            .timeout(outbound.timeout())
+            // a redirect is answered as is rather than followed to wherever it points
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Failed to build the outbound http client"),
+    );
+    let outbound = AddExtensionLayer::new(outbound);
 
     logger = tracelog::TracingLogger {
         req_level: Some(Level::INFO),
@@ -39,8 +58,51 @@ pub fn run(
     app = Router::new()
         .route("/health_check", get(health_check))
         .route("/form", get(get_api_form).post(url_form))
-        .route("/service", post(new_service))
+        .route("/service", get(list_services).post(new_service))
+        .route(
+            "/service/:id",
+            get(get_service)
+                .patch(update_service)
+                .delete(delete_service),
+        )
+        .route("/service/:id/api", get(list_apis).post(new_api))
+        .route(
+            "/api/:id",
+            get(get_api).patch(update_api).delete(delete_api),
+        )
+        .route("/api/:id/request", get(list_api_requests))
+        .route("/api/:id/openapi.json", get(export_openapi))
+        .route("/api/:id/client.rs", get(export_rust_client))
+        .route("/api/:id/client.ts", get(export_typescript_client))
+        .route("/request/:id", get(get_request))
+        .route("/request/:id/execute", post(execute_request))
+        .route(
+            "/data_primitive",
+            get(list_data_primitives).post(new_data_primitive),
+        )
+        .route("/data_primitive/:id", delete(delete_data_primitive))
+        .route("/data_type", get(list_data_types).post(new_data_type))
+        .route(
+            "/data_type/:id",
+            get(get_data_type)
+                .patch(update_data_type)
+                .delete(delete_data_type),
+        )
+        .route("/data_type/:id/validate", post(validate_data_type))
+        .route("/response_schema", post(new_response_schema))
+        .route("/response_schema/:id", get(get_response_schema))
+        .route("/response_schema/:id/schemas", post(link_response_schema))
+        .route("/response_schema/infer", post(infer_response_schema))
+        .route("/response_schema/:id/validate", post(validate_payload))
+        .route("/plan", get(plan_requests))
+        .route("/graph", get(export_graph))
+        .route("/import/openapi", post(import_openapi))
+        .route("/import/postman", post(import_postman))
+        .route("/import/har", post(import_har))
+        .nest("/mock", get(mock_api))
         .layer(db_pool)
+        .layer(http_client)
+        .layer(outbound)
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(logger.clone())
@@ -48,7 +110,8 @@ pub fn run(
                 .on_eos(())
                 .on_body_chunk(())
                 .on_failure(logger.clone()),
-        );
+        )
+        .layer(tracelog::RequestIdLayer);
 
     server = Server::from_tcp(listener)?.serve(app.into_make_service());
 
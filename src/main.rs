@@ -1,3 +1,4 @@
+use daysquare_backend::cli::{self, Command};
 use daysquare_backend::configuration::get_configuration;
 use daysquare_backend::telemetry::{get_subscriber, init_subscriber};
 use sqlx::PgPool;
@@ -5,24 +6,42 @@ use std::net::TcpListener;
 
 #[tokio::main]
 async fn main() -> hyper::Result<()> {
+    let command;
     let configuration;
     let connection_pool;
     let subscriber;
     let listener;
     let server;
 
+    command = Command::parse(&std::env::args().skip(1).collect::<Vec<_>>()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+
     configuration = get_configuration().expect("Failed to read configuration.");
     connection_pool = PgPool::connect(&configuration.database.connection_string())
         .await
         .expect("Failed to connect to Postgres.");
 
+    // Subcommands print to stdout, so they run before logging is set up
+    if let Command::RustClient(api_id) = command {
+        match cli::rust_client(&connection_pool, api_id).await {
+            Ok(module) => print!("{}", module),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1)
+            }
+        }
+        return Ok(());
+    }
+
     subscriber = get_subscriber("daysquare".into(), "debug".into(), std::io::stdout);
     init_subscriber(subscriber);
 
     listener =
         TcpListener::bind(configuration.server.public_addr()).expect("Failed to bind to address");
 
-    server = daysquare_backend::run(listener, connection_pool)?;
+    server = daysquare_backend::run(listener, connection_pool, configuration.outbound)?;
 
     tracing::debug!(
         "listening on 127.0.0.1:{}",
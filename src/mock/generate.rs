@@ -0,0 +1,373 @@
+//! Synthetic values that satisfy a data type.
+//!
+//! Values come from a seeded generator so a mock answers a url
+//! the same way every time. A value is drawn a few times until it
+//! passes every constraint of its data type, the last draw is kept
+//! if none does.
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use regex_syntax::hir::{Class, Hir, HirKind, Literal, RepetitionKind, RepetitionRange};
+use serde_json::{json, Value};
+
+use crate::domain::{Constraints, DataTypeRecord, Primitive};
+
+/// Draws of a value before giving up on its constraints
+const ATTEMPTS: u32 = 48;
+/// Extra repeats an unbounded repetition gets at most, e.g. `+` or `*`
+const SPREAD: u32 = 8;
+/// Generated datetimes are within two years of 2021-01-01T00:00:00Z
+const DATETIME_START: i64 = 1_609_459_200;
+const DATETIME_RANGE: u64 = 2 * 365 * 24 * 60 * 60;
+
+/// splitmix64, enough to pick values that look random
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, `0` when `n` is `0`
+    pub fn below(&mut self, n: u64) -> u64 {
+        match n {
+            0 => 0,
+            n => self.next() % n,
+        }
+    }
+
+    /// A number in `lo..=hi`
+    fn between(&mut self, lo: i64, hi: i64) -> i64 {
+        let span = hi.wrapping_sub(lo) as u64;
+
+        match span.checked_add(1) {
+            Some(n) => lo.wrapping_add(self.below(n) as i64),
+            None => self.next() as i64,
+        }
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// A value of `data_type`, typed the way a JSON payload holds it.
+/// Values without a data type are strings.
+pub fn value(rng: &mut Rng, data_type: Option<&DataTypeRecord>) -> Value {
+    let primitive = data_type
+        .map(|t| Primitive::from_name(&t.primitive))
+        .unwrap_or(Primitive::String);
+    let default = Constraints::default();
+    let constraints = data_type.map(|t| &t.constraints).unwrap_or(&default);
+    let pattern = constraints
+        .pattern
+        .as_deref()
+        .and_then(|p| regex_syntax::Parser::new().parse(p).ok());
+    let mut text = String::new();
+
+    for attempt in 0..ATTEMPTS {
+        text = match (&constraints.enum_values, &pattern) {
+            (Some(values), _) if !values.is_empty() => rng.pick(values).clone(),
+            (_, Some(hir)) => {
+                let stretch = match constraints.min_length.or(constraints.max_length) {
+                    // Walk the lengths up so a fixed length is found
+                    Some(_) => Stretch::Fixed(attempt),
+                    None => Stretch::Random,
+                };
+                let mut text = String::new();
+                Pattern {
+                    rng: &mut *rng,
+                    stretch,
+                }
+                .push(hir, &mut text);
+                text
+            }
+            _ => plain(rng, primitive, constraints),
+        };
+
+        if constraints.validate(primitive, &text).is_empty() {
+            break;
+        }
+    }
+
+    typed(primitive, text)
+}
+
+/// A text value as JSON, numbers and booleans unquoted
+pub fn typed(primitive: Primitive, text: String) -> Value {
+    let parsed = match primitive {
+        Primitive::Int => text.parse::<i64>().ok().map(|n| json!(n)),
+        Primitive::Float => text
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(|n| json!(n)),
+        Primitive::Bool => text.parse::<bool>().ok().map(Value::Bool),
+        _ => None,
+    };
+
+    parsed.unwrap_or(Value::String(text))
+}
+
+/// A value of `primitive` within the length and value bounds of `constraints`
+fn plain(rng: &mut Rng, primitive: Primitive, constraints: &Constraints) -> String {
+    match primitive {
+        Primitive::Int => {
+            let (lo, hi) = bounds(constraints, 1000.0);
+            rng.between(lo.ceil() as i64, hi.floor() as i64).to_string()
+        }
+        Primitive::Float => {
+            let (lo, hi) = bounds(constraints, 1000.0);
+            let fraction = rng.below(10_001) as f64 / 10_000.0;
+            let number = ((lo + (hi - lo) * fraction) * 100.0).round() / 100.0;
+            number.max(lo).min(hi).to_string()
+        }
+        Primitive::Bool => (rng.below(2) == 1).to_string(),
+        Primitive::Uuid => {
+            let mut bytes = [0; 16];
+            bytes[..8].copy_from_slice(&rng.next().to_be_bytes());
+            bytes[8..].copy_from_slice(&rng.next().to_be_bytes());
+            uuid::Builder::from_bytes(bytes)
+                .set_variant(uuid::Variant::RFC4122)
+                .set_version(uuid::Version::Random)
+                .build()
+                .to_string()
+        }
+        Primitive::Datetime => Utc
+            .timestamp_opt(DATETIME_START + rng.below(DATETIME_RANGE) as i64, 0)
+            .single()
+            .unwrap_or_else(Utc::now)
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+        Primitive::String | Primitive::Const => {
+            let min = constraints.min_length.unwrap_or(0).max(0) as i64;
+            let max = constraints.max_length.map(i64::from).unwrap_or(min.max(12));
+            let length = rng.between(min.max(8).min(max), max.min(12).max(min));
+
+            (0..length)
+                .map(|_| (b'a' + rng.below(26) as u8) as char)
+                .collect()
+        }
+    }
+}
+
+/// The range of numbers `constraints` allow, `width` wide when open on a side
+fn bounds(constraints: &Constraints, width: f64) -> (f64, f64) {
+    match (constraints.min_value, constraints.max_value) {
+        (Some(min), Some(max)) => (min, max),
+        (Some(min), None) => (min, min + width),
+        (None, Some(max)) => (max - width, max),
+        (None, None) => (0.0, width),
+    }
+}
+
+/// How many extra repeats unbounded repetitions get
+#[derive(Clone, Copy)]
+enum Stretch {
+    Random,
+    Fixed(u32),
+}
+
+/// Writes a string matching a parsed pattern
+struct Pattern<'r> {
+    rng: &'r mut Rng,
+    stretch: Stretch,
+}
+
+impl Pattern<'_> {
+    fn push(&mut self, hir: &Hir, out: &mut String) {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => (),
+            HirKind::Literal(Literal::Unicode(c)) => out.push(*c),
+            HirKind::Literal(Literal::Byte(b)) => out.push(char::from(*b)),
+            HirKind::Class(Class::Unicode(class)) => {
+                let ranges: Vec<_> = class
+                    .ranges()
+                    .iter()
+                    .map(|r| (r.start() as u32, r.end() as u32))
+                    .collect();
+                out.push(self.char(&ranges));
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                let ranges: Vec<_> = class
+                    .ranges()
+                    .iter()
+                    .map(|r| (r.start() as u32, r.end() as u32))
+                    .collect();
+                out.push(self.char(&ranges));
+            }
+            HirKind::Repetition(repetition) => {
+                let (min, max) = match &repetition.kind {
+                    RepetitionKind::ZeroOrOne => (0, Some(1)),
+                    RepetitionKind::ZeroOrMore => (0, None),
+                    RepetitionKind::OneOrMore => (1, None),
+                    RepetitionKind::Range(RepetitionRange::Exactly(n)) => (*n, Some(*n)),
+                    RepetitionKind::Range(RepetitionRange::AtLeast(n)) => (*n, None),
+                    RepetitionKind::Range(RepetitionRange::Bounded(min, max)) => (*min, Some(*max)),
+                };
+                let extra = match (max, self.stretch) {
+                    (Some(max), _) => self.rng.below(u64::from(max - min) + 1) as u32,
+                    (None, Stretch::Fixed(extra)) => extra,
+                    (None, Stretch::Random) => self.rng.below(u64::from(SPREAD) + 1) as u32,
+                };
+
+                for _ in 0..min + extra {
+                    self.push(&repetition.hir, out);
+                }
+            }
+            HirKind::Group(group) => self.push(&group.hir, out),
+            HirKind::Concat(hirs) => {
+                for hir in hirs {
+                    self.push(hir, out);
+                }
+            }
+            HirKind::Alternation(hirs) => {
+                let hir = self.rng.pick(hirs);
+                self.push(hir, out);
+            }
+        }
+    }
+
+    /// A character of the class, ASCII letters and digits first so ids look like ids
+    fn char(&mut self, ranges: &[(u32, u32)]) -> char {
+        let ascii = |keep: fn(&char) -> bool| -> Vec<char> {
+            ranges
+                .iter()
+                .flat_map(|&(start, end)| start.min(128)..end.min(127) + 1)
+                .filter_map(char::from_u32)
+                .filter(keep)
+                .collect()
+        };
+
+        for tier in [
+            ascii(char::is_ascii_alphanumeric),
+            ascii(char::is_ascii_graphic),
+        ] {
+            if !tier.is_empty() {
+                return *self.rng.pick(&tier);
+            }
+        }
+
+        match ranges {
+            [] => 'x',
+            ranges => {
+                let (start, end) = *self.rng.pick(ranges);
+                char::from_u32(start + self.rng.below(u64::from(end - start) + 1) as u32)
+                    .unwrap_or('x')
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn data_type(primitive: &str, constraints: Constraints) -> DataTypeRecord {
+        DataTypeRecord {
+            id: Uuid::new_v4(),
+            label: "label".to_string(),
+            data_primitive_id: Uuid::new_v4(),
+            primitive: primitive.to_string(),
+            constraints,
+        }
+    }
+
+    fn values(data_type: &DataTypeRecord) -> Vec<Value> {
+        let mut rng = Rng::new(7);
+        (0..50).map(|_| value(&mut rng, Some(data_type))).collect()
+    }
+
+    fn fits(data_type: &DataTypeRecord, value: &Value) -> bool {
+        let text = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        data_type
+            .constraints
+            .validate(Primitive::from_name(&data_type.primitive), &text)
+            .is_empty()
+    }
+
+    #[test]
+    fn values_match_their_pattern() {
+        for (pattern, min_length) in &[
+            ("[0-9A-Za-z]{22}", None),
+            ("[0-9A-Za-z]+", Some(22)),
+            ("(sp|yt)-[a-f0-9]{4}(-[A-Z])?", None),
+            ("[^/]+\\.jpg", None),
+        ] {
+            let data_type = data_type(
+                "string",
+                Constraints {
+                    pattern: Some(pattern.to_string()),
+                    min_length: *min_length,
+                    ..Constraints::default()
+                },
+            );
+
+            for value in values(&data_type) {
+                assert!(fits(&data_type, &value), "{} {}", pattern, value);
+            }
+        }
+    }
+
+    #[test]
+    fn values_honour_their_constraints() {
+        let popularity = data_type(
+            "int",
+            Constraints {
+                min_value: Some(0.0),
+                max_value: Some(100.0),
+                ..Constraints::default()
+            },
+        );
+        let market = data_type(
+            "string",
+            Constraints {
+                enum_values: Some(vec!["SE".to_string(), "GB".to_string()]),
+                ..Constraints::default()
+            },
+        );
+        let code = data_type(
+            "string",
+            Constraints {
+                min_length: Some(2),
+                max_length: Some(3),
+                ..Constraints::default()
+            },
+        );
+
+        for data_type in &[popularity, market, code] {
+            for value in values(data_type) {
+                assert!(fits(data_type, &value), "{}", value);
+            }
+        }
+    }
+
+    #[test]
+    fn values_are_typed_by_primitive() {
+        for primitive in &["int", "float", "bool", "uuid", "datetime"] {
+            let data_type = data_type(primitive, Constraints::default());
+
+            for value in values(&data_type) {
+                assert!(fits(&data_type, &value), "{} {}", primitive, value);
+                assert_eq!(value.is_string(), ["uuid", "datetime"].contains(primitive));
+            }
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_values() {
+        let data_type = data_type("uuid", Constraints::default());
+
+        assert_eq!(values(&data_type), values(&data_type));
+    }
+}
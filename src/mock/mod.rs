@@ -0,0 +1,358 @@
+//! Synthetic responses for the stored requests of an API version.
+//!
+//! A url is answered by the request whose path it is an instance of,
+//! with JSON shaped like that request's response schema. Values fit
+//! the data types of their fields, and a field named after a path slot
+//! of the same data type echoes the value from the url.
+
+mod generate;
+
+use percent_encoding::percent_decode_str;
+use serde_json::{Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::domain::{DataTypeRecord, Primitive, RequestDefinition, ResponseSchema, CONST_TYPE};
+use crate::export::ApiExport;
+use generate::Rng;
+
+/// Items in a generated array are between one and this many
+const MAX_ITEMS: u64 = 3;
+
+/// The answer a mock of `export` gives to `path`, e.g. `v1/artists/0OdUWJ0sBjDrqHygGUXeCF`.
+/// `None` when no stored request matches it.
+///
+/// A literal segment is preferred over a slot when several requests match,
+/// so `v1/me/albums` goes to `/me/albums` rather than `/{id}/albums`.
+pub fn respond(export: &ApiExport, path: &str) -> Option<Value> {
+    let data_types: HashMap<&str, &DataTypeRecord> = export
+        .data_types
+        .iter()
+        .map(|t| (t.label.as_str(), t))
+        .collect();
+    let segments: Vec<String> = path
+        .trim_matches('/')
+        .split('/')
+        .map(|s| percent_decode_str(s).decode_utf8_lossy().into_owned())
+        .collect();
+    let (vers, segments) = segments.split_first()?;
+
+    if vers != &export.api.vers {
+        return None;
+    }
+
+    // Reversed so the first stored request wins a tie
+    let (request, values) = export
+        .requests
+        .iter()
+        .rev()
+        .filter_map(|r| matched(r, segments, &data_types).map(|values| (r, values)))
+        .max_by_key(|(r, _)| {
+            r.paths
+                .iter()
+                .map(|p| p.data_type == CONST_TYPE)
+                .collect::<Vec<_>>()
+        })?;
+
+    let mut hasher = DefaultHasher::new();
+    request.id.hash(&mut hasher);
+    segments.hash(&mut hasher);
+
+    let mut mock = Mock {
+        data_types: &data_types,
+        rng: Rng::new(hasher.finish()),
+    };
+
+    Some(
+        export
+            .response_schemas
+            .iter()
+            .find(|s| s.id == Some(request.response_schema_id))
+            .map(|schema| mock.object(schema, &values))
+            .unwrap_or_else(|| Value::Object(Map::new())),
+    )
+}
+
+/// Values of the path slots of `request` if `segments` is an instance of its path
+fn matched<'r>(
+    request: &'r RequestDefinition,
+    segments: &[String],
+    data_types: &HashMap<&str, &DataTypeRecord>,
+) -> Option<HashMap<&'r str, (&'r str, String)>> {
+    let mut values = HashMap::new();
+
+    if request.paths.len() != segments.len() {
+        return None;
+    }
+
+    for (slot, segment) in request.paths.iter().zip(segments) {
+        if slot.data_type == CONST_TYPE {
+            if &slot.name != segment {
+                return None;
+            }
+            continue;
+        }
+
+        if let Some(data_type) = data_types.get(slot.data_type.as_str()) {
+            let primitive = Primitive::from_name(&data_type.primitive);
+
+            if !data_type
+                .constraints
+                .validate(primitive, segment)
+                .is_empty()
+            {
+                return None;
+            }
+        }
+        if segment.is_empty() {
+            return None;
+        }
+
+        values.insert(
+            slot.name.as_str(),
+            (slot.data_type.as_str(), segment.clone()),
+        );
+    }
+
+    Some(values)
+}
+
+struct Mock<'a> {
+    data_types: &'a HashMap<&'a str, &'a DataTypeRecord>,
+    rng: Rng,
+}
+
+impl Mock<'_> {
+    /// An object shaped like `schema`. `echoed` holds the path slot values
+    /// of the request by slot name, with their data type label.
+    fn object(&mut self, schema: &ResponseSchema, echoed: &HashMap<&str, (&str, String)>) -> Value {
+        let mut object = Map::new();
+
+        for field in &schema.data {
+            let data_type = self.data_types.get(field.data_type.as_str()).copied();
+            let value = match echoed.get(field.identifier.as_str()) {
+                Some((label, value)) if !field.is_vec && *label == field.data_type => {
+                    let primitive = data_type
+                        .map(|t| Primitive::from_name(&t.primitive))
+                        .unwrap_or(Primitive::String);
+                    generate::typed(primitive, value.clone())
+                }
+                _ => self.each(field.is_vec, |m| generate::value(&mut m.rng, data_type)),
+            };
+
+            object.insert(field.identifier.clone(), value);
+        }
+
+        for child in &schema.schemas {
+            let value = self.each(child.is_vec, |m| m.object(&child.schema, &HashMap::new()));
+
+            object.insert(child.identifier.clone(), value);
+        }
+
+        Value::Object(object)
+    }
+
+    /// One value, or an array of a few for `is_vec` fields
+    fn each(&mut self, is_vec: bool, mut value: impl FnMut(&mut Self) -> Value) -> Value {
+        match is_vec {
+            true => {
+                let items = 1 + self.rng.below(MAX_ITEMS);
+                Value::Array((0..items).map(|_| value(self)).collect())
+            }
+            false => value(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        check_payload, ApiRecord, Constraints, QueryVecStyle, ResponseChild, ResponseField,
+        ServiceRecord, Slot,
+    };
+    use uuid::Uuid;
+
+    fn data_type(label: &str, primitive: &str, constraints: Constraints) -> DataTypeRecord {
+        DataTypeRecord {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+            data_primitive_id: Uuid::new_v4(),
+            primitive: primitive.to_string(),
+            constraints,
+        }
+    }
+
+    fn slot(name: &str, data_type: &str) -> Slot {
+        Slot {
+            name: name.to_string(),
+            data_type_id: Uuid::new_v4(),
+            data_type: data_type.to_string(),
+            is_vec: false,
+        }
+    }
+
+    fn field(identifier: &str, data_type: &str, is_vec: bool) -> ResponseField {
+        ResponseField {
+            identifier: identifier.to_string(),
+            data_type: data_type.to_string(),
+            is_vec,
+        }
+    }
+
+    fn request(description: &str, paths: Vec<Slot>, schema: &ResponseSchema) -> RequestDefinition {
+        RequestDefinition {
+            id: Uuid::new_v4(),
+            api_id: Uuid::nil(),
+            response_schema_id: schema.id.unwrap(),
+            description: description.to_string(),
+            query_vec_style: QueryVecStyle::Repeat,
+            url: "https://api.spotify.com".to_string(),
+            vers: "v1".to_string(),
+            paths,
+            queries: Vec::new(),
+            headers: Vec::new(),
+        }
+    }
+
+    fn spotify() -> ApiExport {
+        let artist = ResponseSchema {
+            id: Some(Uuid::new_v4()),
+            description: "Artist".to_string(),
+            data: vec![
+                field("id", "spotify_artist_id", false),
+                field("genres", "string", true),
+                field("popularity", "popularity", false),
+            ],
+            schemas: vec![ResponseChild {
+                identifier: "images".to_string(),
+                is_vec: true,
+                schema: ResponseSchema {
+                    id: Some(Uuid::new_v4()),
+                    description: "Image".to_string(),
+                    data: vec![field("url", "string", false)],
+                    schemas: Vec::new(),
+                },
+            }],
+        };
+        let user = ResponseSchema {
+            id: Some(Uuid::new_v4()),
+            description: "User".to_string(),
+            data: vec![field("display_name", "string", false)],
+            schemas: Vec::new(),
+        };
+        let me = ResponseSchema {
+            id: Some(Uuid::new_v4()),
+            description: "Current user".to_string(),
+            data: vec![field("email", "string", false)],
+            schemas: Vec::new(),
+        };
+
+        ApiExport {
+            service: ServiceRecord {
+                id: Uuid::nil(),
+                title: "spotify".to_string(),
+                description: "music".to_string(),
+                url: "api.spotify.com".to_string(),
+            },
+            api: ApiRecord {
+                id: Uuid::nil(),
+                service_id: Uuid::nil(),
+                url: "https://api.spotify.com".to_string(),
+                vers: "v1".to_string(),
+            },
+            requests: vec![
+                request(
+                    "Get an artist",
+                    vec![slot("artists", "const"), slot("id", "spotify_artist_id")],
+                    &artist,
+                ),
+                request(
+                    "Get a user",
+                    vec![slot("users", "const"), slot("user_id", "string")],
+                    &user,
+                ),
+                request(
+                    "Get the current user",
+                    vec![slot("users", "const"), slot("me", "const")],
+                    &me,
+                ),
+            ],
+            data_types: vec![
+                data_type("const", "const", Constraints::default()),
+                data_type("string", "string", Constraints::default()),
+                data_type(
+                    "popularity",
+                    "int",
+                    Constraints {
+                        min_value: Some(0.0),
+                        max_value: Some(100.0),
+                        ..Constraints::default()
+                    },
+                ),
+                data_type(
+                    "spotify_artist_id",
+                    "string",
+                    Constraints {
+                        pattern: Some("[0-9A-Za-z]{22}".to_string()),
+                        ..Constraints::default()
+                    },
+                ),
+            ],
+            response_schemas: vec![artist, user, me],
+        }
+    }
+
+    #[test]
+    fn responses_conform_to_the_response_schema() {
+        let export = spotify();
+        let body = respond(&export, "v1/artists/0OdUWJ0sBjDrqHygGUXeCF").unwrap();
+        let data_types = export
+            .data_types
+            .into_iter()
+            .map(|t| (t.label.clone(), t))
+            .collect();
+
+        assert_eq!(body["id"], "0OdUWJ0sBjDrqHygGUXeCF");
+        assert!(check_payload(&export.response_schemas[0], &data_types, &body).is_empty());
+    }
+
+    #[test]
+    fn the_same_url_gets_the_same_response() {
+        let export = spotify();
+
+        assert_eq!(
+            respond(&export, "/v1/artists/0OdUWJ0sBjDrqHygGUXeCF"),
+            respond(&export, "v1/artists/0OdUWJ0sBjDrqHygGUXeCF/")
+        );
+    }
+
+    #[test]
+    fn literal_segments_win_over_slots() {
+        let export = spotify();
+
+        assert!(respond(&export, "v1/users/me")
+            .unwrap()
+            .get("email")
+            .is_some());
+        assert!(respond(&export, "v1/users/jmperez")
+            .unwrap()
+            .get("display_name")
+            .is_some());
+    }
+
+    #[test]
+    fn urls_outside_the_api_are_not_answered() {
+        let export = spotify();
+
+        for path in &[
+            "v2/artists/0OdUWJ0sBjDrqHygGUXeCF",
+            "v1/artists/radiohead",
+            "v1/artists",
+            "v1/albums/0OdUWJ0sBjDrqHygGUXeCF",
+        ] {
+            assert_eq!(respond(&export, path), None, "{}", path);
+        }
+    }
+}
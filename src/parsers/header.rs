@@ -0,0 +1,116 @@
+use regex::Regex;
+use lazy_static::lazy_static;
+use thiserror::Error;
+use std::fmt;
+
+use crate::error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum HeaderParamError {
+    #[error("empty header param: && or ends with &")]
+    EmptyParam,
+    #[error("ill formed header param: {0}")]
+    IllFormedParam(String),
+    #[error("invalid header name: {0}")]
+    BadName(String),
+}
+
+#[derive(Error, PartialEq)]
+pub enum HeadersError {
+    #[error("empty headers")]
+    EmptyHeaders,
+    #[error("invalid header from header parameters")]
+    BadHeader(#[from] HeaderParamError),
+}
+
+impl fmt::Debug for HeadersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        error::debug::error_chain_fmt(self, f)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct HeaderParam<'a> {
+    pub(crate) name:       &'a str,
+    pub(crate) data_type:  &'a str,
+}
+
+pub(crate) fn parse_header_param(header: &str) -> Result<HeaderParam<'_>, HeaderParamError> {
+    // https://datatracker.ietf.org/doc/html/rfc7230#section-3.2.6
+    lazy_static! {
+        static ref TOKEN_RE: Regex = Regex::new(r"^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$").unwrap();
+    }
+
+    if header.is_empty() {
+        return Err(HeaderParamError::EmptyParam);
+    }
+
+    let args: Vec<&str> = header.split('=').collect();
+
+    match args.len() {
+        2 if args[1].is_empty() => Err(HeaderParamError::IllFormedParam(header.to_string())),
+        2 if !TOKEN_RE.is_match(args[0]) => Err(HeaderParamError::BadName(args[0].to_string())),
+        2 => Ok(HeaderParam { name: args[0], data_type: args[1] }),
+        _ => Err(HeaderParamError::IllFormedParam(header.to_string()))
+    }
+}
+
+/// Parse the header section of a url e.g.:
+/// `Authorization=spotify_token&Accept=const`
+pub(crate) fn parse_api_headers(headers: &str) -> Result<Vec<HeaderParam<'_>>, HeadersError> {
+    if headers.is_empty() {
+        return Err(HeadersError::EmptyHeaders);
+    };
+
+    headers.split('&')
+        .map(|h| Ok(parse_header_param(h)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_correct_headers() {
+        let mut header;
+        let mut headers;
+
+        header = "Authorization=spotify_token&Accept=const";
+        headers = parse_api_headers(header).unwrap();
+        assert!(headers.len() == 2);
+        assert!(headers[0] == HeaderParam { name: "Authorization", data_type: "spotify_token" });
+        assert!(headers[1] == HeaderParam { name: "Accept", data_type: "const" });
+
+        header = "X-Api-Key=google_api_key";
+        headers = parse_api_headers(header).unwrap();
+        assert!(headers.len() == 1);
+        assert!(headers[0] == HeaderParam { name: "X-Api-Key", data_type: "google_api_key" });
+    }
+
+    #[test]
+    fn parse_incorrect_headers() {
+        let mut header;
+        let mut headers;
+
+        header = "";
+        headers = parse_api_headers(header).unwrap_err();
+        assert!(headers == HeadersError::EmptyHeaders);
+
+        header = "Accept=const&";
+        headers = parse_api_headers(header).unwrap_err();
+        assert!(headers == HeadersError::from(HeaderParamError::EmptyParam));
+
+        header = "Authorization";
+        headers = parse_api_headers(header).unwrap_err();
+        assert!(headers == HeadersError::from(HeaderParamError::IllFormedParam("Authorization".to_string())));
+
+        header = "Authorization=";
+        headers = parse_api_headers(header).unwrap_err();
+        assert!(headers == HeadersError::from(HeaderParamError::IllFormedParam("Authorization=".to_string())));
+
+        header = "Api Key=token";
+        headers = parse_api_headers(header).unwrap_err();
+        assert!(headers == HeadersError::from(HeaderParamError::BadName("Api Key".to_string())));
+    }
+}
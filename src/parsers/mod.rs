@@ -1 +1,3 @@
+pub mod header;
+pub mod render;
 pub mod url;
@@ -0,0 +1,341 @@
+//! Turn parsed urls back into DSL text or into concrete urls.
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
+
+use super::header::HeaderParam;
+use super::url::{ApiGet, PathParam, QueryParam};
+use crate::domain::{QueryVecStyle, CONST_TYPE};
+
+// https://url.spec.whatwg.org/#path-percent-encode-set plus `/`
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'/')
+    .add(b'%');
+
+// everything but unreserved characters https://datatracker.ietf.org/doc/html/rfc3986#section-2.3
+const QUERY_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+impl fmt::Display for PathParam<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.data_type {
+            CONST_TYPE => write!(f, "{}", self.name),
+            data_type => write!(f, "{{{},{}}}", self.name, data_type),
+        }
+    }
+}
+
+impl fmt::Display for QueryParam<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.is_vec {
+            true => write!(f, "{}=[{}]", self.name, self.data_type),
+            false => write!(f, "{}={}", self.name, self.data_type),
+        }
+    }
+}
+
+impl fmt::Display for HeaderParam<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.data_type)
+    }
+}
+
+/// Canonical DSL text e.g.:
+/// `https://spotify.com|v1/artists/{id,spotify_artist_id}?market=country_code`
+impl fmt::Display for ApiGet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.url, self.ver)?;
+
+        for path in &self.paths {
+            write!(f, "/{}", path)?;
+        }
+
+        if let Some(queries) = &self.queries {
+            write_joined(f, '?', queries)?;
+        }
+
+        if let Some(headers) = &self.headers {
+            write_joined(f, '#', headers)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write `items` separated by `&`, after `prefix`
+fn write_joined<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    prefix: char,
+    items: &[T],
+) -> fmt::Result {
+    write!(f, "{}", prefix)?;
+
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, "&")?;
+        }
+        write!(f, "{}", item)?;
+    }
+
+    Ok(())
+}
+
+impl PathParam<'_> {
+    pub fn to_dsl(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl QueryParam<'_> {
+    pub fn to_dsl(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Value given to a path or query slot when instantiating a url.
+///
+/// Deserializes from either a string or a list of strings.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SlotValue {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl SlotValue {
+    fn as_slice(&self) -> &[String] {
+        match self {
+            SlotValue::Single(value) => std::slice::from_ref(value),
+            SlotValue::List(values) => values,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InstantiateError {
+    #[error("missing value for path parameter {0}")]
+    MissingPath(String),
+    #[error("path parameter {0} takes a single value")]
+    ListForPath(String),
+    #[error("query {0} takes a single value")]
+    ListForScalarQuery(String),
+}
+
+impl ApiGet<'_> {
+    pub fn to_dsl(&self) -> String {
+        self.to_string()
+    }
+
+    /// Concrete url with every typed slot filled in from `values`, keyed by slot name.
+    ///
+    /// Every typed path slot needs a single value. Queries without a value are left out
+    /// and vector queries are written out according to `style`. Values are percent-encoded.
+    pub fn instantiate(
+        &self,
+        values: &HashMap<String, SlotValue>,
+        style: QueryVecStyle,
+    ) -> Result<String, InstantiateError> {
+        let mut url = format!("{}/{}", self.url, self.ver);
+        let mut pairs = Vec::new();
+
+        for path in &self.paths {
+            let segment = match (path.data_type, values.get(path.name)) {
+                (CONST_TYPE, _) => path.name,
+                (_, Some(SlotValue::Single(value))) => value,
+                (_, Some(SlotValue::List(values))) if values.len() == 1 => &values[0],
+                (_, Some(SlotValue::List(_))) => {
+                    return Err(InstantiateError::ListForPath(path.name.to_string()))
+                }
+                (_, None) => return Err(InstantiateError::MissingPath(path.name.to_string())),
+            };
+
+            url.push('/');
+            url.extend(utf8_percent_encode(segment, PATH_SEGMENT));
+        }
+
+        for query in self.queries.iter().flatten() {
+            let given = match values.get(query.name) {
+                Some(given) => given.as_slice(),
+                None => continue,
+            };
+
+            if !query.is_vec && given.len() != 1 {
+                return Err(InstantiateError::ListForScalarQuery(query.name.to_string()));
+            }
+
+            let name = encode_query(query.name);
+            match (query.is_vec, style) {
+                (false, _) | (true, QueryVecStyle::Repeat) => pairs.extend(
+                    given
+                        .iter()
+                        .map(|v| format!("{}={}", name, encode_query(v))),
+                ),
+                (true, QueryVecStyle::Comma) => pairs.push(format!(
+                    "{}={}",
+                    name,
+                    given
+                        .iter()
+                        .map(|v| encode_query(v))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                )),
+                (true, QueryVecStyle::Brackets) => pairs.extend(
+                    given
+                        .iter()
+                        .map(|v| format!("{}[]={}", name, encode_query(v))),
+                ),
+            }
+        }
+
+        if !pairs.is_empty() {
+            url.push('?');
+            url.push_str(&pairs.join("&"));
+        }
+
+        Ok(url)
+    }
+}
+
+fn encode_query(s: &str) -> String {
+    utf8_percent_encode(s, QUERY_COMPONENT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::url::parse_api_url;
+
+    fn values(pairs: &[(&str, SlotValue)]) -> HashMap<String, SlotValue> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn dsl_round_trips() {
+        for query in &[
+            "http://spotify.com|v1/helloworld/myman/mwhahahah",
+            "https://spotify.com|v1/artists/{id,spotify_artist_id}?market=country_code",
+            "https://api.spotify.com|v1/tracks?ids=[spotify_track_id]&market=country_code",
+            "https://api.spotify.com|v1/me#Authorization=spotify_token&Accept=const",
+            "https://api.ticktick.com/open|v1/project/{projectId,string}/task/{taskId,string}",
+        ] {
+            let parsed = parse_api_url(query).unwrap();
+            assert_eq!(&parsed.to_dsl(), query);
+            assert!(parse_api_url(&parsed.to_dsl()).unwrap() == parsed);
+        }
+    }
+
+    #[test]
+    fn dsl_is_canonical() {
+        let parsed = parse_api_url("  https://spotify.com|v1/{artists,const}/{id,int}  ").unwrap();
+        assert_eq!(parsed.to_dsl(), "https://spotify.com|v1/artists/{id,int}");
+    }
+
+    #[test]
+    fn instantiate_fills_slots() {
+        let parsed = parse_api_url(
+            "https://api.spotify.com|v1/artists/{id,spotify_artist_id}/albums?market=country_code&limit=int",
+        )
+        .unwrap();
+        let url = parsed
+            .instantiate(
+                &values(&[
+                    ("id", SlotValue::Single("0OdUWJ0sBjDrqHygGUXeCF".into())),
+                    ("market", SlotValue::Single("ES".into())),
+                ]),
+                QueryVecStyle::Repeat,
+            )
+            .unwrap();
+
+        assert_eq!(
+            url,
+            "https://api.spotify.com/v1/artists/0OdUWJ0sBjDrqHygGUXeCF/albums?market=ES"
+        );
+    }
+
+    #[test]
+    fn instantiate_encodes_values() {
+        let parsed = parse_api_url("https://example.com|v1/search/{term,string}?q=string").unwrap();
+        let url = parsed
+            .instantiate(
+                &values(&[
+                    ("term", SlotValue::Single("a/b c".into())),
+                    ("q", SlotValue::Single("rock & roll".into())),
+                ]),
+                QueryVecStyle::Repeat,
+            )
+            .unwrap();
+
+        assert_eq!(
+            url,
+            "https://example.com/v1/search/a%2Fb%20c?q=rock%20%26%20roll"
+        );
+    }
+
+    #[test]
+    fn instantiate_vector_query_styles() {
+        let parsed =
+            parse_api_url("https://api.spotify.com|v1/tracks?ids=[spotify_track_id]").unwrap();
+        let given = values(&[("ids", SlotValue::List(vec!["a".into(), "b".into()]))]);
+
+        assert_eq!(
+            parsed.instantiate(&given, QueryVecStyle::Repeat).unwrap(),
+            "https://api.spotify.com/v1/tracks?ids=a&ids=b"
+        );
+        assert_eq!(
+            parsed.instantiate(&given, QueryVecStyle::Comma).unwrap(),
+            "https://api.spotify.com/v1/tracks?ids=a,b"
+        );
+        assert_eq!(
+            parsed.instantiate(&given, QueryVecStyle::Brackets).unwrap(),
+            "https://api.spotify.com/v1/tracks?ids[]=a&ids[]=b"
+        );
+    }
+
+    #[test]
+    fn instantiate_rejects_bad_values() {
+        let parsed = parse_api_url(
+            "https://api.spotify.com|v1/artists/{id,spotify_artist_id}?market=country_code",
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed.instantiate(&values(&[]), QueryVecStyle::Repeat),
+            Err(InstantiateError::MissingPath("id".into()))
+        );
+        assert_eq!(
+            parsed.instantiate(
+                &values(&[("id", SlotValue::List(vec!["a".into(), "b".into()]))]),
+                QueryVecStyle::Repeat
+            ),
+            Err(InstantiateError::ListForPath("id".into()))
+        );
+        assert_eq!(
+            parsed.instantiate(
+                &values(&[
+                    ("id", SlotValue::Single("a".into())),
+                    ("market", SlotValue::List(vec!["ES".into(), "US".into()]))
+                ]),
+                QueryVecStyle::Repeat
+            ),
+            Err(InstantiateError::ListForScalarQuery("market".into()))
+        );
+    }
+}
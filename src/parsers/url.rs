@@ -1,8 +1,11 @@
+use lazy_static::lazy_static;
 use regex::Regex;
 use thiserror::Error;
 use std::fmt;
+use std::ops::Range;
 
 use crate::error;
+use super::header::{parse_api_headers, parse_header_param, HeaderParam, HeadersError};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum PathParamError {
@@ -53,57 +56,219 @@ impl fmt::Debug for QueriesError {
     }
 }
 
+/// Byte range into the url given to `parse_api_url`
+pub type Span = Range<usize>;
+
+#[derive(Error, PartialEq)]
+pub enum UrlParseError {
+    #[error("missing | between the base url and the version")]
+    MissingVersionSeparator { span: Span },
+    #[error("base url is not an absolute http(s) url")]
+    BadBaseUrl { span: Span },
+    #[error("version is empty or contains whitespace")]
+    BadVersion { span: Span },
+    #[error("invalid path")]
+    Paths {
+        #[source]
+        source: PathsError,
+        span: Span,
+    },
+    #[error("invalid queries")]
+    Queries {
+        #[source]
+        source: QueriesError,
+        span: Span,
+    },
+    #[error("invalid headers")]
+    Headers {
+        #[source]
+        source: HeadersError,
+        span: Span,
+    },
+}
+
+impl UrlParseError {
+    /// Part of the url the error points at
+    pub fn span(&self) -> Span {
+        match self {
+            UrlParseError::MissingVersionSeparator { span }
+            | UrlParseError::BadBaseUrl { span }
+            | UrlParseError::BadVersion { span }
+            | UrlParseError::Paths { span, .. }
+            | UrlParseError::Queries { span, .. }
+            | UrlParseError::Headers { span, .. } => span.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for UrlParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        error::debug::error_chain_fmt(self, f)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct QueryParam<'a> {
-    name:       &'a str,
-    data_type:  &'a str,
+    pub(crate) name:       &'a str,
+    pub(crate) data_type:  &'a str,
+    pub(crate) is_vec:     bool,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct PathParam<'a> {
-    name:       &'a str,
-    data_type:  &'a str,
+    pub(crate) name:       &'a str,
+    pub(crate) data_type:  &'a str,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct ApiGet<'a> {
-    url:        &'a str,
-    ver:        &'a str,
-    paths:      Vec<PathParam<'a>>, queries:    Option<Vec<QueryParam<'a>>>,
+    pub(crate) url:        &'a str,
+    pub(crate) ver:        &'a str,
+    pub(crate) paths:      Vec<PathParam<'a>>,
+    pub(crate) queries:    Option<Vec<QueryParam<'a>>>,
+    pub(crate) headers:    Option<Vec<HeaderParam<'a>>>,
 }
 
-pub fn parse_api_url<'a>(url: &'a str) -> ApiGet<'a> {
+pub fn parse_api_url<'a>(url: &'a str) -> Result<ApiGet<'a>, UrlParseError> {
+    // https://spotify.com|v1/{artist,id}/hello?joe=5&bloe=4#Authorization=token
+    // base = https://spotify.com
+    // ver = v1
+    // paths = /{artist,id}/hello
+    // queries = joe=5&bloe=4
+    // headers = Authorization=token
     lazy_static! {
-        static ref URL_RE: Regex = Regex::new(r"(?x)    # https://spotify.com|v1/{artist,id}/hello?joe=5&bloe=4
-                (?P<base>https?://(?:\S+?))             # base = https://spotify.com
-                \|                                      # |
-                (?P<ver>\S+?)                           # ver = v1
-                (?P<paths>/[^?\s]+)           # paths = /{artist,id}/hello
-                (?:\?(                                  # ?
-                      ?P<queries>(\S+)                  # queries = joe=5&bloe=4
-                ))?$
-            ")
-            .unwrap();
+        static ref BASE_RE: Regex = Regex::new(r"^https?://\S+$").unwrap();
     }
 
+    // every span is relative to the untrimmed url
+    let start = url.len() - url.trim_start().len();
     let trim_url = url.trim();
-    let capture = URL_RE.captures(trim_url).unwrap();
 
-    let q;
-    q = match capture.name("queries") {
+    let pipe = trim_url.find('|').ok_or(UrlParseError::MissingVersionSeparator {
+        span: start..start + trim_url.len(),
+    })?;
+
+    let base = &trim_url[..pipe];
+    if !BASE_RE.is_match(base) {
+        return Err(UrlParseError::BadBaseUrl { span: start..start + pipe });
+    }
+
+    let rest = &trim_url[pipe + 1..];
+    let header_start = rest.find('#').unwrap_or(rest.len());
+    let query_start = rest[..header_start].find('?').unwrap_or(header_start);
+    let path_start = rest[..query_start].find('/').unwrap_or(query_start);
+
+    let ver = &rest[..path_start];
+    if ver.is_empty() || ver.contains(char::is_whitespace) {
+        return Err(UrlParseError::BadVersion {
+            span: start + pipe..start + pipe + 1 + path_start,
+        });
+    }
+
+    let offset = start + pipe + 1 + path_start;
+    let path = &rest[path_start..query_start];
+    let paths = parse_api_path(path).map_err(|e| UrlParseError::Paths {
+        span: path_error_span(path, ver, offset, &e),
+        source: e,
+    })?;
+
+    let queries = match rest.get(query_start + 1..header_start) {
         None    =>  None,
-        Some(i) =>  Some(parse_api_queries(i.as_str()).unwrap()),
+        Some(q) =>  {
+            let offset = start + pipe + 1 + query_start + 1;
+            Some(parse_api_queries(q).map_err(|e| UrlParseError::Queries {
+                span: query_error_span(q, offset),
+                source: e,
+            })?)
+        },
     };
 
-    ApiGet {
-        url:        capture.name("base").unwrap().as_str(),
-        ver:        capture.name("ver").unwrap().as_str(),
-        paths:      parse_api_path(&capture.name("paths").unwrap().as_str()).unwrap(),
-        queries:    q,
+    let headers = match rest.get(header_start + 1..) {
+        None    =>  None,
+        Some(h) =>  {
+            let offset = start + pipe + 1 + header_start + 1;
+            Some(parse_api_headers(h).map_err(|e| UrlParseError::Headers {
+                span: header_error_span(h, offset),
+                source: e,
+            })?)
+        },
+    };
+
+    Ok(ApiGet {
+        url:        base,
+        ver,
+        paths,
+        queries,
+        headers,
+    })
+}
+
+/// Span of the path segment `parse_api_path` failed on.
+///
+/// `offset` is where `path` starts in the url, right after `ver`.
+fn path_error_span(path: &str, ver: &str, offset: usize, e: &PathsError) -> Span {
+    match e {
+        PathsError::BadPath(_) => {
+            bad_item_span(&path[1..], '/', offset + 1, |p| parse_path_param(p).is_err())
+                .unwrap_or(offset..offset + path.len())
+        },
+        // an empty path points at the last char of the version
+        PathsError::EmptyPath => {
+            offset - ver.chars().last().map_or(0, char::len_utf8)..offset
+        },
+        _ => offset..offset + path.len(),
+    }
+}
+
+/// Span of the query `parse_api_queries` failed on.
+///
+/// `offset` is where `queries` starts in the url.
+fn query_error_span(queries: &str, offset: usize) -> Span {
+    if queries.is_empty() {
+        // point at the dangling ?
+        return offset - 1..offset;
     }
+
+    bad_item_span(queries, '&', offset, |q| parse_query_param(q).is_err())
+        .unwrap_or(offset..offset + queries.len())
 }
 
-fn parse_path_param (param: &str) -> Result<PathParam, PathParamError> {
+/// Span of the header `parse_api_headers` failed on.
+///
+/// `offset` is where `headers` starts in the url.
+fn header_error_span(headers: &str, offset: usize) -> Span {
+    if headers.is_empty() {
+        // point at the dangling #
+        return offset - 1..offset;
+    }
+
+    bad_item_span(headers, '&', offset, |h| parse_header_param(h).is_err())
+        .unwrap_or(offset..offset + headers.len())
+}
+
+/// Span of the first `sep` separated item of `s` that `is_bad` rejects.
+///
+/// An empty item is given the span of the separator in front of it,
+/// or behind it for the first item, so there is always something to point at.
+/// When `s` is empty that is the separator in front of `offset`.
+fn bad_item_span(s: &str, sep: char, offset: usize, is_bad: impl Fn(&str) -> bool) -> Option<Span> {
+    let mut item_start = offset;
+
+    for item in s.split(sep) {
+        if is_bad(item) {
+            return Some(match (item.is_empty(), item_start == offset && !s.is_empty()) {
+                (false, _)      =>  item_start..item_start + item.len(),
+                (true, false)   =>  item_start - sep.len_utf8()..item_start,
+                (true, true)    =>  item_start..item_start + sep.len_utf8(),
+            });
+        }
+        item_start += item.len() + sep.len_utf8();
+    }
+
+    None
+}
+
+fn parse_path_param (param: &str) -> Result<PathParam<'_>, PathParamError> {
     if param.is_empty() {
         return Err(PathParamError::EmptyParam);
     }
@@ -137,12 +302,12 @@ fn parse_path_param (param: &str) -> Result<PathParam, PathParamError> {
     }
 }
 
-fn parse_api_path(path: &str) -> Result<Vec<PathParam>, PathsError> {
+fn parse_api_path(path: &str) -> Result<Vec<PathParam<'_>>, PathsError> {
     if path.is_empty() {
         return Err(PathsError::EmptyPath);
     }
 
-    if path.chars().nth(0).unwrap() != '/' {
+    if !path.starts_with('/') {
         return Err(PathsError::StartSlash(path.to_string()));
     }
 
@@ -151,20 +316,43 @@ fn parse_api_path(path: &str) -> Result<Vec<PathParam>, PathsError> {
         .collect()
 }
 
-fn parse_query_param(query: &str) -> Result<QueryParam, QueryParamError> {
+fn parse_query_param(query: &str) -> Result<QueryParam<'_>, QueryParamError> {
     if query.is_empty() {
         return Err(QueryParamError::EmptyParam);
     }
 
+    lazy_static! {
+        static ref VEC_RE: Regex = Regex::new(r"(?x)    # [spotify_track_id]
+                ^\[                                    # [
+                (?P<data_type>[^\[\]]+)               # data_type = spotify_track_id
+                \]$                                    # ]
+            ")
+            .unwrap();
+        static ref INVALID_TYPE_RE: Regex = Regex::new(r"[\[\]]").unwrap();
+    }
+
     let args: Vec<&str> = query.split("=").collect();
 
-    match args.len() {
-        2 => Ok(QueryParam { name: args[0], data_type: args[1] }),
-        _ => Err(QueryParamError::IllFormedParam(query.to_string()))
+    if args.len() != 2 {
+        return Err(QueryParamError::IllFormedParam(query.to_string()));
+    }
+
+    match VEC_RE.captures(args[1]) {
+        None => {
+            match INVALID_TYPE_RE.find(args[1]) {
+                None => Ok(QueryParam { name: args[0], data_type: args[1], is_vec: false }),
+                Some(_) => Err(QueryParamError::IllFormedParam(query.to_string())),
+            }
+        },
+        Some(m) => Ok(QueryParam {
+            name:       args[0],
+            data_type:  m.name("data_type").unwrap().as_str(),
+            is_vec:     true,
+        }),
     }
 }
 
-fn parse_api_queries(queries: &str) -> Result<Vec<QueryParam>, QueriesError> {
+fn parse_api_queries(queries: &str) -> Result<Vec<QueryParam<'_>>, QueriesError> {
     if queries.is_empty() {
         return Err(QueriesError::EmptyQueries);
     };
@@ -186,7 +374,7 @@ mod tests {
         let mut result;
 
         query = "http://spotify.com|v1/helloworld/myman/mwhahahah";
-        result = parse_api_url(query);
+        result = parse_api_url(query).unwrap();
         assert!(result == ApiGet {
             url: "http://spotify.com",
             ver: "v1",
@@ -205,10 +393,11 @@ mod tests {
                  }
             ]),
             queries: None,
+            headers: None,
         });
 
         query = "https://spotify.com|v4/hello-world/{artist,world}?bonvoyage=3&john=3";
-        result = parse_api_url(query);
+        result = parse_api_url(query).unwrap();
         assert!(result == ApiGet {
             url: "https://spotify.com",
             ver: "v4",
@@ -226,16 +415,19 @@ mod tests {
                 QueryParam {
                     name: "bonvoyage",
                     data_type: "3",
+                    is_vec: false,
                 },
                 QueryParam {
                     name: "john",
                     data_type: "3",
+                    is_vec: false,
                 },
             ])),
+            headers: None,
         });
 
         query = "https://www.googleapis.com/youtube|v3/channels";
-        result = parse_api_url(query);
+        result = parse_api_url(query).unwrap();
         assert!(result == ApiGet {
             url: "https://www.googleapis.com/youtube",
             ver: "v3",
@@ -245,10 +437,11 @@ mod tests {
                      data_type: "const",
                  }]),
             queries: None,
+            headers: None,
         });
 
         query = "https://graph.microsoft.com|v1.0/me/messages?filter=emailAddress";
-        result = parse_api_url(query);
+        result = parse_api_url(query).unwrap();
         assert!(result == ApiGet {
             url: "https://graph.microsoft.com",
             ver: "v1.0",
@@ -266,12 +459,14 @@ mod tests {
                 QueryParam {
                     name: "filter",
                     data_type: "emailAddress",
+                    is_vec: false,
                 },
             ])),
+            headers: None,
         });
 
         query = "https://api.ticktick.com/open|v1/project/{projectId,string}/task/{taskId,string}";
-        result = parse_api_url(&query);
+        result = parse_api_url(query).unwrap();
         assert!(result == ApiGet {
             url: "https://api.ticktick.com/open",
             ver: "v1",
@@ -294,27 +489,137 @@ mod tests {
                  },
             ]),
             queries: None,
+            headers: None,
         });
             
     }
 
+    #[test]
+    fn parse_incorrect_api() {
+        let mut query;
+        let mut error;
+
+        query = "https://spotify.com/v1/artists";
+        error = parse_api_url(query).unwrap_err();
+        assert!(error == UrlParseError::MissingVersionSeparator { span: 0..30 });
+
+        query = "spotify.com|v1/artists";
+        error = parse_api_url(query).unwrap_err();
+        assert!(error == UrlParseError::BadBaseUrl { span: 0..11 });
+
+        query = "  https://spotify.com|/artists";
+        error = parse_api_url(query).unwrap_err();
+        assert!(error == UrlParseError::BadVersion { span: 21..22 });
+        assert!(&query[error.span()] == "|");
+
+        query = "https://spotify.com|v1";
+        error = parse_api_url(query).unwrap_err();
+        assert!(error == UrlParseError::Paths {
+            source: PathsError::EmptyPath,
+            span: 21..22,
+        });
+
+        query = "https://spotify.com|v1/artists/{id/albums";
+        error = parse_api_url(query).unwrap_err();
+        assert!(&query[error.span()] == "{id");
+        assert!(error == UrlParseError::Paths {
+            source: PathsError::from(PathParamError::IllFormedParam("{id".to_string())),
+            span: 31..34,
+        });
+
+        query = "https://spotify.com|v1/artists//albums";
+        error = parse_api_url(query).unwrap_err();
+        assert!(&query[error.span()] == "/");
+        assert!(error.span() == (30..31));
+
+        query = "https://a.com|v1/";
+        error = parse_api_url(query).unwrap_err();
+        assert!(error.span().end <= query.len());
+        assert!(error.span() == (16..17));
+
+        query = "https://a.com|v1//";
+        error = parse_api_url(query).unwrap_err();
+        assert!(error.span().end <= query.len());
+        assert!(&query[error.span()] == "/");
+
+        query = "https://spotify.com|v1/search?q=string&type";
+        error = parse_api_url(query).unwrap_err();
+        assert!(&query[error.span()] == "type");
+        assert!(error == UrlParseError::Queries {
+            source: QueriesError::from(QueryParamError::IllFormedParam("type".to_string())),
+            span: 39..43,
+        });
+
+        query = "https://x|vé";
+        error = parse_api_url(query).unwrap_err();
+        assert!(&query[error.span()] == "é");
+
+        query = "https://spotify.com|v1/search?";
+        error = parse_api_url(query).unwrap_err();
+        assert!(&query[error.span()] == "?");
+        assert!(error == UrlParseError::Queries {
+            source: QueriesError::EmptyQueries,
+            span: 29..30,
+        });
+    }
+
+    #[test]
+    fn parse_api_with_headers() {
+        let mut query;
+        let mut result;
+
+        query = "https://api.spotify.com|v1/me#Authorization=spotify_token&Accept=const";
+        result = parse_api_url(query).unwrap();
+        assert!(result == ApiGet {
+            url: "https://api.spotify.com",
+            ver: "v1",
+            paths: Vec::from([
+                 PathParam {
+                     name: "me",
+                     data_type: "const",
+                 },
+            ]),
+            queries: None,
+            headers: Some(Vec::from([
+                HeaderParam {
+                    name: "Authorization",
+                    data_type: "spotify_token",
+                },
+                HeaderParam {
+                    name: "Accept",
+                    data_type: "const",
+                },
+            ])),
+        });
+
+        query = "https://api.spotify.com|v1/search?q=string#Authorization=spotify_token";
+        result = parse_api_url(query).unwrap();
+        assert!(result.queries == Some(Vec::from([QueryParam { name: "q", data_type: "string", is_vec: false }])));
+        assert!(result.headers == Some(Vec::from([HeaderParam { name: "Authorization", data_type: "spotify_token" }])));
+
+        query = "https://api.spotify.com|v1/me#Authorization";
+        let error = parse_api_url(query).unwrap_err();
+        assert!(&query[error.span()] == "Authorization");
+        assert!(error.span() == (30..43));
+    }
+
     #[test]
     fn parse_correct_paths() {
         let mut path;
         let mut parse_path;
         path = "/hello/world/how";
-        parse_path = parse_api_path(&path).unwrap();
+        parse_path = parse_api_path(path).unwrap();
         assert!(parse_path.len() == 3);
         assert!(parse_path[0] == PathParam { name: "hello", data_type: "const"});
-        assert!(parse_path[1] == PathParam { name: &"world", data_type: &"const"});
-        assert!(parse_path[2] == PathParam { name: &"how", data_type: &"const"});
+        assert!(parse_path[1] == PathParam { name: "world", data_type: "const"});
+        assert!(parse_path[2] == PathParam { name: "how", data_type: "const"});
 
         path = "/hello/{artist,spotify_artist_id}/tbd";
-        parse_path = parse_api_path(&path).unwrap();
+        parse_path = parse_api_path(path).unwrap();
         assert!(parse_path.len() == 3);
-        assert!(parse_path[0] == PathParam { name: &"hello", data_type: &"const"});
-        assert!(parse_path[1] == PathParam { name: &"artist", data_type: &"spotify_artist_id"});
-        assert!(parse_path[2] == PathParam { name: &"tbd", data_type: &"const"});
+        assert!(parse_path[0] == PathParam { name: "hello", data_type: "const"});
+        assert!(parse_path[1] == PathParam { name: "artist", data_type: "spotify_artist_id"});
+        assert!(parse_path[2] == PathParam { name: "tbd", data_type: "const"});
     }
 
     #[test]
@@ -323,27 +628,27 @@ mod tests {
         let mut parse_path;
 
         path = "/hello/world/";
-        parse_path = parse_api_path(&path).unwrap_err();
+        parse_path = parse_api_path(path).unwrap_err();
         assert!(parse_path == PathsError::from(PathParamError::EmptyParam));
 
         path = "/hello//world";
-        parse_path = parse_api_path(&path).unwrap_err();
+        parse_path = parse_api_path(path).unwrap_err();
         assert!(parse_path == PathsError::from(PathParamError::EmptyParam));
 
         path = "/hello/world,hi";
-        parse_path = parse_api_path(&path).unwrap_err();
+        parse_path = parse_api_path(path).unwrap_err();
         assert!(parse_path == PathsError::from(PathParamError::IllFormedParam("world,hi".to_string())));
 
         path = "/hello/{world";
-        parse_path = parse_api_path(&path).unwrap_err();
+        parse_path = parse_api_path(path).unwrap_err();
         assert!(parse_path == PathsError::from(PathParamError::IllFormedParam("{world".to_string())));
         
         path = "";
-        parse_path = parse_api_path(&path).unwrap_err();
+        parse_path = parse_api_path(path).unwrap_err();
         assert!(parse_path == PathsError::EmptyPath);
 
         path = "hello/world";
-        parse_path = parse_api_path(&path).unwrap_err();
+        parse_path = parse_api_path(path).unwrap_err();
         assert!(parse_path == PathsError::StartSlash("hello/world".to_string()));
     }
 
@@ -353,15 +658,21 @@ mod tests {
         let mut queries;
 
         query = "john=int&offset=str";
-        queries = parse_api_queries(&query).unwrap();
+        queries = parse_api_queries(query).unwrap();
         assert!(queries.len() == 2);
-        assert!(queries[0] == QueryParam { name: "john", data_type: "int"} );
-        assert!(queries[1] == QueryParam { name: "offset", data_type: "str" } );
+        assert!(queries[0] == QueryParam { name: "john", data_type: "int", is_vec: false } );
+        assert!(queries[1] == QueryParam { name: "offset", data_type: "str", is_vec: false } );
 
         query = "artist_id=int";
-        queries = parse_api_queries(&query).unwrap();
+        queries = parse_api_queries(query).unwrap();
         assert!(queries.len() == 1);
-        assert!(queries[0] == QueryParam { name: "artist_id", data_type: "int"} );
+        assert!(queries[0] == QueryParam { name: "artist_id", data_type: "int", is_vec: false } );
+
+        query = "ids=[spotify_track_id]&market=country_code";
+        queries = parse_api_queries(query).unwrap();
+        assert!(queries.len() == 2);
+        assert!(queries[0] == QueryParam { name: "ids", data_type: "spotify_track_id", is_vec: true } );
+        assert!(queries[1] == QueryParam { name: "market", data_type: "country_code", is_vec: false } );
     }
 
     #[test]
@@ -370,25 +681,37 @@ mod tests {
         let mut queries;
 
         query = "";
-        queries = parse_api_queries(&query).unwrap_err();
+        queries = parse_api_queries(query).unwrap_err();
         assert!(queries == QueriesError::EmptyQueries);
 
         query = "&";
-        queries = parse_api_queries(&query).unwrap_err();
+        queries = parse_api_queries(query).unwrap_err();
         assert!(queries == QueriesError::from(QueryParamError::EmptyParam));
 
 
         query = "&test=hello";
-        queries = parse_api_queries(&query).unwrap_err();
+        queries = parse_api_queries(query).unwrap_err();
         assert!(queries == QueriesError::from(QueryParamError::EmptyParam));
 
         query = "test=hello&";
-        queries = parse_api_queries(&query).unwrap_err();
+        queries = parse_api_queries(query).unwrap_err();
         assert!(queries == QueriesError::from(QueryParamError::EmptyParam));
 
         query = "lo&hello=hi";
-        queries = parse_api_queries(&query).unwrap_err();
+        queries = parse_api_queries(query).unwrap_err();
         assert!(queries == QueriesError::from(QueryParamError::IllFormedParam("lo".to_string())));
+
+        query = "ids=[spotify_track_id";
+        queries = parse_api_queries(query).unwrap_err();
+        assert!(queries == QueriesError::from(QueryParamError::IllFormedParam("ids=[spotify_track_id".to_string())));
+
+        query = "ids=[]";
+        queries = parse_api_queries(query).unwrap_err();
+        assert!(queries == QueriesError::from(QueryParamError::IllFormedParam("ids=[]".to_string())));
+
+        query = "ids=[[int]]";
+        queries = parse_api_queries(query).unwrap_err();
+        assert!(queries == QueriesError::from(QueryParamError::IllFormedParam("ids=[[int]]".to_string())));
     }
 
     #[test]
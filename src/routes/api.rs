@@ -1,43 +1,152 @@
 use axum::extract;
-use axum::extract::Form;
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
 use daysquare_shared::Service;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::domain::{Page, Pagination, ServicePatch, ServiceRecord};
+use crate::error::AppError;
+use crate::extract::{Form, Json, Path, Query};
+
+#[tracing::instrument(
+    name = "Adding a new API service",
+    skip(input, connection),
+    fields(request_url = %input.url)
+)]
 pub async fn new_service(
     Form(input): Form<Service>,
     connection: extract::Extension<PgPool>,
-) -> StatusCode {
-    let request_span;
-    let _request_span_guard;
+) -> Result<(StatusCode, HeaderMap, Json<ServiceRecord>), AppError> {
     let connection = connection.0;
-    request_span = tracing::info_span!(
-        "Adding a new API service",
-        request_url = %input.url
-    );
-
-    _request_span_guard = request_span.enter();
+    let service;
+    let mut headers;
 
     tracing::event!(tracing::Level::INFO, "Recieved: {:?}", input);
 
-    match sqlx::query!(
+    service = sqlx::query_as!(
+        ServiceRecord,
         r#"
         insert into daysquare.service (id, title, description, url)
         values ($1, $2, $3, $4)
+        returning id, title, description, url
         "#,
         Uuid::new_v4(),
         input.title,
         input.description,
         input.url
     )
-    .execute(&connection)
-    .await
-    {
-        Ok(_) => StatusCode::OK,
-        Err(e) => {
-            println!("Failed to execute query: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        }
+    .fetch_one(&connection)
+    .await?;
+
+    headers = HeaderMap::new();
+    headers.insert(header::LOCATION, service_location(service.id));
+
+    Ok((StatusCode::CREATED, headers, Json(service)))
+}
+
+#[tracing::instrument(name = "Listing API services", skip(connection))]
+pub async fn list_services(
+    Query(pagination): Query<Pagination>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<Page<ServiceRecord>>, AppError> {
+    let connection = connection.0;
+    let total;
+    let services;
+
+    total = sqlx::query!(r#"select count(*) as "count!" from daysquare.service"#)
+        .fetch_one(&connection)
+        .await?
+        .count;
+
+    services = sqlx::query_as!(
+        ServiceRecord,
+        r#"
+        select id, title, description, url
+        from daysquare.service
+        order by title, id
+        limit $1 offset $2
+        "#,
+        pagination.per_page(),
+        pagination.offset()
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(Json(Page::new(services, &pagination, total)))
+}
+
+#[tracing::instrument(name = "Fetching an API service", skip(connection))]
+pub async fn get_service(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<ServiceRecord>, AppError> {
+    let connection = connection.0;
+
+    sqlx::query_as!(
+        ServiceRecord,
+        r#"
+        select id, title, description, url
+        from daysquare.service
+        where id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .map(Json)
+    .ok_or_else(|| AppError::not_found("service"))
+}
+
+#[tracing::instrument(name = "Updating an API service", skip(connection))]
+pub async fn update_service(
+    Path(id): Path<Uuid>,
+    Json(patch): Json<ServicePatch>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<ServiceRecord>, AppError> {
+    let connection = connection.0;
+
+    sqlx::query_as!(
+        ServiceRecord,
+        r#"
+        update daysquare.service
+        set title = coalesce($2, title),
+            description = coalesce($3, description),
+            url = coalesce($4, url)
+        where id = $1
+        returning id, title, description, url
+        "#,
+        id,
+        patch.title,
+        patch.description,
+        patch.url
+    )
+    .fetch_optional(&connection)
+    .await?
+    .map(Json)
+    .ok_or_else(|| AppError::not_found("service"))
+}
+
+#[tracing::instrument(name = "Deleting an API service", skip(connection))]
+pub async fn delete_service(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<StatusCode, AppError> {
+    let connection = connection.0;
+    let deleted;
+
+    deleted = sqlx::query!("delete from daysquare.service where id = $1", id)
+        .execute(&connection)
+        .await?
+        .rows_affected();
+
+    match deleted {
+        0 => Err(AppError::not_found("service")),
+        _ => Ok(StatusCode::NO_CONTENT),
     }
 }
+
+/// Path a service can be fetched from, for use in a `Location` header
+fn service_location(id: Uuid) -> HeaderValue {
+    HeaderValue::from_str(&format!("/service/{}", id))
+        .expect("A uuid path is always a valid header value")
+}
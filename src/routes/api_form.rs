@@ -1,17 +1,103 @@
-use axum::{extract::Form, response::Html};
+use axum::extract;
+use axum::http::StatusCode;
+use axum::response::Html;
 use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
 
-pub async fn get_api_form() -> Html<&'static str> {
-    Html(
+use crate::domain::{BaseUrl, QueryVecStyle, UnknownTypes};
+use crate::error::AppError;
+use crate::extract::Form;
+use crate::parsers::url::{parse_api_url, ApiGet, Span, UrlParseError};
+use crate::store::{self, NewRequest};
+
+#[tracing::instrument(name = "Rendering the API form", skip(connection))]
+pub async fn get_api_form(
+    connection: extract::Extension<PgPool>,
+) -> Result<Html<String>, AppError> {
+    let connection = connection.0;
+
+    Ok(Html(form_page(&connection, None).await?))
+}
+
+/// The submission form, with the url of a rejected submission
+/// and the part of it that failed to parse highlighted
+async fn form_page(
+    connection: &PgPool,
+    rejected: Option<(&Input, &UrlParseError)>,
+) -> Result<String, AppError> {
+    let services;
+    let options;
+    let error;
+
+    services = sqlx::query!("select id, title, url from daysquare.service order by title")
+        .fetch_all(connection)
+        .await?;
+
+    options = services
+        .iter()
+        .map(|s| {
+            let selected = match rejected {
+                Some((input, _)) if input.service_id == s.id => " selected",
+                _ => "",
+            };
+            format!(
+                r#"<option value="{}"{}>{} ({})</option>"#,
+                s.id,
+                selected,
+                escape(&s.title),
+                escape(&s.url)
+            )
+        })
+        .collect::<String>();
+
+    error = match rejected {
+        Some((input, e)) => format!(
+            "<p>{}</p><p><code>{}</code></p>",
+            escape(&e.to_string()),
+            highlight(&input.url, e.span())
+        ),
+        None => String::new(),
+    };
+
+    Ok(format!(
         r#"
         <!doctype html>
         <html>
             <head>Submit API</head>
             <body>
+                {error}
                 <form action="/form" method="post">
+                    <label for="service_id">
+                        Service:
+                        <select name="service_id">{options}</select>
+                    </label>
+
                     <label for="url">
                         Enter url:
-                        <input type="text" name="url">
+                        <input type="text" name="url" value="{url}">
+                    </label>
+
+                    <label for="description">
+                        Description:
+                        <input type="text" name="description" value="{description}">
+                    </label>
+
+                    <label for="query_vec_style">
+                        Vector queries:
+                        <select name="query_vec_style">
+                            <option value="repeat">ids=1&amp;ids=2</option>
+                            <option value="comma">ids=1,2</option>
+                            <option value="brackets">ids[]=1&amp;ids[]=2</option>
+                        </select>
+                    </label>
+
+                    <label for="unknown_types">
+                        Unknown data types:
+                        <select name="unknown_types">
+                            <option value="reject">Reject</option>
+                            <option value="create">Create as string</option>
+                        </select>
                     </label>
 
                     <input type="submit" value="Submit">
@@ -19,21 +105,212 @@ pub async fn get_api_form() -> Html<&'static str> {
             </body>
         </html>
         "#,
-    )
+        error = error,
+        options = options,
+        url = rejected.map(|(i, _)| escape(&i.url)).unwrap_or_default(),
+        description = rejected
+            .map(|(i, _)| escape(&i.description))
+            .unwrap_or_default(),
+    ))
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Input {
+    service_id: Uuid,
     url: String,
+    description: String,
+    #[serde(default)]
+    query_vec_style: QueryVecStyle,
+    #[serde(default)]
+    unknown_types: UnknownTypes,
 }
 
-pub async fn url_form(Form(input): Form<Input>) {
-    let request_span = tracing::info_span!(
-        "Adding a new HTTP request.",
-        request_url = %input.url,
-    );
+/// Parse a url written in the api DSL and store it as a request e.g.:
+/// `https://spotify.com|v1/artists/{id,spotify_artist_id}?market=country_code#Authorization=spotify_token`
+///
+/// The api (base url + version) is created if the service doesn't have it yet.
+/// Data type labels that aren't registered are created or rejected
+/// depending on the `unknown_types` field, rejected when it is left out.
+/// Every request gets an empty response schema to be filled in later.
+///
+/// A url that fails to parse sends the form back with the error highlighted.
+#[tracing::instrument(
+    name = "Adding a new HTTP request.",
+    skip(input, connection),
+    fields(request_url = %input.url)
+)]
+pub async fn url_form(
+    Form(input): Form<Input>,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, Html<String>), AppError> {
+    let connection = connection.0;
+    let api_get;
+    let base_url;
+    let mut tx;
+    let api_id;
+    let response_schema_id;
+    let request_id;
+
+    api_get = match parse_api_url(&input.url) {
+        Ok(api_get) => api_get,
+        Err(e) => {
+            tracing::info!("Rejected url: {:?}", e);
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                Html(form_page(&connection, Some((&input, &e))).await?),
+            ));
+        }
+    };
+    base_url = BaseUrl::parse(api_get.url.to_string()).map_err(AppError::Validation)?;
 
-    let _request_span_guard = request_span.enter();
+    tx = connection.begin().await?;
+
+    api_id = store::find_or_create_api(&mut tx, input.service_id, &base_url, api_get.ver).await?;
+
+    response_schema_id = Uuid::new_v4();
+    sqlx::query!(
+        "insert into daysquare.response_schema (id, description) values ($1, $2)",
+        response_schema_id,
+        input.description
+    )
+    .execute(&mut tx)
+    .await?;
+
+    request_id = store::insert_request(
+        &mut tx,
+        &NewRequest {
+            api_id,
+            response_schema_id,
+            description: &input.description,
+            query_vec_style: input.query_vec_style,
+            api_get: &api_get,
+        },
+        input.unknown_types,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(%request_id, %api_id, "Stored new request");
+
+    Ok((StatusCode::OK, Html(summary(&api_get, api_id, request_id))))
+}
+
+/// Page shown after a request was stored
+fn summary(api_get: &ApiGet<'_>, api_id: Uuid, request_id: Uuid) -> String {
+    let paths;
+    let queries;
+    let headers;
+
+    paths = api_get
+        .paths
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                i,
+                escape(p.name),
+                escape(p.data_type)
+            )
+        })
+        .collect::<String>();
+
+    queries = api_get
+        .queries
+        .iter()
+        .flatten()
+        .map(|q| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(q.name),
+                escape(q.data_type),
+                q.is_vec
+            )
+        })
+        .collect::<String>();
+
+    headers = api_get
+        .headers
+        .iter()
+        .flatten()
+        .map(|h| {
+            format!(
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape(h.name),
+                escape(h.data_type)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"
+        <!doctype html>
+        <html>
+            <head>Request added</head>
+            <body>
+                <p>Request <code>{request_id}</code> added to api <code>{api_id}</code></p>
+                <p>Base url: <code>{url}</code> version: <code>{ver}</code></p>
+                <p>Stored as: <code>{dsl}</code></p>
+                <table>
+                    <tr><th>Sequence</th><th>Path</th><th>Data type</th></tr>
+                    {paths}
+                </table>
+                <table>
+                    <tr><th>Query</th><th>Data type</th><th>Vector</th></tr>
+                    {queries}
+                </table>
+                <table>
+                    <tr><th>Header</th><th>Data type</th></tr>
+                    {headers}
+                </table>
+                <a href="/form">Add another</a>
+            </body>
+        </html>
+        "#,
+        request_id = request_id,
+        api_id = api_id,
+        url = escape(api_get.url),
+        ver = escape(api_get.ver),
+        dsl = escape(&api_get.to_dsl()),
+        paths = paths,
+        queries = queries,
+        headers = headers,
+    )
+}
+
+/// Escaped `url` with `span` wrapped in a `<mark>`.
+///
+/// An empty span still gets a visible marker.
+/// A span that doesn't fit `url` marks its end instead of panicking.
+fn highlight(url: &str, span: Span) -> String {
+    let span = match url.get(span.clone()) {
+        Some(_) => span,
+        None => url.len()..url.len(),
+    };
+    let marked = match &url[span.clone()] {
+        "" => " ",
+        marked => marked,
+    };
+
+    format!(
+        "{}<mark>{}</mark>{}",
+        escape(&url[..span.start]),
+        escape(marked),
+        escape(&url[span.end..])
+    )
+}
 
-    dbg!(&input);
+/// Escape user input before putting it in a page
+fn escape(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&#x27;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
 }
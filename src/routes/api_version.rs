@@ -0,0 +1,167 @@
+use axum::extract;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::{parse_version, ApiPatch, ApiRecord, BaseUrl, NewApi};
+use crate::error::AppError;
+use crate::extract::{Json, Path};
+
+#[tracing::instrument(name = "Adding a new API version", skip(input, connection))]
+pub async fn new_api(
+    Path(service_id): Path<Uuid>,
+    Json(input): Json<NewApi>,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, HeaderMap, Json<ApiRecord>), AppError> {
+    let connection = connection.0;
+    let url;
+    let vers;
+    let api;
+    let mut headers;
+
+    url = BaseUrl::parse(input.url).map_err(AppError::Validation)?;
+    vers = parse_version(input.vers).map_err(AppError::Validation)?;
+
+    ensure_service_exists(&connection, service_id).await?;
+
+    api = sqlx::query_as!(
+        ApiRecord,
+        r#"
+        insert into daysquare.api (id, service_id, url, vers)
+        values ($1, $2, $3, $4)
+        returning id, service_id, url, vers
+        "#,
+        Uuid::new_v4(),
+        service_id,
+        url.as_ref(),
+        vers
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    headers = HeaderMap::new();
+    headers.insert(header::LOCATION, api_location(api.id));
+
+    Ok((StatusCode::CREATED, headers, Json(api)))
+}
+
+#[tracing::instrument(name = "Listing versions of an API", skip(connection))]
+pub async fn list_apis(
+    Path(service_id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<Vec<ApiRecord>>, AppError> {
+    let connection = connection.0;
+    let apis;
+
+    ensure_service_exists(&connection, service_id).await?;
+
+    apis = sqlx::query_as!(
+        ApiRecord,
+        r#"
+        select id, service_id, url, vers
+        from daysquare.api
+        where service_id = $1
+        order by vers, url
+        "#,
+        service_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(Json(apis))
+}
+
+#[tracing::instrument(name = "Fetching an API version", skip(connection))]
+pub async fn get_api(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<ApiRecord>, AppError> {
+    let connection = connection.0;
+
+    sqlx::query_as!(
+        ApiRecord,
+        r#"
+        select id, service_id, url, vers
+        from daysquare.api
+        where id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .map(Json)
+    .ok_or_else(|| AppError::not_found("api"))
+}
+
+#[tracing::instrument(name = "Updating an API version", skip(connection))]
+pub async fn update_api(
+    Path(id): Path<Uuid>,
+    Json(patch): Json<ApiPatch>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<ApiRecord>, AppError> {
+    let connection = connection.0;
+    let url;
+    let vers;
+
+    url = patch
+        .url
+        .map(BaseUrl::parse)
+        .transpose()
+        .map_err(AppError::Validation)?;
+    vers = patch
+        .vers
+        .map(parse_version)
+        .transpose()
+        .map_err(AppError::Validation)?;
+
+    sqlx::query_as!(
+        ApiRecord,
+        r#"
+        update daysquare.api
+        set url = coalesce($2, url),
+            vers = coalesce($3, vers)
+        where id = $1
+        returning id, service_id, url, vers
+        "#,
+        id,
+        url.as_ref().map(AsRef::<str>::as_ref),
+        vers
+    )
+    .fetch_optional(&connection)
+    .await?
+    .map(Json)
+    .ok_or_else(|| AppError::not_found("api"))
+}
+
+#[tracing::instrument(name = "Deleting an API version", skip(connection))]
+pub async fn delete_api(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<StatusCode, AppError> {
+    let connection = connection.0;
+    let deleted;
+
+    deleted = sqlx::query!("delete from daysquare.api where id = $1", id)
+        .execute(&connection)
+        .await?
+        .rows_affected();
+
+    match deleted {
+        0 => Err(AppError::not_found("api")),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+async fn ensure_service_exists(connection: &PgPool, service_id: Uuid) -> Result<(), AppError> {
+    sqlx::query!("select id from daysquare.service where id = $1", service_id)
+        .fetch_optional(connection)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| AppError::not_found("service"))
+}
+
+/// Path an API can be fetched from, for use in a `Location` header
+fn api_location(id: Uuid) -> HeaderValue {
+    HeaderValue::from_str(&format!("/api/{}", id))
+        .expect("A uuid path is always a valid header value")
+}
@@ -0,0 +1,322 @@
+use axum::extract;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::{
+    parse_label, DataPrimitiveRecord, DataTypePatch, DataTypeRecord, NewDataPrimitive, NewDataType,
+    Primitive, SampleValue, Validation, BUILTIN_PRIMITIVES,
+};
+use crate::error::AppError;
+use crate::extract::{Json, Path};
+use crate::store;
+
+#[tracing::instrument(name = "Listing data primitives", skip(connection))]
+pub async fn list_data_primitives(
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<Vec<DataPrimitiveRecord>>, AppError> {
+    let connection = connection.0;
+    let primitives;
+
+    primitives = sqlx::query_as!(
+        DataPrimitiveRecord,
+        "select id, primitive from daysquare.data_primitive order by primitive"
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(Json(primitives))
+}
+
+#[tracing::instrument(name = "Adding a new data primitive", skip(input, connection))]
+pub async fn new_data_primitive(
+    Json(input): Json<NewDataPrimitive>,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, Json<DataPrimitiveRecord>), AppError> {
+    let connection = connection.0;
+    let primitive;
+    let record;
+
+    primitive = parse_label(input.primitive).map_err(AppError::Validation)?;
+
+    record = sqlx::query_as!(
+        DataPrimitiveRecord,
+        r#"
+        insert into daysquare.data_primitive (id, primitive)
+        values ($1, $2)
+        returning id, primitive
+        "#,
+        Uuid::new_v4(),
+        primitive
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+/// A built-in primitive, or one still used by a data type, is a conflict.
+#[tracing::instrument(name = "Deleting a data primitive", skip(connection))]
+pub async fn delete_data_primitive(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<StatusCode, AppError> {
+    let connection = connection.0;
+    let primitive;
+
+    primitive = sqlx::query!(
+        "select primitive from daysquare.data_primitive where id = $1",
+        id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or_else(|| AppError::not_found("data_primitive"))?
+    .primitive;
+
+    if BUILTIN_PRIMITIVES.contains(&primitive.as_str()) {
+        return Err(builtin(
+            "data_primitive",
+            "primitive",
+            &primitive,
+            "deleted",
+        ));
+    }
+
+    sqlx::query!("delete from daysquare.data_primitive where id = $1", id)
+        .execute(&connection)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(name = "Listing data types", skip(connection))]
+pub async fn list_data_types(
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<Vec<DataTypeRecord>>, AppError> {
+    let connection = connection.0;
+
+    Ok(Json(store::load_data_types(&connection).await?))
+}
+
+#[tracing::instrument(name = "Adding a new data type", skip(input, connection))]
+pub async fn new_data_type(
+    Json(input): Json<NewDataType>,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, HeaderMap, Json<DataTypeRecord>), AppError> {
+    let connection = connection.0;
+    let label;
+    let primitive_id;
+    let constraints;
+    let id;
+    let mut headers;
+
+    label = parse_label(input.label).map_err(AppError::Validation)?;
+    primitive_id = find_primitive(&connection, &input.primitive).await?;
+    constraints = input
+        .constraints
+        .parse(Primitive::from_name(&input.primitive))
+        .map_err(AppError::Validation)?;
+    id = Uuid::new_v4();
+
+    sqlx::query!(
+        r#"
+        insert into daysquare.data_type
+            (id, data_primitive_id, label,
+             pattern, min_length, max_length, min_value, max_value, enum_values)
+        values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        id,
+        primitive_id,
+        label,
+        constraints.pattern,
+        constraints.min_length,
+        constraints.max_length,
+        constraints.min_value,
+        constraints.max_value,
+        constraints.enum_values.as_deref()
+    )
+    .execute(&connection)
+    .await?;
+
+    headers = HeaderMap::new();
+    headers.insert(header::LOCATION, data_type_location(id));
+
+    Ok((
+        StatusCode::CREATED,
+        headers,
+        Json(DataTypeRecord {
+            id,
+            label,
+            data_primitive_id: primitive_id,
+            primitive: input.primitive,
+            constraints,
+        }),
+    ))
+}
+
+#[tracing::instrument(name = "Fetching a data type", skip(connection))]
+pub async fn get_data_type(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<DataTypeRecord>, AppError> {
+    let connection = connection.0;
+
+    store::load_data_type(&connection, id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::not_found("data_type"))
+}
+
+/// Constraints are checked against the primitive the data type ends up with.
+#[tracing::instrument(name = "Updating a data type", skip(connection))]
+pub async fn update_data_type(
+    Path(id): Path<Uuid>,
+    Json(patch): Json<DataTypePatch>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<DataTypeRecord>, AppError> {
+    let connection = connection.0;
+    let current;
+    let label;
+    let primitive_id;
+    let constraints;
+    let primitive;
+
+    current = store::load_data_type(&connection, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("data_type"))?;
+
+    if BUILTIN_PRIMITIVES.contains(&current.label.as_str()) {
+        return Err(builtin("data_type", "label", &current.label, "changed"));
+    }
+
+    label = patch
+        .label
+        .map(parse_label)
+        .transpose()
+        .map_err(AppError::Validation)?
+        .unwrap_or(current.label);
+    primitive = patch.primitive.unwrap_or(current.primitive);
+    primitive_id = find_primitive(&connection, &primitive).await?;
+    constraints = patch
+        .constraints
+        .unwrap_or(current.constraints)
+        .parse(Primitive::from_name(&primitive))
+        .map_err(AppError::Validation)?;
+
+    sqlx::query!(
+        r#"
+        update daysquare.data_type
+        set label = $2,
+            data_primitive_id = $3,
+            pattern = $4,
+            min_length = $5,
+            max_length = $6,
+            min_value = $7,
+            max_value = $8,
+            enum_values = $9
+        where id = $1
+        "#,
+        id,
+        label,
+        primitive_id,
+        constraints.pattern,
+        constraints.min_length,
+        constraints.max_length,
+        constraints.min_value,
+        constraints.max_value,
+        constraints.enum_values.as_deref()
+    )
+    .execute(&connection)
+    .await?;
+
+    Ok(Json(DataTypeRecord {
+        id,
+        label,
+        data_primitive_id: primitive_id,
+        primitive,
+        constraints,
+    }))
+}
+
+/// Check a sample value against a data type's primitive and constraints.
+/// Every violation is reported, not just the first.
+#[tracing::instrument(name = "Validating a value against a data type", skip(connection))]
+pub async fn validate_data_type(
+    Path(id): Path<Uuid>,
+    Json(sample): Json<SampleValue>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<Validation>, AppError> {
+    let connection = connection.0;
+    let data_type;
+    let violations;
+
+    data_type = store::load_data_type(&connection, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("data_type"))?;
+
+    violations = data_type
+        .constraints
+        .validate(Primitive::from_name(&data_type.primitive), &sample.value);
+
+    Ok(Json(Validation::new(violations)))
+}
+
+/// A data type still used by a request or response is a conflict,
+/// so is a built-in one.
+#[tracing::instrument(name = "Deleting a data type", skip(connection))]
+pub async fn delete_data_type(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<StatusCode, AppError> {
+    let connection = connection.0;
+    let label;
+
+    label = sqlx::query!("select label from daysquare.data_type where id = $1", id)
+        .fetch_optional(&connection)
+        .await?
+        .ok_or_else(|| AppError::not_found("data_type"))?
+        .label;
+
+    if BUILTIN_PRIMITIVES.contains(&label.as_str()) {
+        return Err(builtin("data_type", "label", &label, "deleted"));
+    }
+
+    sqlx::query!("delete from daysquare.data_type where id = $1", id)
+        .execute(&connection)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The primitives and data types seeded by the migrations are what the url DSL
+/// and the exporters resolve built-in labels such as `const` to, so they stay as they are.
+fn builtin(resource: &'static str, field: &'static str, name: &str, action: &str) -> AppError {
+    AppError::Conflict {
+        resource,
+        field,
+        message: format!(
+            "{} is a built-in {} and cannot be {}",
+            name,
+            resource.replace('_', " "),
+            action
+        ),
+    }
+}
+
+/// Id of the primitive with the given name. An unknown name is a validation error.
+async fn find_primitive(connection: &PgPool, primitive: &str) -> Result<Uuid, AppError> {
+    sqlx::query!(
+        "select id from daysquare.data_primitive where primitive = $1",
+        primitive
+    )
+    .fetch_optional(connection)
+    .await?
+    .map(|row| row.id)
+    .ok_or_else(|| AppError::validation(format!("unknown data primitive {}", primitive)))
+}
+
+/// Path a data type can be fetched from, for use in a `Location` header
+fn data_type_location(id: Uuid) -> HeaderValue {
+    HeaderValue::from_str(&format!("/data_type/{}", id))
+        .expect("A uuid path is always a valid header value")
+}
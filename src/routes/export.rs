@@ -0,0 +1,130 @@
+use axum::extract;
+use axum::http::{header, HeaderValue, Response};
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::export::{graph, openapi, rust, typescript};
+use crate::extract::{Json, Path, Query};
+use crate::store;
+
+/// Describe an API version and its requests as an OpenAPI 3.1 document
+#[tracing::instrument(name = "Exporting an API as OpenAPI", skip(connection))]
+pub async fn export_openapi(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let connection = connection.0;
+
+    store::load_api_export(&connection, id)
+        .await?
+        .map(|export| Json(openapi::document(&export)))
+        .ok_or_else(|| AppError::not_found("api"))
+}
+
+/// Generate a Rust module calling an API version
+#[tracing::instrument(name = "Generating a Rust client", skip(connection))]
+pub async fn export_rust_client(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Response<<String as IntoResponse>::Body>, AppError> {
+    let connection = connection.0;
+
+    store::load_api_export(&connection, id)
+        .await?
+        .map(|export| source_file(rust::module(&export), "text/x-rust; charset=utf-8"))
+        .ok_or_else(|| AppError::not_found("api"))
+}
+
+/// Generate a TypeScript module of interfaces and `fetch` helpers for an API version
+#[tracing::instrument(name = "Generating a TypeScript client", skip(connection))]
+pub async fn export_typescript_client(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Response<<String as IntoResponse>::Body>, AppError> {
+    let connection = connection.0;
+
+    store::load_api_export(&connection, id)
+        .await?
+        .map(|export| {
+            source_file(
+                typescript::module(&export),
+                "text/typescript; charset=utf-8",
+            )
+        })
+        .ok_or_else(|| AppError::not_found("api"))
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphFormat {
+    Json,
+    Dot,
+}
+
+impl Default for GraphFormat {
+    fn default() -> Self {
+        GraphFormat::Json
+    }
+}
+
+/// Query parameters of the graph export e.g.:
+/// `?format=dot&service=7a4e…`
+#[derive(Deserialize, Debug)]
+pub struct GraphOptions {
+    #[serde(default)]
+    format: GraphFormat,
+    service: Option<Uuid>,
+    api: Option<Uuid>,
+}
+
+/// Export stored requests and the data types they consume and produce
+/// as a bipartite graph, only those of a service or an API version when given.
+#[tracing::instrument(name = "Exporting the request graph", skip(connection))]
+pub async fn export_graph(
+    Query(options): Query<GraphOptions>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Response<<String as IntoResponse>::Body>, AppError> {
+    let connection = connection.0;
+    let (requests, schemas);
+    let graph;
+
+    if let Some(service_id) = options.service {
+        sqlx::query!("select id from daysquare.service where id = $1", service_id)
+            .fetch_optional(&connection)
+            .await?
+            .ok_or_else(|| AppError::not_found("service"))?;
+    }
+    if let Some(api_id) = options.api {
+        sqlx::query!("select id from daysquare.api where id = $1", api_id)
+            .fetch_optional(&connection)
+            .await?
+            .ok_or_else(|| AppError::not_found("api"))?;
+    }
+
+    (requests, schemas) = store::load_catalog(&connection, options.service, options.api).await?;
+    graph = graph::graph(&requests, &schemas);
+
+    Ok(match options.format {
+        GraphFormat::Json => source_file(
+            serde_json::to_string(&graph).expect("A graph always serializes to JSON"),
+            "application/json",
+        ),
+        GraphFormat::Dot => source_file(graph::dot(&graph), "text/vnd.graphviz; charset=utf-8"),
+    })
+}
+
+/// Generated text served with its content type
+fn source_file(
+    source: String,
+    content_type: &'static str,
+) -> Response<<String as IntoResponse>::Body> {
+    let mut response = source.into_response();
+
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response
+}
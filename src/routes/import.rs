@@ -0,0 +1,135 @@
+use axum::extract;
+use axum::http::StatusCode;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::error::AppError;
+use crate::extract::{Json, Query};
+use crate::import::{har, openapi, postman, CapturePlan, CaptureReport, ImportReport};
+use crate::store;
+
+#[derive(Deserialize, Debug)]
+pub struct ImportOptions {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Import an OpenAPI 3.0 or 3.1 document, in YAML or JSON.
+///
+/// With `?dry_run=true` everything is written in a transaction that is
+/// rolled back, so the report shows what would be created.
+#[tracing::instrument(name = "Importing an OpenAPI document", skip(body, connection))]
+pub async fn import_openapi(
+    Query(options): Query<ImportOptions>,
+    body: String,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, Json<ImportReport>), AppError> {
+    let connection = connection.0;
+    let document;
+    let plan;
+    let mut tx;
+    let mut report;
+
+    document = openapi::parse_document(&body).map_err(AppError::Parse)?;
+    plan = openapi::plan(&document).map_err(AppError::Validation)?;
+
+    tx = connection.begin().await?;
+    report = store::store_import(&mut tx, plan).await?;
+
+    if options.dry_run {
+        tx.rollback().await?;
+        report.dry_run = true;
+        return Ok((StatusCode::OK, Json(report)));
+    }
+
+    tx.commit().await?;
+
+    tracing::info!(
+        service_id = %report.service.id,
+        requests = report.requests.len(),
+        "Imported OpenAPI document"
+    );
+
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+/// Import a Postman v2.0 or v2.1 collection, one service per host.
+/// Takes `?dry_run=true` like [`import_openapi`].
+#[tracing::instrument(name = "Importing a Postman collection", skip(body, connection))]
+pub async fn import_postman(
+    Query(options): Query<ImportOptions>,
+    body: String,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, Json<CaptureReport>), AppError> {
+    let document;
+    let capture;
+
+    document = serde_json::from_str(&body).map_err(|e| AppError::Parse(e.to_string()))?;
+    capture = postman::plan(&document).map_err(AppError::Validation)?;
+
+    import_capture(options, capture, &connection.0).await
+}
+
+/// Import a HAR 1.1 or 1.2 capture, one service per host.
+/// Takes `?dry_run=true` like [`import_openapi`].
+#[tracing::instrument(name = "Importing a HAR capture", skip(body, connection))]
+pub async fn import_har(
+    Query(options): Query<ImportOptions>,
+    body: String,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, Json<CaptureReport>), AppError> {
+    let document;
+    let capture;
+
+    document = serde_json::from_str(&body).map_err(|e| AppError::Parse(e.to_string()))?;
+    capture = har::plan(&document).map_err(AppError::Validation)?;
+
+    import_capture(options, capture, &connection.0).await
+}
+
+/// Store the plan of every host in one transaction
+async fn import_capture(
+    options: ImportOptions,
+    capture: CapturePlan,
+    connection: &PgPool,
+) -> Result<(StatusCode, Json<CaptureReport>), AppError> {
+    let mut tx;
+    let mut services = Vec::new();
+
+    tx = connection.begin().await?;
+    for plan in capture.plans {
+        services.push(store::store_import(&mut tx, plan).await?);
+    }
+
+    if options.dry_run {
+        tx.rollback().await?;
+        for service in services.iter_mut() {
+            service.dry_run = true;
+        }
+        return Ok((
+            StatusCode::OK,
+            Json(CaptureReport {
+                dry_run: true,
+                services,
+                warnings: capture.warnings,
+            }),
+        ));
+    }
+
+    tx.commit().await?;
+
+    tracing::info!(
+        services = services.len(),
+        requests = services.iter().map(|s| s.requests.len()).sum::<usize>(),
+        "Imported recorded requests"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CaptureReport {
+            dry_run: false,
+            services,
+            warnings: capture.warnings,
+        }),
+    ))
+}
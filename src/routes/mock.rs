@@ -0,0 +1,32 @@
+use axum::extract;
+use axum::http::Uri;
+use axum::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::mock;
+use crate::store;
+
+/// Answer `/mock/{api_id}/{vers}/...` with synthetic JSON for the matching stored request.
+///
+/// Served nested under `/mock`, so `uri` starts at the api id.
+#[tracing::instrument(name = "Serving a mock response", skip(connection))]
+pub async fn mock_api(
+    uri: Uri,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let connection = connection.0;
+    let path = uri.path().trim_start_matches('/');
+    let (api_id, path) = path.split_once('/').unwrap_or((path, ""));
+    let api_id = Uuid::parse_str(api_id).map_err(|_| AppError::not_found("api"))?;
+    let export;
+
+    export = store::load_api_export(&connection, api_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("api"))?;
+
+    mock::respond(&export, path)
+        .map(Json)
+        .ok_or_else(|| AppError::not_found("request"))
+}
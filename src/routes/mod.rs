@@ -1,7 +1,29 @@
 mod api;
 mod api_form;
+mod api_version;
+mod data_type;
+mod export;
 mod health_check;
+mod import;
+mod mock;
+mod plan;
+mod request;
+mod response_schema;
 
-pub use api::new_service;
+pub use api::{delete_service, get_service, list_services, new_service, update_service};
 pub use api_form::{get_api_form, url_form};
+pub use api_version::{delete_api, get_api, list_apis, new_api, update_api};
+pub use data_type::{
+    delete_data_primitive, delete_data_type, get_data_type, list_data_primitives, list_data_types,
+    new_data_primitive, new_data_type, update_data_type, validate_data_type,
+};
+pub use export::{export_graph, export_openapi, export_rust_client, export_typescript_client};
 pub use health_check::health_check;
+pub use import::{import_har, import_openapi, import_postman};
+pub use mock::mock_api;
+pub use plan::plan_requests;
+pub use request::{execute_request, get_request, list_api_requests};
+pub use response_schema::{
+    get_response_schema, infer_response_schema, link_response_schema, new_response_schema,
+    validate_payload,
+};
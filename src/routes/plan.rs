@@ -0,0 +1,52 @@
+use axum::extract;
+use sqlx::PgPool;
+use std::collections::HashSet;
+
+use crate::domain::{plan, Plan, PlanQuery};
+use crate::error::AppError;
+use crate::extract::{Json, Query};
+use crate::store;
+
+/// The shortest chain of stored requests that turns values of the `have`
+/// data types into a response holding the `want` schema.
+#[tracing::instrument(name = "Planning a chain of requests", skip(connection))]
+pub async fn plan_requests(
+    Query(query): Query<PlanQuery>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<Plan>, AppError> {
+    let connection = connection.0;
+    let have;
+    let labels: HashSet<String>;
+    let unknown: Vec<&str>;
+    let (requests, schemas);
+
+    have = query.labels();
+
+    labels = store::load_data_types(&connection)
+        .await?
+        .into_iter()
+        .map(|t| t.label)
+        .collect();
+    unknown = have
+        .iter()
+        .filter(|label| !labels.contains(label.as_str()))
+        .map(String::as_str)
+        .collect();
+
+    if !unknown.is_empty() {
+        return Err(AppError::validation(format!(
+            "unknown data types: {}",
+            unknown.join(", ")
+        )));
+    }
+
+    store::load_response_schema(&connection, query.want)
+        .await?
+        .ok_or_else(|| AppError::not_found("response_schema"))?;
+
+    (requests, schemas) = store::load_catalog(&connection, None, None).await?;
+
+    plan(&requests, &schemas, &have, query.want)
+        .map(Json)
+        .ok_or_else(|| AppError::not_found("plan"))
+}
@@ -0,0 +1,129 @@
+use axum::extract;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::configuration::OutboundSettings;
+use crate::domain::{ExecuteInput, ExecuteOutput, RequestSummary};
+use crate::error::AppError;
+use crate::extract::{Json, Path};
+use crate::store;
+
+#[tracing::instrument(name = "Fetching a request", skip(connection))]
+pub async fn get_request(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<RequestSummary>, AppError> {
+    let connection = connection.0;
+
+    store::load_request(&connection, id)
+        .await?
+        .map(|request| Json(RequestSummary::from(&request)))
+        .ok_or_else(|| AppError::not_found("request"))
+}
+
+#[tracing::instrument(name = "Listing requests of an API", skip(connection))]
+pub async fn list_api_requests(
+    Path(api_id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<Vec<RequestSummary>>, AppError> {
+    let connection = connection.0;
+    let requests;
+
+    sqlx::query!("select id from daysquare.api where id = $1", api_id)
+        .fetch_optional(&connection)
+        .await?
+        .ok_or_else(|| AppError::not_found("api"))?;
+
+    requests = store::load_api_requests(&connection, api_id).await?;
+
+    Ok(Json(requests.iter().map(RequestSummary::from).collect()))
+}
+
+#[tracing::instrument(name = "Executing a request", skip(connection, client, outbound))]
+pub async fn execute_request(
+    Path(id): Path<Uuid>,
+    Json(input): Json<ExecuteInput>,
+    connection: extract::Extension<PgPool>,
+    client: extract::Extension<reqwest::Client>,
+    outbound: extract::Extension<OutboundSettings>,
+) -> Result<Json<ExecuteOutput>, AppError> {
+    let connection = connection.0;
+    let request;
+    let data_types: HashMap<_, _>;
+    let call;
+    let mut upstream;
+    let mut response;
+    let status;
+    let mut headers = BTreeMap::new();
+    let mut bytes = Vec::new();
+    let text;
+    let body;
+
+    request = store::load_request(&connection, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("request"))?;
+
+    data_types = store::load_data_types(&connection)
+        .await?
+        .into_iter()
+        .filter(|t| {
+            request
+                .paths
+                .iter()
+                .chain(&request.queries)
+                .chain(&request.headers)
+                .any(|s| s.data_type_id == t.id)
+        })
+        .map(|t| (t.id, t))
+        .collect();
+
+    call = request
+        .prepare(&input.values, &data_types)
+        .map_err(AppError::Validation)?;
+
+    upstream = client.0.get(&call.url);
+    for (name, value) in &call.headers {
+        upstream = upstream.header(name.as_str(), value.as_str());
+    }
+    // A request may ask for a shorter timeout than the configured one, never a longer one
+    if let Some(timeout_ms) = input.timeout_ms {
+        upstream = upstream.timeout(Duration::from_millis(timeout_ms).min(outbound.0.timeout()));
+    }
+
+    response = upstream.send().await.map_err(AppError::Upstream)?;
+
+    for (name, value) in response.headers() {
+        let value = String::from_utf8_lossy(value.as_bytes());
+
+        headers
+            .entry(name.to_string())
+            .and_modify(|joined: &mut String| {
+                joined.push_str(", ");
+                joined.push_str(&value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+
+    status = response.status().as_u16();
+    // read a chunk at a time so an endless body stops at the limit
+    while let Some(chunk) = response.chunk().await.map_err(AppError::Upstream)? {
+        if bytes.len() + chunk.len() > outbound.0.max_body_bytes {
+            return Err(AppError::UpstreamTooLarge {
+                limit: outbound.0.max_body_bytes,
+            });
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    text = String::from_utf8_lossy(&bytes).into_owned();
+    body = serde_json::from_str(&text).unwrap_or(Value::String(text));
+
+    Ok(Json(ExecuteOutput {
+        url: call.url,
+        status,
+        headers,
+        body,
+    }))
+}
@@ -0,0 +1,187 @@
+use axum::extract;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::{
+    check_payload, InferredSchema, NewSchemaLink, PayloadValidation, ResponseSchema, SchemaSamples,
+    UnknownTypes,
+};
+use crate::error::AppError;
+use crate::extract::{Json, Path, Query};
+use crate::import::infer::infer_schema;
+use crate::store;
+
+/// How often a link is tried before a serialization failure is returned
+const LINK_ATTEMPTS: usize = 3;
+
+#[derive(Deserialize, Debug)]
+pub struct SchemaOptions {
+    #[serde(default)]
+    unknown_types: UnknownTypes,
+}
+
+/// Store a nested response schema in one transaction.
+/// Unknown data type labels are rejected unless `?unknown_types=create` is given.
+#[tracing::instrument(name = "Adding a new response schema", skip(input, connection))]
+pub async fn new_response_schema(
+    Query(options): Query<SchemaOptions>,
+    Json(input): Json<ResponseSchema>,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, HeaderMap, Json<ResponseSchema>), AppError> {
+    let connection = connection.0;
+    let schema;
+    let mut tx;
+    let id;
+    let stored;
+    let mut headers;
+
+    schema = input.parse().map_err(AppError::Validation)?;
+
+    tx = connection.begin().await?;
+    id = store::insert_response_schema(&mut tx, &schema, options.unknown_types).await?;
+    tx.commit().await?;
+
+    stored = store::load_response_schema(&connection, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("response_schema"))?;
+
+    headers = HeaderMap::new();
+    headers.insert(header::LOCATION, response_schema_location(id));
+
+    Ok((StatusCode::CREATED, headers, Json(stored)))
+}
+
+#[tracing::instrument(name = "Fetching a response schema", skip(connection))]
+pub async fn get_response_schema(
+    Path(id): Path<Uuid>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<ResponseSchema>, AppError> {
+    let connection = connection.0;
+
+    store::load_response_schema(&connection, id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::not_found("response_schema"))
+}
+
+/// Check a JSON payload, e.g. a recorded provider response, against the schema `id`
+#[tracing::instrument(name = "Validating a payload", skip(payload, connection))]
+pub async fn validate_payload(
+    Path(id): Path<Uuid>,
+    Json(payload): Json<serde_json::Value>,
+    connection: extract::Extension<PgPool>,
+) -> Result<Json<PayloadValidation>, AppError> {
+    let connection = connection.0;
+    let schema;
+    let labels;
+    let data_types;
+
+    schema = store::load_response_schema(&connection, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("response_schema"))?;
+
+    labels = schema.labels();
+    data_types = store::load_data_types(&connection)
+        .await?
+        .into_iter()
+        .filter(|t| labels.contains(&t.label.as_str()))
+        .map(|t| (t.label.clone(), t))
+        .collect();
+
+    Ok(Json(PayloadValidation::new(check_payload(
+        &schema,
+        &data_types,
+        &payload,
+    ))))
+}
+
+/// Guess a schema from example responses without storing it.
+/// The schema comes back in the shape `POST /response_schema` takes.
+#[tracing::instrument(name = "Inferring a response schema", skip(input))]
+pub async fn infer_response_schema(
+    Json(input): Json<SchemaSamples>,
+) -> Result<Json<InferredSchema>, AppError> {
+    let mut warnings = Vec::new();
+    let schema;
+
+    schema = infer_schema(&input.description, &input.samples, &mut warnings)
+        .map_err(AppError::Validation)?;
+
+    Ok(Json(InferredSchema { schema, warnings }))
+}
+
+/// Nest an existing schema under the schema `id`.
+/// Links that would nest a schema in itself are rejected.
+#[tracing::instrument(name = "Nesting a response schema", skip(connection))]
+pub async fn link_response_schema(
+    Path(id): Path<Uuid>,
+    Json(link): Json<NewSchemaLink>,
+    connection: extract::Extension<PgPool>,
+) -> Result<(StatusCode, Json<ResponseSchema>), AppError> {
+    let connection = connection.0;
+    let mut attempt = 1;
+
+    if link.identifier.trim().is_empty() {
+        return Err(AppError::validation("field identifier cannot be empty"));
+    }
+
+    // two links can form a loop that neither sees on its own,
+    // the one retried after a serialization failure sees the other
+    while let Err(e) = try_link_response_schema(&connection, id, &link).await {
+        if !e.is_serialization_failure() || attempt == LINK_ATTEMPTS {
+            return Err(e);
+        }
+        attempt += 1;
+    }
+
+    store::load_response_schema(&connection, id)
+        .await?
+        .map(|schema| (StatusCode::CREATED, Json(schema)))
+        .ok_or_else(|| AppError::not_found("response_schema"))
+}
+
+/// Link the schemas in one serializable transaction
+async fn try_link_response_schema(
+    connection: &PgPool,
+    id: Uuid,
+    link: &NewSchemaLink,
+) -> Result<(), AppError> {
+    let mut tx;
+    let ids;
+    let found;
+
+    tx = connection.begin().await?;
+    sqlx::query("set transaction isolation level serializable")
+        .execute(&mut tx)
+        .await?;
+
+    // a schema nested in itself is reported as a loop by the store
+    ids = if id == link.schema_id {
+        vec![id]
+    } else {
+        vec![id, link.schema_id]
+    };
+    found = sqlx::query!(
+        r#"select count(*) as "count!" from daysquare.response_schema where id = any($1)"#,
+        &ids
+    )
+    .fetch_one(&mut tx)
+    .await?
+    .count;
+    if found < ids.len() as i64 {
+        return Err(AppError::not_found("response_schema"));
+    }
+
+    store::link_response_schema(&mut tx, id, link).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Path a response schema can be fetched from, for use in a `Location` header
+fn response_schema_location(id: Uuid) -> HeaderValue {
+    HeaderValue::from_str(&format!("/response_schema/{}", id))
+        .expect("A uuid path is always a valid header value")
+}
@@ -0,0 +1,55 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::domain::BaseUrl;
+use crate::error::AppError;
+
+/// Id of the API with the given version of the service, registering it
+/// with the service if it doesn't exist yet.
+///
+/// A version is identified by its service and `vers` alone, so a version
+/// already stored with another base url is a conflict rather than a new API.
+pub async fn find_or_create_api(
+    tx: &mut Transaction<'_, Postgres>,
+    service_id: Uuid,
+    url: &BaseUrl,
+    vers: &str,
+) -> Result<Uuid, AppError> {
+    sqlx::query!(
+        r#"
+        insert into daysquare.api (id, service_id, url, vers)
+        values ($1, $2, $3, $4)
+        on conflict (service_id, vers) do nothing
+        "#,
+        Uuid::new_v4(),
+        service_id,
+        url.as_ref(),
+        vers
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let api = sqlx::query!(
+        r#"
+        select id, url from daysquare.api
+        where service_id = $1 and vers = $2
+        "#,
+        service_id,
+        vers
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if api.url != url.as_ref() {
+        return Err(AppError::Conflict {
+            resource: "api",
+            field: "vers",
+            message: format!(
+                "version {} of this service is already served from {}",
+                vers, api.url
+            ),
+        });
+    }
+
+    Ok(api.id)
+}
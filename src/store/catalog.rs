@@ -0,0 +1,25 @@
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{find_requests, load_response_schemas};
+use crate::domain::{RequestDefinition, ResponseSchema};
+use crate::error::AppError;
+
+/// Every stored request, with the response schema of each by id.
+/// Only the requests of a service or an API when given.
+pub async fn load_catalog(
+    pool: &PgPool,
+    service_id: Option<Uuid>,
+    api_id: Option<Uuid>,
+) -> Result<(Vec<RequestDefinition>, HashMap<Uuid, ResponseSchema>), AppError> {
+    let requests;
+    let ids: Vec<Uuid>;
+    let schemas;
+
+    requests = find_requests(pool, service_id, api_id).await?;
+    ids = requests.iter().map(|r| r.response_schema_id).collect();
+    schemas = load_response_schemas(pool, &ids).await?;
+
+    Ok((requests, schemas))
+}
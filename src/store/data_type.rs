@@ -0,0 +1,142 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::{Constraints, DataTypeRecord, UnknownTypes, DEFAULT_PRIMITIVE};
+use crate::error::AppError;
+
+/// Ids of the data types with the given labels, keyed by label.
+///
+/// Labels without a data type are either registered with the `string`
+/// primitive or rejected together in one validation error, depending on `unknown`.
+pub async fn resolve_data_types(
+    tx: &mut Transaction<'_, Postgres>,
+    labels: &[&str],
+    unknown: UnknownTypes,
+) -> Result<HashMap<String, Uuid>, AppError> {
+    let wanted: Vec<String>;
+    let mut resolved: HashMap<String, Uuid>;
+    let mut missing: Vec<String>;
+
+    wanted = labels.iter().map(|l| l.to_string()).collect();
+
+    resolved = sqlx::query!(
+        "select id, label from daysquare.data_type where label = any($1)",
+        &wanted
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .map(|row| (row.label, row.id))
+    .collect();
+
+    missing = wanted
+        .into_iter()
+        .filter(|label| !resolved.contains_key(label))
+        .collect();
+    missing.sort();
+    missing.dedup();
+
+    if missing.is_empty() {
+        return Ok(resolved);
+    }
+
+    if unknown == UnknownTypes::Reject {
+        return Err(AppError::validation(format!(
+            "unknown data types: {}",
+            missing.join(", ")
+        )));
+    }
+
+    for label in missing {
+        let id = Uuid::new_v4();
+
+        tracing::info!(%label, "Registering unknown data type as a {}", DEFAULT_PRIMITIVE);
+        sqlx::query!(
+            r#"
+            insert into daysquare.data_type (id, data_primitive_id, label)
+            select $1::uuid, p.id, $2::text
+            from daysquare.data_primitive p
+            where p.primitive = $3
+            "#,
+            id,
+            label,
+            DEFAULT_PRIMITIVE
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        resolved.insert(label, id);
+    }
+
+    Ok(resolved)
+}
+
+/// Every data type, ordered by label
+pub async fn load_data_types(pool: &PgPool) -> Result<Vec<DataTypeRecord>, AppError> {
+    let rows = sqlx::query_as!(
+        DataTypeRow,
+        r#"
+        select t.id, t.label, t.data_primitive_id, p.primitive,
+            t.pattern, t.min_length, t.max_length, t.min_value, t.max_value, t.enum_values
+        from daysquare.data_type t
+        join daysquare.data_primitive p on p.id = t.data_primitive_id
+        order by t.label
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(DataTypeRecord::from).collect())
+}
+
+/// A stored data type, or `None` if there is no data type with that id
+pub async fn load_data_type(pool: &PgPool, id: Uuid) -> Result<Option<DataTypeRecord>, AppError> {
+    let row = sqlx::query_as!(
+        DataTypeRow,
+        r#"
+        select t.id, t.label, t.data_primitive_id, p.primitive,
+            t.pattern, t.min_length, t.max_length, t.min_value, t.max_value, t.enum_values
+        from daysquare.data_type t
+        join daysquare.data_primitive p on p.id = t.data_primitive_id
+        where t.id = $1
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(DataTypeRecord::from))
+}
+
+struct DataTypeRow {
+    id: Uuid,
+    label: String,
+    data_primitive_id: Uuid,
+    primitive: String,
+    pattern: Option<String>,
+    min_length: Option<i32>,
+    max_length: Option<i32>,
+    min_value: Option<f64>,
+    max_value: Option<f64>,
+    enum_values: Option<Vec<String>>,
+}
+
+impl From<DataTypeRow> for DataTypeRecord {
+    fn from(row: DataTypeRow) -> Self {
+        DataTypeRecord {
+            id: row.id,
+            label: row.label,
+            data_primitive_id: row.data_primitive_id,
+            primitive: row.primitive,
+            constraints: Constraints {
+                pattern: row.pattern,
+                min_length: row.min_length,
+                max_length: row.max_length,
+                min_value: row.min_value,
+                max_value: row.max_value,
+                enum_values: row.enum_values,
+            },
+        }
+    }
+}
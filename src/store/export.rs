@@ -0,0 +1,75 @@
+use sqlx::PgPool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::{load_api_requests, load_data_types, load_response_schema};
+use crate::domain::{ApiRecord, ServiceRecord};
+use crate::error::AppError;
+use crate::export::ApiExport;
+
+/// Everything stored about an API version, or `None` if there is no API with that id
+pub async fn load_api_export(pool: &PgPool, api_id: Uuid) -> Result<Option<ApiExport>, AppError> {
+    let api;
+    let service;
+    let requests;
+    let mut schema_ids = HashSet::new();
+    let mut response_schemas = Vec::new();
+    let mut data_types;
+    let mut labels: HashSet<&str> = HashSet::new();
+
+    api = match sqlx::query_as!(
+        ApiRecord,
+        "select id, service_id, url, vers from daysquare.api where id = $1",
+        api_id
+    )
+    .fetch_optional(pool)
+    .await?
+    {
+        Some(api) => api,
+        None => return Ok(None),
+    };
+
+    service = sqlx::query_as!(
+        ServiceRecord,
+        "select id, title, description, url from daysquare.service where id = $1",
+        api.service_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    requests = load_api_requests(pool, api_id).await?;
+
+    for request in &requests {
+        if !schema_ids.insert(request.response_schema_id) {
+            continue;
+        }
+        if let Some(schema) = load_response_schema(pool, request.response_schema_id).await? {
+            response_schemas.push(schema);
+        }
+    }
+
+    for request in &requests {
+        labels.extend(
+            request
+                .paths
+                .iter()
+                .chain(&request.queries)
+                .chain(&request.headers)
+                .map(|slot| slot.data_type.as_str()),
+        );
+    }
+    for schema in &response_schemas {
+        labels.extend(schema.labels());
+    }
+
+    data_types = load_data_types(pool).await?;
+    data_types.retain(|t| labels.contains(t.label.as_str()));
+
+    Ok(Some(ApiExport {
+        service,
+        api,
+        requests,
+        data_types,
+        response_schemas,
+    }))
+}
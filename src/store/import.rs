@@ -0,0 +1,172 @@
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{find_or_create_api, insert_request, insert_response_schema, NewRequest};
+use crate::domain::{BaseUrl, QueryVecStyle, UnknownTypes};
+use crate::error::AppError;
+use crate::import::{
+    ImportPlan, ImportReport, ImportedRequest, ImportedSchema, ImportedService, ServicePlan,
+};
+use crate::parsers::url::parse_api_url;
+
+/// Write everything an import maps to.
+///
+/// The service and data types are reused when they already exist,
+/// response schemas and requests are always added.
+pub async fn store_import(
+    tx: &mut Transaction<'_, Postgres>,
+    plan: ImportPlan,
+) -> Result<ImportReport, AppError> {
+    let service;
+    let mut data_types = Vec::new();
+    let mut schemas: HashMap<String, Uuid> = HashMap::new();
+    let mut response_schemas = Vec::new();
+    let mut requests = Vec::new();
+
+    service = find_or_create_service(tx, &plan.service).await?;
+
+    for data_type in &plan.data_types {
+        let created = sqlx::query!(
+            r#"
+            insert into daysquare.data_type
+                (id, data_primitive_id, label,
+                 pattern, min_length, max_length, min_value, max_value, enum_values)
+            select $1::uuid, p.id, $2::text,
+                $4::text, $5::integer, $6::integer,
+                $7::double precision, $8::double precision, $9::text[]
+            from daysquare.data_primitive p
+            where p.primitive = $3
+            on conflict (label) do nothing
+            "#,
+            Uuid::new_v4(),
+            data_type.label,
+            data_type.primitive,
+            data_type.constraints.pattern,
+            data_type.constraints.min_length,
+            data_type.constraints.max_length,
+            data_type.constraints.min_value,
+            data_type.constraints.max_value,
+            data_type.constraints.enum_values.as_deref()
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if created > 0 {
+            data_types.push(data_type.label.clone());
+        }
+    }
+
+    for schema in &plan.response_schemas {
+        let id = insert_response_schema(tx, &schema.schema, UnknownTypes::Create).await?;
+
+        schemas.insert(schema.name.clone(), id);
+        response_schemas.push(ImportedSchema {
+            id,
+            name: schema.name.clone(),
+        });
+    }
+
+    for request in plan.requests {
+        let api_get = parse_api_url(&request.dsl).map_err(|e| {
+            AppError::validation(format!(
+                "imported request {} is invalid: {}",
+                request.dsl, e
+            ))
+        })?;
+        let base_url = BaseUrl::parse(api_get.url.to_string()).map_err(AppError::Validation)?;
+        let api_id = find_or_create_api(tx, service.id, &base_url, api_get.ver).await?;
+        let response_schema_id = match request.response.as_ref().and_then(|r| schemas.get(r)) {
+            Some(id) => *id,
+            None => empty_response_schema(tx, &request.description).await?,
+        };
+
+        let id = insert_request(
+            tx,
+            &NewRequest {
+                api_id,
+                response_schema_id,
+                description: &request.description,
+                query_vec_style: QueryVecStyle::default(),
+                api_get: &api_get,
+            },
+            UnknownTypes::Create,
+        )
+        .await?;
+
+        requests.push(ImportedRequest {
+            id,
+            api_id,
+            description: request.description,
+            dsl: request.dsl,
+        });
+    }
+
+    Ok(ImportReport {
+        dry_run: false,
+        service,
+        data_types,
+        response_schemas,
+        requests,
+        warnings: plan.warnings,
+    })
+}
+
+async fn find_or_create_service(
+    tx: &mut Transaction<'_, Postgres>,
+    service: &ServicePlan,
+) -> Result<ImportedService, AppError> {
+    let existing = sqlx::query!(
+        "select id from daysquare.service where url = $1",
+        service.url
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if let Some(existing) = existing {
+        return Ok(ImportedService {
+            id: existing.id,
+            url: service.url.clone(),
+            created: false,
+        });
+    }
+
+    let id = Uuid::new_v4();
+    sqlx::query!(
+        r#"
+        insert into daysquare.service (id, title, description, url)
+        values ($1, $2, $3, $4)
+        "#,
+        id,
+        service.title,
+        service.description,
+        service.url
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(ImportedService {
+        id,
+        url: service.url.clone(),
+        created: true,
+    })
+}
+
+/// Response schema of a request without a known response, to be filled in later
+async fn empty_response_schema(
+    tx: &mut Transaction<'_, Postgres>,
+    description: &str,
+) -> Result<Uuid, AppError> {
+    let id = Uuid::new_v4();
+
+    sqlx::query!(
+        "insert into daysquare.response_schema (id, description) values ($1, $2)",
+        id,
+        description
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(id)
+}
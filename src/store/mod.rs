@@ -0,0 +1,22 @@
+//! Queries shared between several routes.
+//!
+//! Writes run inside a caller provided transaction so a route can
+//! group a set of writes into one atomic change. Reads take the pool.
+
+mod api;
+mod catalog;
+mod data_type;
+mod export;
+mod import;
+mod request;
+mod response_schema;
+
+pub use api::find_or_create_api;
+pub use catalog::load_catalog;
+pub use data_type::{load_data_type, load_data_types, resolve_data_types};
+pub use export::load_api_export;
+pub use import::store_import;
+pub use request::{find_requests, insert_request, load_api_requests, load_request, NewRequest};
+pub use response_schema::{
+    insert_response_schema, link_response_schema, load_response_schema, load_response_schemas,
+};
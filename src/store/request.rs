@@ -0,0 +1,258 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use std::convert::TryFrom;
+use uuid::Uuid;
+
+use super::resolve_data_types;
+use crate::domain::{QueryVecStyle, RequestDefinition, Slot, UnknownTypes};
+use crate::error::AppError;
+use crate::parsers::url::ApiGet;
+
+/// A request about to be stored under an API
+#[derive(Debug)]
+pub struct NewRequest<'a> {
+    pub api_id: Uuid,
+    pub response_schema_id: Uuid,
+    pub description: &'a str,
+    pub query_vec_style: QueryVecStyle,
+    pub api_get: &'a ApiGet<'a>,
+}
+
+/// Store a parsed request under an API along with its path, query and header data.
+///
+/// Data type labels are resolved with [`resolve_data_types`].
+/// Returns the id of the new `daysquare.request` row.
+pub async fn insert_request(
+    tx: &mut Transaction<'_, Postgres>,
+    request: &NewRequest<'_>,
+    unknown: UnknownTypes,
+) -> Result<Uuid, AppError> {
+    let api_get = request.api_get;
+    let request_id = Uuid::new_v4();
+    let labels: Vec<&str>;
+    let data_types;
+
+    labels = api_get
+        .paths
+        .iter()
+        .map(|p| p.data_type)
+        .chain(api_get.queries.iter().flatten().map(|q| q.data_type))
+        .chain(api_get.headers.iter().flatten().map(|h| h.data_type))
+        .collect();
+    data_types = resolve_data_types(tx, &labels, unknown).await?;
+
+    sqlx::query!(
+        r#"
+        insert into daysquare.request
+            (id, api_id, response_schema_id, description, query_vec_style)
+        values ($1, $2, $3, $4, $5)
+        "#,
+        request_id,
+        request.api_id,
+        request.response_schema_id,
+        request.description,
+        request.query_vec_style.as_str()
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for (sequence, path) in api_get.paths.iter().enumerate() {
+        let sequence = i16::try_from(sequence).map_err(|_| {
+            AppError::validation("a request cannot have more than 32767 path segments")
+        })?;
+
+        sqlx::query!(
+            r#"
+            insert into daysquare.path_data (id, request_id, data_type_id, sequence, name)
+            values ($1, $2, $3, $4, $5)
+            "#,
+            Uuid::new_v4(),
+            request_id,
+            data_types[path.data_type],
+            sequence,
+            path.name
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for query in api_get.queries.iter().flatten() {
+        sqlx::query!(
+            r#"
+            insert into daysquare.query_data (id, request_id, data_type_id, name, is_vec)
+            values ($1, $2, $3, $4, $5)
+            "#,
+            Uuid::new_v4(),
+            request_id,
+            data_types[query.data_type],
+            query.name,
+            query.is_vec
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for header in api_get.headers.iter().flatten() {
+        sqlx::query!(
+            r#"
+            insert into daysquare.header_data (id, request_id, data_type_id, name)
+            values ($1, $2, $3, $4)
+            "#,
+            Uuid::new_v4(),
+            request_id,
+            data_types[header.data_type],
+            header.name
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    Ok(request_id)
+}
+
+/// A stored request, or `None` if there is no request with that id
+pub async fn load_request(
+    pool: &PgPool,
+    request_id: Uuid,
+) -> Result<Option<RequestDefinition>, AppError> {
+    Ok(load_requests(pool, Some(request_id), None, None)
+        .await?
+        .pop())
+}
+
+/// Every request stored under an API
+pub async fn load_api_requests(
+    pool: &PgPool,
+    api_id: Uuid,
+) -> Result<Vec<RequestDefinition>, AppError> {
+    load_requests(pool, None, None, Some(api_id)).await
+}
+
+/// Every stored request, only those of a service or an API when given
+pub async fn find_requests(
+    pool: &PgPool,
+    service_id: Option<Uuid>,
+    api_id: Option<Uuid>,
+) -> Result<Vec<RequestDefinition>, AppError> {
+    load_requests(pool, None, service_id, api_id).await
+}
+
+async fn load_requests(
+    pool: &PgPool,
+    request_id: Option<Uuid>,
+    service_id: Option<Uuid>,
+    api_id: Option<Uuid>,
+) -> Result<Vec<RequestDefinition>, AppError> {
+    let rows;
+    let ids: Vec<Uuid>;
+    let mut requests: Vec<RequestDefinition>;
+
+    rows = sqlx::query!(
+        r#"
+        select r.id, r.api_id, r.response_schema_id, r.description, r.query_vec_style,
+            a.url, a.vers
+        from daysquare.request r
+        join daysquare.api a on a.id = r.api_id
+        where ($1::uuid is null or r.id = $1)
+            and ($2::uuid is null or a.service_id = $2)
+            and ($3::uuid is null or r.api_id = $3)
+        order by r.description, r.id
+        "#,
+        request_id,
+        service_id,
+        api_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    ids = rows.iter().map(|r| r.id).collect();
+    requests = rows
+        .into_iter()
+        .map(|r| RequestDefinition {
+            id: r.id,
+            api_id: r.api_id,
+            response_schema_id: r.response_schema_id,
+            description: r.description,
+            // the column's check constraint only allows known styles
+            query_vec_style: QueryVecStyle::parse(&r.query_vec_style).unwrap_or_default(),
+            url: r.url,
+            vers: r.vers,
+            paths: Vec::new(),
+            queries: Vec::new(),
+            headers: Vec::new(),
+        })
+        .collect();
+
+    let paths = sqlx::query!(
+        r#"
+        select p.request_id, p.name, p.data_type_id, t.label
+        from daysquare.path_data p
+        join daysquare.data_type t on t.id = p.data_type_id
+        where p.request_id = any($1)
+        order by p.sequence
+        "#,
+        &ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let queries = sqlx::query!(
+        r#"
+        select q.request_id, q.name, q.data_type_id, q.is_vec, t.label
+        from daysquare.query_data q
+        join daysquare.data_type t on t.id = q.data_type_id
+        where q.request_id = any($1)
+        order by q.name
+        "#,
+        &ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let headers = sqlx::query!(
+        r#"
+        select h.request_id, h.name, h.data_type_id, t.label
+        from daysquare.header_data h
+        join daysquare.data_type t on t.id = h.data_type_id
+        where h.request_id = any($1)
+        order by h.name
+        "#,
+        &ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for request in requests.iter_mut() {
+        request.paths = paths
+            .iter()
+            .filter(|p| p.request_id == request.id)
+            .map(|p| Slot {
+                name: p.name.clone(),
+                data_type_id: p.data_type_id,
+                data_type: p.label.clone(),
+                is_vec: false,
+            })
+            .collect();
+        request.queries = queries
+            .iter()
+            .filter(|q| q.request_id == request.id)
+            .map(|q| Slot {
+                name: q.name.clone(),
+                data_type_id: q.data_type_id,
+                data_type: q.label.clone(),
+                is_vec: q.is_vec,
+            })
+            .collect();
+        request.headers = headers
+            .iter()
+            .filter(|h| h.request_id == request.id)
+            .map(|h| Slot {
+                name: h.name.clone(),
+                data_type_id: h.data_type_id,
+                data_type: h.label.clone(),
+                is_vec: false,
+            })
+            .collect();
+    }
+
+    Ok(requests)
+}
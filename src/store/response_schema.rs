@@ -0,0 +1,311 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::resolve_data_types;
+use crate::domain::{
+    find_loop, NewSchemaLink, ResponseChild, ResponseField, ResponseSchema, SchemaLink,
+    UnknownTypes,
+};
+use crate::error::AppError;
+
+/// Store a response schema tree: one `daysquare.response_schema` row per
+/// schema, its fields in `response_data` and its children in `response_schema_data`.
+///
+/// Returns the id of the root schema.
+pub async fn insert_response_schema(
+    tx: &mut Transaction<'_, Postgres>,
+    schema: &ResponseSchema,
+    unknown: UnknownTypes,
+) -> Result<Uuid, AppError> {
+    let root_id = Uuid::new_v4();
+    let data_types;
+    let mut stack: Vec<(Uuid, &ResponseSchema)>;
+    let mut links: Vec<(Uuid, Uuid, &ResponseChild)>;
+
+    data_types = resolve_data_types(tx, &schema.labels(), unknown).await?;
+
+    stack = vec![(root_id, schema)];
+    links = Vec::new();
+
+    while let Some((id, schema)) = stack.pop() {
+        sqlx::query!(
+            "insert into daysquare.response_schema (id, description) values ($1, $2)",
+            id,
+            schema.description
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for field in &schema.data {
+            sqlx::query!(
+                r#"
+                insert into daysquare.response_data
+                    (id, response_schema_id, data_type_id, identifier, is_vec)
+                values ($1, $2, $3, $4, $5)
+                "#,
+                Uuid::new_v4(),
+                id,
+                data_types[field.data_type.as_str()],
+                field.identifier,
+                field.is_vec
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for child in &schema.schemas {
+            let child_id = Uuid::new_v4();
+
+            links.push((id, child_id, child));
+            stack.push((child_id, &child.schema));
+        }
+    }
+
+    // children have to exist before they can be linked
+    for (parent_id, child_id, child) in links {
+        sqlx::query!(
+            r#"
+            insert into daysquare.response_schema_data
+                (id, parent_response_schema_id, child_response_schema_id, identifier, is_vec)
+            values ($1, $2, $3, $4, $5)
+            "#,
+            Uuid::new_v4(),
+            parent_id,
+            child_id,
+            child.identifier,
+            child.is_vec
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    Ok(root_id)
+}
+
+/// A stored response schema with all its descendants,
+/// or `None` if there is no schema with that id
+pub async fn load_response_schema(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<ResponseSchema>, AppError> {
+    Ok(load_response_schemas(pool, &[id]).await?.remove(&id))
+}
+
+/// The stored response schemas with `ids` and all their descendants, by id.
+/// Ids without a schema are left out.
+pub async fn load_response_schemas(
+    pool: &PgPool,
+    ids: &[Uuid],
+) -> Result<HashMap<Uuid, ResponseSchema>, AppError> {
+    let schemas: HashMap<Uuid, String>;
+    let tree_ids: Vec<Uuid>;
+    let data;
+    let links;
+    let mut tree;
+
+    schemas = sqlx::query!(
+        r#"
+        with recursive tree(id) as (
+            select unnest($1::uuid[])
+            union
+            select d.child_response_schema_id
+            from daysquare.response_schema_data d
+            join tree t on t.id = d.parent_response_schema_id
+        )
+        select s.id, s.description
+        from daysquare.response_schema s
+        join tree t on t.id = s.id
+        "#,
+        ids
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, row.description))
+    .collect();
+
+    tree_ids = schemas.keys().copied().collect();
+
+    data = sqlx::query!(
+        r#"
+        select r.response_schema_id, r.identifier, r.is_vec, t.label
+        from daysquare.response_data r
+        join daysquare.data_type t on t.id = r.data_type_id
+        where r.response_schema_id = any($1)
+        order by r.identifier
+        "#,
+        &tree_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    links = sqlx::query!(
+        r#"
+        select parent_response_schema_id, child_response_schema_id, identifier, is_vec
+        from daysquare.response_schema_data
+        where parent_response_schema_id = any($1)
+        order by identifier
+        "#,
+        &tree_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    tree = Tree {
+        descriptions: schemas,
+        fields: HashMap::new(),
+        children: HashMap::new(),
+    };
+    for row in data {
+        tree.fields
+            .entry(row.response_schema_id)
+            .or_default()
+            .push(ResponseField {
+                identifier: row.identifier,
+                data_type: row.label,
+                is_vec: row.is_vec,
+            });
+    }
+    for row in links {
+        tree.children
+            .entry(row.parent_response_schema_id)
+            .or_default()
+            .push((row.child_response_schema_id, row.identifier, row.is_vec));
+    }
+
+    Ok(ids
+        .iter()
+        .filter(|id| tree.descriptions.contains_key(id))
+        .map(|&id| (id, tree.build(id)))
+        .collect())
+}
+
+/// Rows of a response schema and its descendants, keyed by schema id
+struct Tree {
+    descriptions: HashMap<Uuid, String>,
+    fields: HashMap<Uuid, Vec<ResponseField>>,
+    children: HashMap<Uuid, Vec<(Uuid, String, bool)>>,
+}
+
+impl Tree {
+    fn build(&self, id: Uuid) -> ResponseSchema {
+        ResponseSchema {
+            id: Some(id),
+            description: self.descriptions.get(&id).cloned().unwrap_or_default(),
+            data: self.fields.get(&id).cloned().unwrap_or_default(),
+            schemas: self
+                .children
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .map(|(child_id, identifier, is_vec)| ResponseChild {
+                    identifier: identifier.clone(),
+                    is_vec: *is_vec,
+                    schema: self.build(*child_id),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Nest an existing schema under another one.
+///
+/// A link that would nest a schema in itself is rejected
+/// with the loop it would create, e.g. `Artist.albums -> Album.artist -> Artist`.
+pub async fn link_response_schema(
+    tx: &mut Transaction<'_, Postgres>,
+    parent_id: Uuid,
+    link: &NewSchemaLink,
+) -> Result<(), AppError> {
+    let new;
+    let links: Vec<SchemaLink>;
+
+    new = SchemaLink {
+        parent: parent_id,
+        child: link.schema_id,
+        identifier: link.identifier.clone(),
+    };
+
+    links = sqlx::query!(
+        r#"
+        with recursive reachable(parent_id, child_id, identifier) as (
+            select parent_response_schema_id, child_response_schema_id, identifier
+            from daysquare.response_schema_data
+            where parent_response_schema_id = $1
+            union
+            select d.parent_response_schema_id, d.child_response_schema_id, d.identifier
+            from daysquare.response_schema_data d
+            join reachable r on r.child_id = d.parent_response_schema_id
+        )
+        select parent_id as "parent_id!", child_id as "child_id!", identifier as "identifier!"
+        from reachable
+        "#,
+        new.child
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .map(|row| SchemaLink {
+        parent: row.parent_id,
+        child: row.child_id,
+        identifier: row.identifier,
+    })
+    .collect();
+
+    if let Some(path) = find_loop(&links, &new) {
+        return Err(AppError::validation(format!(
+            "nesting would create a loop: {}",
+            describe_loop(tx, &path).await?
+        )));
+    }
+
+    sqlx::query!(
+        r#"
+        insert into daysquare.response_schema_data
+            (id, parent_response_schema_id, child_response_schema_id, identifier, is_vec)
+        values ($1, $2, $3, $4, $5)
+        "#,
+        Uuid::new_v4(),
+        new.parent,
+        new.child,
+        new.identifier,
+        link.is_vec
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(())
+}
+
+/// `Artist.albums -> Album.artist -> Artist`, using ids for schemas without a description
+async fn describe_loop(
+    tx: &mut Transaction<'_, Postgres>,
+    path: &[&SchemaLink],
+) -> Result<String, AppError> {
+    let ids: Vec<Uuid>;
+    let names: HashMap<Uuid, String>;
+    let name;
+    let mut steps: Vec<String>;
+
+    ids = path.iter().map(|l| l.parent).collect();
+    names = sqlx::query!(
+        "select id, description from daysquare.response_schema where id = any($1)",
+        &ids
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .filter(|row| !row.description.is_empty())
+    .map(|row| (row.id, row.description))
+    .collect();
+    name = |id: Uuid| names.get(&id).cloned().unwrap_or_else(|| id.to_string());
+
+    steps = path
+        .iter()
+        .map(|l| format!("{}.{}", name(l.parent), l.identifier))
+        .collect();
+    steps.push(name(path[0].parent));
+
+    Ok(steps.join(" -> "))
+}
@@ -1,7 +1,8 @@
-//mod request_id;
 mod logger;
+mod request_id;
 //mod root_span;
 
 pub mod root_span_macro;
 
 pub use logger::TracingLogger;
+pub use request_id::{RequestId, RequestIdLayer};
@@ -1,15 +1,30 @@
-use axum::{async_trait, body::Body, extract::{FromRequest, RequestParts}, http::{Response, StatusCode}, response::IntoResponse};
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequest, RequestParts},
+    http::{Request, Response, StatusCode},
+    response::IntoResponse,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use thiserror::Error;
+use tower::{Layer, Service};
 use uuid::Uuid;
 
+tokio::task_local! {
+    static REQUEST_ID: RequestId;
+}
+
 /// A unique identifier generated for each incoming request.
 ///
-/// Extracting a `RequestId` when the `TracingLogger` service is not
+/// Extracting a `RequestId` when the `RequestIdLayer` is not
 /// registered will result in an internal server error.
 ///
 /// # Usage
 ///
-/// todo
+/// Take it as a handler argument, or call [`RequestId::current`]
+/// from anywhere inside the handler's task.
 #[derive(Clone, Copy, Debug)]
 pub struct RequestId(Uuid);
 
@@ -17,6 +32,13 @@ impl RequestId {
     pub(crate) fn generate() -> Self {
         Self(Uuid::new_v4())
     }
+
+    /// Request id of the request being handled by the current task.
+    ///
+    /// Returns `None` outside of a request wrapped by `RequestIdLayer`.
+    pub fn current() -> Option<Self> {
+        REQUEST_ID.try_with(|id| *id).ok()
+    }
 }
 
 impl std::ops::Deref for RequestId {
@@ -46,9 +68,12 @@ where
 {
     type Rejection = RequestIdExtractionError;
 
-    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection>{
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
         match req.extensions() {
-            Some(e) => e.get::<RequestId>().cloned().ok_or(RequestIdExtractionError { _priv: () }),
+            Some(e) => e
+                .get::<RequestId>()
+                .cloned()
+                .ok_or(RequestIdExtractionError { _priv: () }),
             None => Err(RequestIdExtractionError { _priv: () }),
         }
     }
@@ -58,9 +83,7 @@ where
 /// the current request id from request-local storage.
 ///
 /// It only occcurs when extracting the current request id without having
-/// registered [`TracingLogger`] as a Tower Service for your application.
-///
-/// [`TracingLogger`]: crate::TracingLogger
+/// registered [`RequestIdLayer`] as a Tower Layer for your application.
 #[derive(Error, Debug)]
 pub struct RequestIdExtractionError {
     // Unit struct has a public constructor.
@@ -75,9 +98,12 @@ pub struct RequestIdExtractionError {
 impl IntoResponse for RequestIdExtractionError {
     type Body = Body;
     type BodyError = <Self::Body as axum::body::HttpBody>::Error;
-    
+
     fn into_response(self) -> Response<Self::Body> {
-        Response::builder().status(StatusCode::INTERNAL_SERVER_ERROR).body(Body::empty()).unwrap()
+        Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::empty())
+            .unwrap()
     }
 }
 
@@ -90,3 +116,47 @@ impl std::fmt::Display for RequestIdExtractionError {
     }
 }
 
+/// Generates a [`RequestId`] for every request.
+///
+/// The id is stored in the request extensions, where the root span
+/// and the [`RequestId`] extractor pick it up, and in a task local
+/// for [`RequestId::current`].
+///
+/// Must be layered outside of the `TraceLayer` so the root span
+/// is created after the id is generated.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { inner }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<Request<B>> for RequestIdService<S>
+where
+    S: Service<Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        let request_id = RequestId::generate();
+
+        req.extensions_mut().insert(request_id);
+        Box::pin(REQUEST_ID.scope(request_id, self.inner.call(req)))
+    }
+}
@@ -11,7 +11,7 @@ macro_rules! root_span {
                 .get("User-Agent")
                 .map(|h| h.to_str().unwrap_or(""))
                 .unwrap_or("");
-            let request_id = $crate::tracelog::root_span_macro::private::generate_request_id();
+            let request_id = $crate::tracelog::root_span_macro::private::request_id($request);
             let span = $crate::tracelog::root_span_macro::private::tracing::info_span!(
                 "HTTP request",
                 http.method         = %$request.method(),
@@ -37,12 +37,14 @@ pub mod private {
     //! in the code generated by the `root_span` macro.
     //! Items in this module are not part of the public interface of `tracing-actix-web` - they are considered
     //! implementation details and will change without notice in patch, minor and major releases.
-    use axum::http::{Method, Version};
+    use axum::http::{Method, Request, Version};
     use std::borrow::Cow;
 
     pub use tracing;
     use uuid::Uuid;
 
+    use crate::tracelog::RequestId;
+
     #[doc(hidden)]
     #[inline]
     pub fn http_method_str(method: &Method) -> Cow<'static, str> {
@@ -87,4 +89,14 @@ pub mod private {
     pub fn generate_request_id() -> Uuid {
         Uuid::new_v4()
     }
+
+    /// Id set by `RequestIdLayer`, or a fresh one if the layer is missing
+    #[doc(hidden)]
+    pub fn request_id<B>(request: &Request<B>) -> Uuid {
+        request
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| **id)
+            .unwrap_or_else(generate_request_id)
+    }
 }
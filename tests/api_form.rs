@@ -0,0 +1,319 @@
+pub mod helper;
+
+#[tokio::test]
+async fn url_form_stores_the_parsed_request() {
+    let app;
+    let service_id;
+    let response;
+    let api;
+    let paths;
+    let queries;
+
+    app = helper::spawn_app().await;
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            (
+                "url",
+                "https://spotify.com|v1/artists/{id,spotify_artist_id}?market=country_code",
+            ),
+            ("description", "Get an artist"),
+            ("unknown_types", "create"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+
+    api = sqlx::query!(
+        "select url, vers from daysquare.api where service_id = $1",
+        service_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to fetch saved api.");
+    assert_eq!(api.url, "https://spotify.com");
+    assert_eq!(api.vers, "v1");
+
+    paths = sqlx::query!(
+        r#"
+        select p.name, p.sequence, t.label
+        from daysquare.path_data p
+        join daysquare.data_type t on t.id = p.data_type_id
+        order by p.sequence
+        "#
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .expect("Failed to fetch saved paths.");
+    assert_eq!(paths.len(), 2);
+    assert_eq!(
+        (paths[0].name.as_str(), paths[0].label.as_str()),
+        ("artists", "const")
+    );
+    assert_eq!(
+        (paths[1].name.as_str(), paths[1].label.as_str()),
+        ("id", "spotify_artist_id")
+    );
+
+    queries = sqlx::query!(
+        r#"
+        select q.name, t.label
+        from daysquare.query_data q
+        join daysquare.data_type t on t.id = q.data_type_id
+        "#
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .expect("Failed to fetch saved queries.");
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].name, "market");
+    assert_eq!(queries[0].label, "country_code");
+}
+
+#[tokio::test]
+async fn url_form_reuses_an_existing_api() {
+    let app;
+    let client;
+    let service_id;
+    let apis;
+    let requests;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    for url in &[
+        "https://spotify.com|v1/artists/{id,spotify_artist_id}",
+        "https://spotify.com|v1/tracks/{id,spotify_track_id}",
+    ] {
+        let response = client
+            .post(&format!("{}/form", &app.address))
+            .form(&[
+                ("service_id", service_id.to_string().as_str()),
+                ("url", url),
+                ("description", "lookup"),
+                ("unknown_types", "create"),
+            ])
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(200, response.status().as_u16());
+    }
+
+    apis = sqlx::query!(r#"select count(*) as "count!" from daysquare.api"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count apis.");
+    requests = sqlx::query!(r#"select count(*) as "count!" from daysquare.request"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count requests.");
+
+    assert_eq!(apis.count, 1);
+    assert_eq!(requests.count, 2);
+}
+
+#[tokio::test]
+async fn url_form_returns_a_409_for_a_version_with_another_base_url() {
+    let app;
+    let client;
+    let service_id;
+    let mut response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = client
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            ("url", "https://spotify.com|v1/me"),
+            ("description", "lookup"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+
+    response = client
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            ("url", "https://api.spotify.com|v1/me"),
+            ("description", "lookup"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(409, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["resource"], "api");
+    assert_eq!(body["field"], "vers");
+}
+
+#[tokio::test]
+async fn url_form_highlights_the_malformed_part_of_the_url() {
+    let app;
+    let service_id;
+    let response;
+    let page;
+    let requests;
+
+    app = helper::spawn_app().await;
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            ("url", "https://spotify.com|v1/artists/{id/albums"),
+            ("description", "Get an artist"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(400, response.status().as_u16());
+    page = response.text().await.expect("Failed to read body.");
+    assert!(page.contains("<mark>{id</mark>"), "{}", page);
+
+    requests = sqlx::query!(r#"select count(*) as "count!" from daysquare.request"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count requests.");
+    assert_eq!(requests.count, 0);
+}
+
+#[tokio::test]
+async fn url_form_highlights_a_lone_trailing_slash() {
+    let app;
+    let service_id;
+    let response;
+    let page;
+
+    app = helper::spawn_app().await;
+    service_id = helper::create_service(&app, "a.com", "a").await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            ("url", "https://a.com|v1/"),
+            ("description", "Nothing"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(400, response.status().as_u16());
+    page = response.text().await.expect("Failed to read body.");
+    assert!(page.contains("https://a.com|v1<mark>/</mark>"), "{}", page);
+}
+
+#[tokio::test]
+async fn url_form_stores_header_data() {
+    let app;
+    let service_id;
+    let response;
+    let headers;
+
+    app = helper::spawn_app().await;
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            (
+                "url",
+                "https://api.spotify.com|v1/me#Authorization=spotify_token&Accept=const",
+            ),
+            ("description", "Current user"),
+            ("unknown_types", "create"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+
+    headers = sqlx::query!(
+        r#"
+        select h.name, t.label
+        from daysquare.header_data h
+        join daysquare.data_type t on t.id = h.data_type_id
+        order by h.name
+        "#
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .expect("Failed to fetch saved headers.");
+
+    assert_eq!(headers.len(), 2);
+    assert_eq!(
+        (headers[0].name.as_str(), headers[0].label.as_str()),
+        ("Accept", "const")
+    );
+    assert_eq!(
+        (headers[1].name.as_str(), headers[1].label.as_str()),
+        ("Authorization", "spotify_token")
+    );
+}
+
+#[tokio::test]
+async fn url_form_stores_vector_queries_and_their_style() {
+    let app;
+    let service_id;
+    let response;
+    let request;
+    let query;
+
+    app = helper::spawn_app().await;
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            (
+                "url",
+                "https://api.spotify.com|v1/tracks?ids=[spotify_track_id]",
+            ),
+            ("description", "Several tracks"),
+            ("query_vec_style", "comma"),
+            ("unknown_types", "create"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+
+    request = sqlx::query!("select query_vec_style from daysquare.request")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved request.");
+    assert_eq!(request.query_vec_style, "comma");
+
+    query = sqlx::query!(
+        r#"
+        select q.name, q.is_vec, t.label
+        from daysquare.query_data q
+        join daysquare.data_type t on t.id = q.data_type_id
+        "#
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to fetch saved query.");
+    assert_eq!(query.name, "ids");
+    assert_eq!(query.label, "spotify_track_id");
+    assert!(query.is_vec);
+}
@@ -0,0 +1,127 @@
+pub mod helper;
+
+#[tokio::test]
+async fn new_api_returns_a_201_and_can_be_listed() {
+    let app;
+    let client;
+    let service_id;
+    let mut response;
+    let created: serde_json::Value;
+    let listed: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = client
+        .post(&format!("{}/service/{}/api", &app.address, service_id))
+        .json(&serde_json::json!({ "url": "https://api.spotify.com/", "vers": "v1" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(201, response.status().as_u16());
+    created = response.json().await.expect("Failed to parse body.");
+    assert_eq!(created["url"], "https://api.spotify.com");
+    assert_eq!(created["vers"], "v1");
+    assert_eq!(created["service_id"], service_id.to_string());
+
+    response = client
+        .get(&format!("{}/service/{}/api", &app.address, service_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    listed = response.json().await.expect("Failed to parse body.");
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["id"], created["id"]);
+}
+
+#[tokio::test]
+async fn new_api_returns_a_409_for_a_duplicate_version() {
+    let app;
+    let client;
+    let service_id;
+    let mut response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = client
+        .post(&format!("{}/service/{}/api", &app.address, service_id))
+        .json(&serde_json::json!({ "url": "https://api.spotify.com", "vers": "v1" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, response.status().as_u16());
+
+    response = client
+        .post(&format!("{}/service/{}/api", &app.address, service_id))
+        .json(&serde_json::json!({ "url": "https://spotify.com/api", "vers": "v1" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(409, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["field"], "vers");
+}
+
+#[tokio::test]
+async fn new_api_returns_a_422_for_invalid_base_urls() {
+    let app;
+    let client;
+    let service_id;
+    let test_cases;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+    test_cases = vec![
+        ("api.spotify.com", "no scheme"),
+        ("/v1", "relative url"),
+        ("ftp://api.spotify.com", "not http"),
+    ];
+
+    for (url, error_message) in test_cases {
+        let response;
+
+        response = client
+            .post(&format!("{}/service/{}/api", &app.address, service_id))
+            .json(&serde_json::json!({ "url": url, "vers": "v1" }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(
+            422,
+            response.status().as_u16(),
+            "The API did not fail with 422 Unprocessable Entity when the url was {}.",
+            error_message
+        );
+    }
+}
+
+#[tokio::test]
+async fn new_api_returns_a_404_for_unknown_services() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .post(&format!(
+            "{}/service/{}/api",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .json(&serde_json::json!({ "url": "https://api.spotify.com", "vers": "v1" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(404, response.status().as_u16());
+}
@@ -0,0 +1,348 @@
+pub mod helper;
+
+#[tokio::test]
+async fn builtin_primitives_are_seeded() {
+    let app;
+    let response;
+    let body: serde_json::Value;
+    let primitives: Vec<&str>;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .get(&format!("{}/data_primitive", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    primitives = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["primitive"].as_str().unwrap())
+        .collect();
+    for primitive in &[
+        "bool", "const", "datetime", "float", "int", "string", "uuid",
+    ] {
+        assert!(primitives.contains(primitive), "{}", primitive);
+    }
+}
+
+#[tokio::test]
+async fn builtin_primitives_cannot_be_deleted() {
+    let app;
+    let client;
+    let primitive;
+    let response;
+    let problem: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    primitive = sqlx::query!("select id from daysquare.data_primitive where primitive = 'int'")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch seeded primitive.");
+
+    response = client
+        .delete(&format!("{}/data_primitive/{}", &app.address, primitive.id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(409, response.status().as_u16());
+    problem = response.json().await.expect("Failed to parse body.");
+    assert_eq!(
+        problem["detail"],
+        "int is a built-in data primitive and cannot be deleted"
+    );
+}
+
+#[tokio::test]
+async fn builtin_data_types_cannot_be_changed_or_deleted() {
+    let app;
+    let client;
+    let data_type;
+    let mut response;
+    let problem: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    data_type = sqlx::query!("select id from daysquare.data_type where label = 'const'")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch seeded data type.");
+
+    response = client
+        .patch(&format!("{}/data_type/{}", &app.address, data_type.id))
+        .json(&serde_json::json!({"label": "literal"}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(409, response.status().as_u16());
+    problem = response.json().await.expect("Failed to parse body.");
+    assert_eq!(
+        problem["detail"],
+        "const is a built-in data type and cannot be changed"
+    );
+
+    response = client
+        .delete(&format!("{}/data_type/{}", &app.address, data_type.id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(409, response.status().as_u16());
+    sqlx::query!("select id from daysquare.data_type where label = 'const'")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("The built-in data type was deleted.");
+}
+
+#[tokio::test]
+async fn new_data_type_returns_a_201_and_rejects_duplicate_labels() {
+    let app;
+    let client;
+    let response;
+    let body: serde_json::Value;
+    let duplicate;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    response = client
+        .post(&format!("{}/data_type", &app.address))
+        .json(&serde_json::json!({"label": "spotify_artist_id", "primitive": "string"}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(201, response.status().as_u16());
+    assert!(response.headers().contains_key("location"));
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["label"], "spotify_artist_id");
+    assert_eq!(body["primitive"], "string");
+
+    duplicate = client
+        .post(&format!("{}/data_type", &app.address))
+        .json(&serde_json::json!({"label": "spotify_artist_id", "primitive": "int"}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(409, duplicate.status().as_u16());
+}
+
+#[tokio::test]
+async fn new_data_type_returns_a_422_for_an_unknown_primitive() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/data_type", &app.address))
+        .json(&serde_json::json!({"label": "country_code", "primitive": "currency"}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(422, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn update_data_type_changes_its_primitive() {
+    let app;
+    let client;
+    let created: serde_json::Value;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    created = client
+        .post(&format!("{}/data_type", &app.address))
+        .json(&serde_json::json!({"label": "limit", "primitive": "string"}))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse body.");
+
+    response = client
+        .patch(&format!(
+            "{}/data_type/{}",
+            &app.address,
+            created["id"].as_str().unwrap()
+        ))
+        .json(&serde_json::json!({"primitive": "int"}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["label"], "limit");
+    assert_eq!(body["primitive"], "int");
+}
+
+#[tokio::test]
+async fn url_form_rejects_unknown_labels_when_asked_to() {
+    let app;
+    let client;
+    let service;
+    let response;
+    let body;
+    let requests;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    client
+        .post(&format!("{}/service", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("url=spotify.com&title=spotify&description=music+service")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    service = sqlx::query!("select id from daysquare.service")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved service.");
+
+    response = client
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service.id.to_string().as_str()),
+            (
+                "url",
+                "https://spotify.com|v1/artists/{id,spotify_artist_id}?market=country_code&limit=int",
+            ),
+            ("description", "Get an artist"),
+            ("unknown_types", "reject"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(422, response.status().as_u16());
+    body = response.text().await.expect("Failed to read body.");
+    assert!(body.contains("country_code, spotify_artist_id"), "{}", body);
+
+    requests = sqlx::query!(r#"select count(*) as "count!" from daysquare.request"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count requests.");
+    assert_eq!(requests.count, 0);
+}
+
+#[tokio::test]
+async fn url_form_rejects_unknown_labels_by_default() {
+    let app;
+    let service_id;
+    let response;
+
+    app = helper::spawn_app().await;
+    service_id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            (
+                "url",
+                "https://spotify.com|v1/artists/{id,spotify_artist_id}",
+            ),
+            ("description", "Get an artist"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(422, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn validate_data_type_reports_every_violation() {
+    let app;
+    let client;
+    let created: serde_json::Value;
+    let validate;
+    let valid: serde_json::Value;
+    let invalid: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    created = client
+        .post(&format!("{}/data_type", &app.address))
+        .json(&serde_json::json!({
+            "label": "spotify_artist_id",
+            "primitive": "string",
+            "constraints": {"pattern": "[0-9A-Za-z]+", "min_length": 22, "max_length": 22}
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse body.");
+    assert_eq!(created["constraints"]["max_length"], 22);
+
+    validate = format!(
+        "{}/data_type/{}/validate",
+        &app.address,
+        created["id"].as_str().unwrap()
+    );
+
+    valid = client
+        .post(&validate)
+        .json(&serde_json::json!({"value": "0OdUWJ0sBjDrqHygGUXeCF"}))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse body.");
+    assert_eq!(valid["valid"], true);
+
+    invalid = client
+        .post(&validate)
+        .json(&serde_json::json!({"value": "not-an-id"}))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse body.");
+    assert_eq!(invalid["valid"], false);
+    assert_eq!(invalid["violations"][0]["constraint"], "pattern");
+    assert_eq!(invalid["violations"][1]["constraint"], "min_length");
+}
+
+#[tokio::test]
+async fn new_data_type_returns_a_422_for_an_invalid_pattern() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/data_type", &app.address))
+        .json(&serde_json::json!({
+            "label": "country_code",
+            "primitive": "string",
+            "constraints": {"pattern": "[A-Z"}
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(422, response.status().as_u16());
+}
@@ -0,0 +1,276 @@
+pub mod helper;
+
+use serde_json::json;
+use std::time::Duration;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Store a request whose base url is `upstream`, returns its id
+async fn submit_request(app: &helper::TestApp, upstream: &str) -> uuid::Uuid {
+    let service_id;
+    let url;
+    let response;
+
+    url = format!(
+        "{}|v1/artists/{{id,string}}/albums?limit=int#Authorization=string",
+        upstream
+    );
+    response = reqwest::Client::new()
+        .post(&format!("{}/service", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("url=localhost&title=mock&description=mock+upstream")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, response.status().as_u16());
+
+    service_id = sqlx::query!("select id from daysquare.service")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved service.")
+        .id;
+
+    let response = reqwest::Client::new()
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            ("url", url.as_str()),
+            ("description", "albums"),
+            ("unknown_types", "create"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+
+    sqlx::query!("select id from daysquare.request")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved request.")
+        .id
+}
+
+async fn execute(
+    app: &helper::TestApp,
+    request_id: uuid::Uuid,
+    body: serde_json::Value,
+) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(&format!("{}/request/{}/execute", &app.address, request_id))
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn execute_returns_what_the_upstream_answered() {
+    let app;
+    let upstream;
+    let request_id;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/artists/radiohead/albums"))
+        .and(query_param("limit", "2"))
+        .and(header("Authorization", "Bearer token"))
+        .respond_with(
+            ResponseTemplate::new(203)
+                .insert_header("x-ratelimit-remaining", "99")
+                .set_body_json(json!({"items": [{"name": "Kid A"}]})),
+        )
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    request_id = submit_request(&app, &upstream.uri()).await;
+
+    response = execute(
+        &app,
+        request_id,
+        json!({"values": {"id": "radiohead", "limit": "2", "Authorization": "Bearer token"}}),
+    )
+    .await;
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(
+        body["url"],
+        format!("{}/v1/artists/radiohead/albums?limit=2", upstream.uri())
+    );
+    assert_eq!(body["status"], 203);
+    assert_eq!(body["headers"]["x-ratelimit-remaining"], "99");
+    assert_eq!(body["body"], json!({"items": [{"name": "Kid A"}]}));
+}
+
+#[tokio::test]
+async fn execute_passes_non_json_bodies_as_text() {
+    let app;
+    let upstream;
+    let request_id;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("upstream broke"))
+        .mount(&upstream)
+        .await;
+    request_id = submit_request(&app, &upstream.uri()).await;
+
+    response = execute(&app, request_id, json!({"values": {"id": "radiohead"}})).await;
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["status"], 500);
+    assert_eq!(body["body"], "upstream broke");
+}
+
+#[tokio::test]
+async fn execute_rejects_values_that_do_not_fit_their_data_type() {
+    let app;
+    let upstream;
+    let request_id;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+    request_id = submit_request(&app, &upstream.uri()).await;
+
+    response = execute(
+        &app,
+        request_id,
+        json!({"values": {"id": "radiohead", "limit": "ten", "market": "SE"}}),
+    )
+    .await;
+
+    assert_eq!(422, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(
+        body["detail"],
+        "market: the request has no slot with this name\nlimit: ten is not an integer"
+    );
+}
+
+#[tokio::test]
+async fn execute_returns_a_504_when_the_upstream_is_too_slow() {
+    let app;
+    let upstream;
+    let request_id;
+    let response;
+
+    app = helper::spawn_app().await;
+    upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+        .mount(&upstream)
+        .await;
+    request_id = submit_request(&app, &upstream.uri()).await;
+
+    response = execute(
+        &app,
+        request_id,
+        json!({"values": {"id": "radiohead"}, "timeout_ms": 50}),
+    )
+    .await;
+
+    assert_eq!(504, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn execute_returns_a_404_for_unknown_requests() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = execute(&app, uuid::Uuid::new_v4(), json!({})).await;
+
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn execute_rejects_header_values_that_cannot_be_sent() {
+    let app;
+    let upstream;
+    let request_id;
+    let response;
+
+    app = helper::spawn_app().await;
+    upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+    request_id = submit_request(&app, &upstream.uri()).await;
+
+    response = execute(
+        &app,
+        request_id,
+        json!({"values": {"id": "radiohead", "Authorization": "Bearer token\r\nX-Injected: 1"}}),
+    )
+    .await;
+
+    assert_eq!(422, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn execute_returns_redirects_without_following_them() {
+    let app;
+    let upstream;
+    let request_id;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/artists/radiohead/albums"))
+        .respond_with(ResponseTemplate::new(302).insert_header("location", "/elsewhere"))
+        .mount(&upstream)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/elsewhere"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+    request_id = submit_request(&app, &upstream.uri()).await;
+
+    response = execute(&app, request_id, json!({"values": {"id": "radiohead"}})).await;
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["status"], 302);
+    assert_eq!(body["headers"]["location"], "/elsewhere");
+}
+
+#[tokio::test]
+async fn execute_returns_a_502_when_the_upstream_body_is_too_large() {
+    let app;
+    let upstream;
+    let request_id;
+    let response;
+
+    app = helper::spawn_app().await;
+    upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![b'a'; 2 * 1_048_576]))
+        .mount(&upstream)
+        .await;
+    request_id = submit_request(&app, &upstream.uri()).await;
+
+    response = execute(&app, request_id, json!({"values": {"id": "radiohead"}})).await;
+
+    assert_eq!(502, response.status().as_u16());
+}
@@ -0,0 +1,282 @@
+pub mod helper;
+
+/// The Spotify document, with an optional query parameter next to the path one
+fn spotify() -> String {
+    helper::spotify_openapi(serde_json::json!({"paths": {"/artists/{id}": {"get": {
+        "parameters": [
+            {"name": "id", "in": "path", "required": true,
+             "schema": {"$ref": "#/components/schemas/spotify_artist_id"}},
+            {"name": "market", "in": "query", "schema": {"type": "string"}}
+        ]
+    }}}}))
+}
+#[tokio::test]
+async fn export_openapi_describes_the_stored_requests() {
+    let app;
+    let client;
+    let report: serde_json::Value;
+    let response;
+    let document: serde_json::Value;
+    let get;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    report = helper::import_openapi(&app, &spotify()).await;
+
+    response = client
+        .get(&format!(
+            "{}/api/{}/openapi.json",
+            &app.address,
+            report["requests"][0]["api_id"].as_str().unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    document = response.json().await.expect("Failed to parse body.");
+    assert_eq!(document["openapi"], "3.1.0");
+    assert_eq!(document["servers"][0]["url"], "https://api.spotify.com/v1");
+
+    get = &document["paths"]["/artists/{id}"]["get"];
+    assert_eq!(get["summary"], "Get an artist");
+    assert_eq!(
+        get["parameters"][0]["schema"],
+        serde_json::json!({"$ref": "#/components/schemas/spotify_artist_id"})
+    );
+    assert_eq!(
+        get["responses"]["200"]["content"]["application/json"]["schema"],
+        serde_json::json!({"$ref": "#/components/schemas/Artist"})
+    );
+    assert_eq!(
+        document["components"]["schemas"]["spotify_artist_id"]["pattern"],
+        "^(?:^[0-9A-Za-z]{22}$)$"
+    );
+    assert_eq!(
+        document["components"]["schemas"]["Artist"]["properties"]["popularity"],
+        serde_json::json!({"$ref": "#/components/schemas/int"})
+    );
+}
+
+#[tokio::test]
+async fn export_openapi_returns_a_404_for_an_unknown_api() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .get(&format!(
+            "{}/api/{}/openapi.json",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn export_rust_client_generates_a_module() {
+    let app;
+    let client;
+    let report: serde_json::Value;
+    let response;
+    let module;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    report = helper::import_openapi(&app, &spotify()).await;
+
+    response = client
+        .get(&format!(
+            "{}/api/{}/client.rs",
+            &app.address,
+            report["requests"][0]["api_id"].as_str().unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/x-rust; charset=utf-8"
+    );
+    module = response.text().await.expect("Failed to read body.");
+    assert!(module.contains("pub const BASE_URL: &str = \"https://api.spotify.com/v1\";"));
+    assert!(module.contains("pub struct SpotifyArtistId(pub String);"));
+    assert!(module.contains("pub struct Artist {"));
+    assert!(module.contains("    pub async fn get_an_artist(\n"));
+    assert!(module.contains("        id: &SpotifyArtistId,\n"));
+    assert!(module.contains("        market: Option<&str>,\n"));
+}
+
+#[tokio::test]
+async fn export_rust_client_returns_a_404_for_an_unknown_api() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .get(&format!(
+            "{}/api/{}/client.rs",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn export_typescript_client_generates_a_module() {
+    let app;
+    let client;
+    let report: serde_json::Value;
+    let response;
+    let module;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    report = helper::import_openapi(&app, &spotify()).await;
+
+    response = client
+        .get(&format!(
+            "{}/api/{}/client.ts",
+            &app.address,
+            report["requests"][0]["api_id"].as_str().unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/typescript; charset=utf-8"
+    );
+    module = response.text().await.expect("Failed to read body.");
+    assert!(module.contains("export const BASE_URL = \"https://api.spotify.com/v1\";"));
+    assert!(module.contains("export type SpotifyArtistId = string;"));
+    assert!(module.contains("export interface Artist {"));
+    assert!(module.contains(
+        "  params: { id: SpotifyArtistId; market?: string },\n  options: RequestOptions = {},\n): Promise<Artist> {\n"
+    ));
+}
+
+#[tokio::test]
+async fn export_graph_links_requests_to_the_data_types_they_use() {
+    let app;
+    let client;
+    let spotify;
+    let response;
+    let graph: serde_json::Value;
+    let request_id;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    spotify = helper::import_openapi(&app, &spotify()).await;
+    helper::import_openapi(
+        &app,
+        &helper::spotify_openapi(serde_json::json!({
+            "servers": [{"url": "https://api.deezer.com/v1"}]
+        })),
+    )
+    .await;
+
+    response = client
+        .get(&format!(
+            "{}/graph?api={}",
+            &app.address,
+            spotify["requests"][0]["api_id"].as_str().unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    graph = response.json().await.expect("Failed to parse body.");
+    request_id = &spotify["requests"][0]["id"];
+    assert_eq!(graph["requests"].as_array().unwrap().len(), 1);
+    assert_eq!(graph["requests"][0]["id"], *request_id);
+    assert_eq!(graph["requests"][0]["path"], "/artists/{id}");
+    assert!(graph["edges"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!({
+            "request": request_id,
+            "data_type": "spotify_artist_id",
+            "direction": "consumes",
+            "via": ["id"]
+        })));
+    assert!(graph["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|e| e["direction"] == "produces" && e["via"] == serde_json::json!(["popularity"])));
+}
+
+#[tokio::test]
+async fn export_graph_renders_dot() {
+    let app;
+    let spotify;
+    let response;
+    let dot;
+
+    app = helper::spawn_app().await;
+    spotify = helper::import_openapi(&app, &spotify()).await;
+
+    response = reqwest::Client::new()
+        .get(&format!(
+            "{}/graph?format=dot&service={}",
+            &app.address,
+            spotify["service"]["id"].as_str().unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/vnd.graphviz; charset=utf-8"
+    );
+    dot = response.text().await.expect("Failed to read body.");
+    assert!(dot.starts_with("digraph catalogue {\n"));
+    assert!(dot.contains(&format!(
+        "    \"data_type:spotify_artist_id\" -> \"request:{}\" [label=\"id\"];\n",
+        spotify["requests"][0]["id"].as_str().unwrap()
+    )));
+}
+
+#[tokio::test]
+async fn export_graph_returns_a_404_for_an_unknown_filter() {
+    let app;
+
+    app = helper::spawn_app().await;
+
+    for filter in &["service", "api"] {
+        let response = reqwest::Client::new()
+            .get(&format!(
+                "{}/graph?{}={}",
+                &app.address,
+                filter,
+                uuid::Uuid::new_v4()
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(404, response.status().as_u16(), "{}", filter);
+    }
+}
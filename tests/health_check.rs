@@ -1,4 +1,4 @@
-mod helper;
+pub mod helper;
 
 #[tokio::test]
 async fn health_check_works() {
@@ -18,11 +18,13 @@ async fn health_check_works() {
 }
 
 #[tokio::test]
-async fn new_service_returns_a_200_for_valid_form_data() {
+async fn new_service_returns_a_201_for_valid_form_data() {
     let app;
     let client;
     let response;
     let body;
+    let location;
+    let created: serde_json::Value;
     let saved;
 
     app = helper::spawn_app().await;
@@ -37,9 +39,17 @@ async fn new_service_returns_a_200_for_valid_form_data() {
         .await
         .expect("Failed to execute request.");
 
-    assert_eq!(200, response.status().as_u16());
+    assert_eq!(201, response.status().as_u16());
+    location = response
+        .headers()
+        .get("Location")
+        .expect("Missing Location header.")
+        .to_str()
+        .unwrap()
+        .to_string();
+    created = response.json().await.expect("Failed to parse body.");
 
-    saved = sqlx::query!("select title, url, description from daysquare.service",)
+    saved = sqlx::query!("select id, title, url, description from daysquare.service",)
         .fetch_one(&app.db_pool)
         .await
         .expect("Failed to fetch saved subscription.");
@@ -47,6 +57,9 @@ async fn new_service_returns_a_200_for_valid_form_data() {
     assert_eq!(saved.title, "spotify");
     assert_eq!(saved.url, "spotify.com");
     assert_eq!(saved.description, "music service");
+    assert_eq!(location, format!("/service/{}", saved.id));
+    assert_eq!(created["id"], saved.id.to_string());
+    assert_eq!(created["title"], "spotify");
 }
 
 #[tokio::test]
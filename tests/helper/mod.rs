@@ -1,7 +1,11 @@
+//! Shared by the test binaries, each declares it as `pub mod helper;`
+//! so the fixtures one binary leaves unused are not reported as dead code.
+
 use daysquare_backend::configuration::{get_configuration, DatabaseSettings};
 use daysquare_backend::run;
 use daysquare_backend::telemetry::{get_subscriber, init_subscriber};
 use once_cell::sync::Lazy;
+use serde_json::{json, Map, Value};
 use sqlx::{Connection, Executor, PgConnection, PgPool};
 use std::net::TcpListener;
 use uuid::Uuid;
@@ -49,7 +53,8 @@ pub async fn spawn_app() -> TestApp {
     configuration.database.database_name = Uuid::new_v4().to_string();
     connection_pool = configure_database(&configuration.database).await;
 
-    server = run(listener, connection_pool.clone()).expect("Failed to bind to address");
+    server = run(listener, connection_pool.clone(), configuration.outbound)
+        .expect("Failed to bind to address");
     let _ = tokio::spawn(server);
     TestApp {
         address,
@@ -78,3 +83,101 @@ pub async fn configure_database(config: &DatabaseSettings) -> PgPool {
 
     connection_pool
 }
+
+/// Create a service through the form endpoint, returns its id
+pub async fn create_service(app: &TestApp, url: &str, title: &str) -> Uuid {
+    let response;
+    let saved;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/service", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "url={}&title={}&description=music+service",
+            url, title
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, response.status().as_u16());
+
+    saved = sqlx::query!("select id from daysquare.service where url = $1", url)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved service.");
+
+    saved.id
+}
+
+/// An OpenAPI document for Spotify's "Get an artist" endpoint.
+/// `patch` is applied to it as a JSON merge patch (RFC 7396),
+/// so a test adds or replaces parts and removes them with `null`.
+pub fn spotify_openapi(patch: Value) -> String {
+    let mut document;
+
+    document = json!({
+        "openapi": "3.1.0",
+        "info": {"title": "Spotify", "version": "1.0.0"},
+        "servers": [{"url": "https://api.spotify.com/v1"}],
+        "paths": {
+            "/artists/{id}": {
+                "get": {
+                    "summary": "Get an artist",
+                    "parameters": [
+                        {"name": "id", "in": "path", "required": true,
+                         "schema": {"$ref": "#/components/schemas/spotify_artist_id"}}
+                    ],
+                    "responses": {"200": {"description": "An artist", "content": {
+                        "application/json": {"schema": {"$ref": "#/components/schemas/Artist"}}
+                    }}}
+                }
+            }
+        },
+        "components": {"schemas": {
+            "spotify_artist_id": {"type": "string", "pattern": "^[0-9A-Za-z]{22}$"},
+            "Artist": {"type": "object", "properties": {
+                "name": {"type": "string"},
+                "popularity": {"type": "integer"}
+            }}
+        }}
+    });
+    merge_patch(&mut document, patch);
+
+    document.to_string()
+}
+
+fn merge_patch(target: &mut Value, patch: Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        patch => {
+            *target = patch;
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(&key);
+            } else {
+                merge_patch(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Import an OpenAPI document, returns the import report
+pub async fn import_openapi(app: &TestApp, document: &str) -> Value {
+    reqwest::Client::new()
+        .post(&format!("{}/import/openapi", &app.address))
+        .body(document.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse body.")
+}
@@ -0,0 +1,239 @@
+pub mod helper;
+
+/// The Spotify document with an unanchored pattern and an operation other than GET
+fn spotify() -> String {
+    helper::spotify_openapi(serde_json::json!({
+        "paths": {"/artists/{id}": {"delete": {
+            "responses": {"204": {"description": "Deleted"}}
+        }}},
+        "components": {"schemas": {
+            "spotify_artist_id": {"pattern": "[0-9A-Za-z]{22}"}
+        }}
+    }))
+}
+async fn import(app: &helper::TestApp, query: &str, body: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(&format!("{}/import/openapi{}", &app.address, query))
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn import_openapi_stores_service_requests_and_types() {
+    let app;
+    let response;
+    let report: serde_json::Value;
+    let request;
+    let data_type;
+
+    app = helper::spawn_app().await;
+
+    response = import(&app, "", &spotify()).await;
+
+    assert_eq!(201, response.status().as_u16());
+    report = response.json().await.expect("Failed to parse body.");
+    assert_eq!(report["dry_run"], false);
+    assert_eq!(report["service"]["created"], true);
+    assert_eq!(
+        report["data_types"],
+        serde_json::json!(["spotify_artist_id"])
+    );
+    assert_eq!(
+        report["requests"][0]["dsl"],
+        "https://api.spotify.com|v1/artists/{id,spotify_artist_id}"
+    );
+    assert_eq!(
+        report["warnings"],
+        serde_json::json!(["skipped 1 operations other than GET"])
+    );
+
+    request = sqlx::query!(
+        r#"
+        select s.url, a.vers
+        from daysquare.request r
+        join daysquare.api a on a.id = r.api_id
+        join daysquare.service s on s.id = a.service_id
+        "#
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to fetch imported request.");
+    assert_eq!(request.url, "https://api.spotify.com");
+    assert_eq!(request.vers, "v1");
+
+    data_type =
+        sqlx::query!("select pattern from daysquare.data_type where label = 'spotify_artist_id'")
+            .fetch_one(&app.db_pool)
+            .await
+            .expect("Failed to fetch imported data type.");
+    assert_eq!(data_type.pattern.as_deref(), Some(".*[0-9A-Za-z]{22}.*"));
+}
+
+#[tokio::test]
+async fn import_openapi_dry_run_stores_nothing() {
+    let app;
+    let response;
+    let report: serde_json::Value;
+    let services;
+
+    app = helper::spawn_app().await;
+
+    response = import(&app, "?dry_run=true", &spotify()).await;
+
+    assert_eq!(200, response.status().as_u16());
+    report = response.json().await.expect("Failed to parse body.");
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["requests"].as_array().unwrap().len(), 1);
+
+    services = sqlx::query!(r#"select count(*) as "count!" from daysquare.service"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count services.");
+    assert_eq!(services.count, 0);
+}
+
+#[tokio::test]
+async fn import_openapi_rejects_unsupported_documents() {
+    let app;
+
+    app = helper::spawn_app().await;
+
+    assert_eq!(
+        400,
+        import(&app, "", "{ not: [json, or yaml")
+            .await
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        422,
+        import(&app, "", r#"{"swagger": "2.0", "info": {"title": "Old"}}"#)
+            .await
+            .status()
+            .as_u16()
+    );
+}
+
+fn har() -> serde_json::Value {
+    let entry = |url: &str, text: &str| {
+        serde_json::json!({
+            "request": {"method": "GET", "url": url},
+            "response": {
+                "status": 200,
+                "content": {"mimeType": "application/json", "text": text}
+            }
+        })
+    };
+
+    serde_json::json!({"log": {"version": "1.2", "entries": [
+        entry("https://api.spotify.com/v1/artists/1/albums", r#"{"total": 3}"#),
+        entry("https://api.spotify.com/v1/artists/2/albums", r#"{"total": 5, "next": null}"#),
+        entry("https://api.github.com/users/octocat", r#"{"login": "octocat"}"#),
+    ]}})
+}
+
+#[tokio::test]
+async fn import_har_stores_a_service_per_host() {
+    let app;
+    let response;
+    let report: serde_json::Value;
+    let services;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/import/har", &app.address))
+        .json(&har())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(201, response.status().as_u16());
+    report = response.json().await.expect("Failed to parse body.");
+    assert_eq!(
+        report["services"][1]["requests"][0]["dsl"],
+        "https://api.spotify.com|v1/artists/{artist_id,int}/albums"
+    );
+    assert_eq!(
+        report["services"][1]["response_schemas"][0]["name"],
+        "GET /v1/artists/{artist_id}/albums"
+    );
+
+    services = sqlx::query!(r#"select count(*) as "count!" from daysquare.service"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count services.");
+    assert_eq!(services.count, 2);
+}
+
+#[tokio::test]
+async fn import_postman_dry_run_stores_nothing() {
+    let app;
+    let response;
+    let report: serde_json::Value;
+    let requests;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/import/postman?dry_run=true", &app.address))
+        .json(&serde_json::json!({
+            "info": {
+                "name": "Spotify",
+                "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+            },
+            "item": [{
+                "name": "Get an artist",
+                "request": {"method": "GET", "url": "https://api.spotify.com/v1/artists/:id"}
+            }]
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    report = response.json().await.expect("Failed to parse body.");
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(
+        report["services"][0]["requests"][0]["dsl"],
+        "https://api.spotify.com|v1/artists/{id,string}"
+    );
+
+    requests = sqlx::query!(r#"select count(*) as "count!" from daysquare.request"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count requests.");
+    assert_eq!(requests.count, 0);
+}
+
+#[tokio::test]
+async fn import_postman_rejects_other_documents() {
+    let app;
+
+    app = helper::spawn_app().await;
+
+    assert_eq!(
+        400,
+        reqwest::Client::new()
+            .post(&format!("{}/import/postman", &app.address))
+            .body("not json")
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        422,
+        reqwest::Client::new()
+            .post(&format!("{}/import/postman", &app.address))
+            .json(&har())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .status()
+            .as_u16()
+    );
+}
@@ -0,0 +1,85 @@
+pub mod helper;
+
+/// The Spotify document, with an artist that uses more data types
+fn spotify() -> String {
+    helper::spotify_openapi(serde_json::json!({"components": {"schemas": {
+        "popularity": {"type": "integer", "minimum": 0, "maximum": 100},
+        "Artist": {"properties": {
+            "id": {"$ref": "#/components/schemas/spotify_artist_id"},
+            "popularity": {"$ref": "#/components/schemas/popularity"},
+            "genres": {"type": "array", "items": {"type": "string"}}
+        }}
+    }}}))
+}
+/// Import the Spotify document, returns the id of its API
+async fn import_spotify(app: &helper::TestApp) -> String {
+    helper::import_openapi(app, &spotify()).await["requests"][0]["api_id"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn get(app: &helper::TestApp, path: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .get(&format!("{}/mock/{}", &app.address, path))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn mock_answers_with_json_shaped_like_the_response_schema() {
+    let app;
+    let api_id;
+    let response;
+    let body: serde_json::Value;
+    let again: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    api_id = import_spotify(&app).await;
+
+    response = get(
+        &app,
+        &format!("{}/v1/artists/0OdUWJ0sBjDrqHygGUXeCF", api_id),
+    )
+    .await;
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["id"], "0OdUWJ0sBjDrqHygGUXeCF");
+    assert!(body["name"].is_string());
+    assert!(matches!(body["popularity"].as_i64(), Some(0..=100)));
+    assert!(body["genres"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|g| g.is_string()));
+
+    again = get(
+        &app,
+        &format!("{}/v1/artists/0OdUWJ0sBjDrqHygGUXeCF", api_id),
+    )
+    .await
+    .json()
+    .await
+    .expect("Failed to parse body.");
+    assert_eq!(body, again);
+}
+
+#[tokio::test]
+async fn mock_returns_a_404_for_urls_no_request_matches() {
+    let app;
+    let api_id;
+
+    app = helper::spawn_app().await;
+    api_id = import_spotify(&app).await;
+
+    for path in &[
+        format!("{}/v1/artists/radiohead", api_id),
+        format!("{}/v1/albums/0OdUWJ0sBjDrqHygGUXeCF", api_id),
+        format!("{}/v1/artists/0OdUWJ0sBjDrqHygGUXeCF", uuid::Uuid::new_v4()),
+        "not-an-api/v1/artists".to_string(),
+    ] {
+        assert_eq!(404, get(&app, path).await.status().as_u16(), "{}", path);
+    }
+}
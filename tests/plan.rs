@@ -0,0 +1,139 @@
+pub mod helper;
+
+/// The Spotify document with two requests, the first returns the ids the second needs
+fn spotify() -> String {
+    helper::spotify_openapi(serde_json::json!({
+        "paths": {
+            "/artists/{id}": null,
+            "/artists/{id}/albums": {
+                "get": {
+                    "summary": "Get an artist's albums",
+                    "parameters": [
+                        {"name": "id", "in": "path", "required": true,
+                         "schema": {"$ref": "#/components/schemas/spotify_artist_id"}}
+                    ],
+                    "responses": {"200": {"description": "Albums", "content": {
+                        "application/json": {"schema": {"$ref": "#/components/schemas/Albums"}}
+                    }}}
+                }
+            },
+            "/albums/{id}/tracks": {
+                "get": {
+                    "summary": "Get an album's tracks",
+                    "parameters": [
+                        {"name": "id", "in": "path", "required": true,
+                         "schema": {"$ref": "#/components/schemas/spotify_album_id"}}
+                    ],
+                    "responses": {"200": {"description": "Tracks", "content": {
+                        "application/json": {"schema": {"$ref": "#/components/schemas/Tracks"}}
+                    }}}
+                }
+            }
+        },
+        "components": {"schemas": {
+            "spotify_artist_id": {"pattern": null},
+            "spotify_album_id": {"type": "string"},
+            "Artist": null,
+            "Albums": {"type": "object", "properties": {
+                "items": {"type": "array", "items": {"type": "object", "properties": {
+                    "id": {"$ref": "#/components/schemas/spotify_album_id"},
+                    "name": {"type": "string"}
+                }}}
+            }},
+            "Tracks": {"type": "object", "properties": {
+                "items": {"type": "array", "items": {"type": "object", "properties": {
+                    "name": {"type": "string"}
+                }}}
+            }}
+        }}
+    }))
+}
+/// Import the Spotify document, returns the id of the `Tracks` schema
+async fn import_spotify(app: &helper::TestApp) -> String {
+    helper::import_openapi(app, &spotify()).await["response_schemas"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["name"] == "Tracks")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn get(app: &helper::TestApp, query: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .get(&format!("{}/plan?{}", &app.address, query))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn plan_chains_requests_through_shared_data_types() {
+    let app;
+    let tracks;
+    let response;
+    let plan: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    tracks = import_spotify(&app).await;
+
+    response = get(&app, &format!("have=spotify_artist_id&want={}", tracks)).await;
+
+    assert_eq!(200, response.status().as_u16());
+    plan = response.json().await.expect("Failed to parse body.");
+    assert_eq!(plan["steps"][0]["description"], "Get an artist's albums");
+    assert_eq!(
+        plan["steps"][0]["bindings"][0],
+        serde_json::json!({
+            "parameter": "id",
+            "in": "path",
+            "data_type": "spotify_artist_id",
+            "source": {"from": "have", "data_type": "spotify_artist_id"}
+        })
+    );
+    assert_eq!(plan["steps"][1]["description"], "Get an album's tracks");
+    assert_eq!(
+        plan["steps"][1]["bindings"][0]["source"],
+        serde_json::json!({"from": "step", "step": 0, "field": "items[].id"})
+    );
+    assert_eq!(plan["steps"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn plan_returns_a_404_when_no_chain_exists() {
+    let app;
+    let tracks;
+
+    app = helper::spawn_app().await;
+    tracks = import_spotify(&app).await;
+
+    for query in &[
+        format!("have=&want={}", tracks),
+        format!("want={}", uuid::Uuid::new_v4()),
+    ] {
+        assert_eq!(404, get(&app, query).await.status().as_u16(), "{}", query);
+    }
+}
+
+#[tokio::test]
+async fn plan_returns_a_422_for_unknown_data_types() {
+    let app;
+    let tracks;
+    let response;
+    let problem: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    tracks = import_spotify(&app).await;
+
+    response = get(
+        &app,
+        &format!("have=spotify_artist_id,spotify_show_id&want={}", tracks),
+    )
+    .await;
+
+    assert_eq!(422, response.status().as_u16());
+    problem = response.json().await.expect("Failed to parse body.");
+    assert_eq!(problem["detail"], "unknown data types: spotify_show_id");
+}
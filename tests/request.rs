@@ -0,0 +1,89 @@
+pub mod helper;
+
+async fn submit_url(app: &helper::TestApp, url: &str) -> uuid::Uuid {
+    let service_id;
+    let response;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/service", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("url=spotify.com&title=spotify&description=music+service")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, response.status().as_u16());
+
+    service_id = sqlx::query!("select id from daysquare.service")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved service.")
+        .id;
+
+    let response = reqwest::Client::new()
+        .post(&format!("{}/form", &app.address))
+        .form(&[
+            ("service_id", service_id.to_string().as_str()),
+            ("url", url),
+            ("description", "lookup"),
+            ("unknown_types", "create"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+
+    sqlx::query!("select id from daysquare.request")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved request.")
+        .id
+}
+
+#[tokio::test]
+async fn get_request_renders_the_stored_request_as_dsl() {
+    let app;
+    let request_id;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    request_id = submit_url(
+        &app,
+        "https://api.spotify.com|v1/artists/{id,spotify_artist_id}/albums?market=country_code&ids=[spotify_album_id]#Authorization=spotify_token",
+    )
+    .await;
+
+    response = reqwest::Client::new()
+        .get(&format!("{}/request/{}", &app.address, request_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(
+        body["dsl"],
+        "https://api.spotify.com|v1/artists/{id,spotify_artist_id}/albums?ids=[spotify_album_id]&market=country_code#Authorization=spotify_token"
+    );
+    assert_eq!(body["query_vec_style"], "repeat");
+}
+
+#[tokio::test]
+async fn get_request_returns_a_404_for_unknown_ids() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .get(&format!(
+            "{}/request/{}",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(404, response.status().as_u16());
+}
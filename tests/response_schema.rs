@@ -0,0 +1,394 @@
+pub mod helper;
+
+fn artist() -> serde_json::Value {
+    serde_json::json!({
+        "description": "Artist",
+        "data": [
+            {"identifier": "genres", "data_type": "string", "is_vec": true},
+            {"identifier": "name", "data_type": "string", "is_vec": false}
+        ],
+        "schemas": [{
+            "identifier": "images",
+            "is_vec": true,
+            "schema": {
+                "description": "Image",
+                "data": [
+                    {"identifier": "height", "data_type": "int", "is_vec": false},
+                    {"identifier": "url", "data_type": "string", "is_vec": false}
+                ],
+                "schemas": []
+            }
+        }]
+    })
+}
+
+/// Drop the ids the server adds so a stored schema can be compared with its input
+fn without_ids(value: &mut serde_json::Value) {
+    if let Some(object) = value.as_object_mut() {
+        object.remove("id");
+        object.values_mut().for_each(without_ids);
+    } else if let Some(array) = value.as_array_mut() {
+        array.iter_mut().for_each(without_ids);
+    }
+}
+
+#[tokio::test]
+async fn new_response_schema_stores_the_whole_tree() {
+    let app;
+    let client;
+    let response;
+    let location;
+    let mut fetched: serde_json::Value;
+    let links;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    response = client
+        .post(&format!("{}/response_schema", &app.address))
+        .json(&artist())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(201, response.status().as_u16());
+    location = response.headers()["location"].to_str().unwrap().to_string();
+
+    fetched = client
+        .get(&format!("{}{}", &app.address, location))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse body.");
+
+    assert!(fetched["schemas"][0]["schema"]["id"].is_string());
+    without_ids(&mut fetched);
+    assert_eq!(fetched, artist());
+
+    links = sqlx::query!(r#"select count(*) as "count!" from daysquare.response_schema_data"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count schema links.");
+    assert_eq!(links.count, 1);
+}
+
+#[tokio::test]
+async fn new_response_schema_stores_nothing_when_a_label_is_unknown() {
+    let app;
+    let mut schema;
+    let response;
+    let schemas;
+
+    app = helper::spawn_app().await;
+    schema = artist();
+    schema["schemas"][0]["schema"]["data"][1]["data_type"] = "image_url".into();
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/response_schema", &app.address))
+        .json(&schema)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(422, response.status().as_u16());
+
+    schemas = sqlx::query!(r#"select count(*) as "count!" from daysquare.response_schema"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count schemas.");
+    assert_eq!(schemas.count, 0);
+}
+
+#[tokio::test]
+async fn new_response_schema_returns_a_422_for_duplicate_identifiers() {
+    let app;
+    let mut schema;
+    let response;
+
+    app = helper::spawn_app().await;
+    schema = artist();
+    schema["data"][0]["identifier"] = "images".into();
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/response_schema", &app.address))
+        .json(&schema)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(422, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn get_response_schema_returns_a_404_for_an_unknown_id() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .get(&format!(
+            "{}/response_schema/{}",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(404, response.status().as_u16());
+}
+
+async fn create_schema(app: &helper::TestApp, description: &str) -> String {
+    let created: serde_json::Value;
+
+    created = reqwest::Client::new()
+        .post(&format!("{}/response_schema", &app.address))
+        .json(&serde_json::json!({"description": description}))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .json()
+        .await
+        .expect("Failed to parse body.");
+
+    created["id"].as_str().unwrap().to_string()
+}
+
+async fn link(
+    app: &helper::TestApp,
+    parent: &str,
+    identifier: &str,
+    child: &str,
+) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(&format!(
+            "{}/response_schema/{}/schemas",
+            &app.address, parent
+        ))
+        .json(&serde_json::json!({"identifier": identifier, "schema_id": child}))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn link_response_schema_rejects_loops_naming_the_path() {
+    let app;
+    let artist;
+    let album;
+    let track;
+    let response;
+    let problem: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    artist = create_schema(&app, "Artist").await;
+    album = create_schema(&app, "Album").await;
+    track = create_schema(&app, "Track").await;
+
+    assert_eq!(
+        201,
+        link(&app, &artist, "albums", &album)
+            .await
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        201,
+        link(&app, &album, "tracks", &track).await.status().as_u16()
+    );
+
+    response = link(&app, &track, "artist", &artist).await;
+
+    assert_eq!(422, response.status().as_u16());
+    problem = response.json().await.expect("Failed to parse body.");
+    assert_eq!(
+        problem["detail"],
+        "nesting would create a loop: Track.artist -> Artist.albums -> Album.tracks -> Track"
+    );
+
+    assert_eq!(
+        422,
+        link(&app, &album, "album", &album).await.status().as_u16()
+    );
+}
+
+#[tokio::test]
+async fn database_rejects_loops_inserted_directly() {
+    let app;
+    let artist;
+    let album;
+    let result;
+
+    app = helper::spawn_app().await;
+    artist = create_schema(&app, "Artist").await;
+    album = create_schema(&app, "Album").await;
+
+    assert_eq!(
+        201,
+        link(&app, &artist, "albums", &album)
+            .await
+            .status()
+            .as_u16()
+    );
+
+    result = sqlx::query!(
+        r#"
+        insert into daysquare.response_schema_data
+            (id, parent_response_schema_id, child_response_schema_id, identifier, is_vec)
+        values ($1, $2, $3, 'artist', false)
+        "#,
+        uuid::Uuid::new_v4(),
+        uuid::Uuid::parse_str(&album).unwrap(),
+        uuid::Uuid::parse_str(&artist).unwrap()
+    )
+    .execute(&app.db_pool)
+    .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn infer_response_schema_merges_samples_into_a_storable_tree() {
+    let app;
+    let client;
+    let response;
+    let inferred: serde_json::Value;
+    let stored;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    response = client
+        .post(&format!("{}/response_schema/infer", &app.address))
+        .json(&serde_json::json!({
+            "description": "Artist",
+            "samples": [
+                {"name": "Radiohead", "images": [{"url": "https://i.scdn.co/a", "height": 640}]},
+                {"name": "Björk", "genres": ["art pop"], "images": []}
+            ]
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    inferred = response.json().await.expect("Failed to parse body.");
+    assert_eq!(
+        inferred["schema"],
+        serde_json::json!({
+            "description": "Artist",
+            "data": [
+                {"identifier": "genres", "data_type": "string", "is_vec": true},
+                {"identifier": "name", "data_type": "string", "is_vec": false}
+            ],
+            "schemas": [{
+                "identifier": "images",
+                "is_vec": true,
+                "schema": {
+                    "description": "images",
+                    "data": [
+                        {"identifier": "height", "data_type": "int", "is_vec": false},
+                        {"identifier": "url", "data_type": "string", "is_vec": false}
+                    ],
+                    "schemas": []
+                }
+            }]
+        })
+    );
+
+    stored = client
+        .post(&format!("{}/response_schema", &app.address))
+        .json(&inferred["schema"])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(201, stored.status().as_u16());
+}
+
+#[tokio::test]
+async fn infer_response_schema_returns_a_422_for_scalar_samples() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .post(&format!("{}/response_schema/infer", &app.address))
+        .json(&serde_json::json!({"samples": [42]}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(422, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn validate_payload_reports_problems_with_json_pointers() {
+    let app;
+    let client;
+    let location;
+    let response;
+    let validation: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    location = client
+        .post(&format!("{}/response_schema", &app.address))
+        .json(&artist())
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .headers()["location"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    response = client
+        .post(&format!("{}{}/validate", &app.address, location))
+        .json(&serde_json::json!({
+            "genres": "rock",
+            "images": [{"height": "640", "url": "https://i.scdn.co/a"}],
+            "followers": 10
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    validation = response.json().await.expect("Failed to parse body.");
+    assert_eq!(validation["valid"], false);
+    assert_eq!(
+        validation["problems"],
+        serde_json::json!([
+            {"pointer": "/genres", "kind": "is_vec", "message": "expected an array, found a string"},
+            {"pointer": "", "kind": "missing", "message": "name is missing"},
+            {"pointer": "/images/0/height", "kind": "type", "message": "expected an integer, found a string"},
+            {"pointer": "/followers", "kind": "unexpected", "message": "followers is not in the schema"}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn validate_payload_returns_a_404_for_an_unknown_schema() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .post(&format!(
+            "{}/response_schema/{}/validate",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(404, response.status().as_u16());
+}
@@ -0,0 +1,215 @@
+pub mod helper;
+
+#[tokio::test]
+async fn list_services_returns_a_page_of_services() {
+    let app;
+    let client;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    helper::create_service(&app, "spotify.com", "spotify").await;
+    helper::create_service(&app, "youtube.com", "youtube").await;
+    helper::create_service(&app, "deezer.com", "deezer").await;
+
+    response = client
+        .get(&format!("{}/service?page=2&per_page=2", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["total"], 3);
+    assert_eq!(body["page"], 2);
+    assert_eq!(body["per_page"], 2);
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+    assert_eq!(body["items"][0]["title"], "youtube");
+}
+
+#[tokio::test]
+async fn get_service_returns_the_stored_service() {
+    let app;
+    let client;
+    let id;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = client
+        .get(&format!("{}/service/{}", &app.address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["id"], id.to_string());
+    assert_eq!(body["url"], "spotify.com");
+    assert_eq!(body["description"], "music service");
+}
+
+#[tokio::test]
+async fn get_service_returns_a_404_for_unknown_ids() {
+    let app;
+    let client;
+    let response;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+
+    response = client
+        .get(&format!(
+            "{}/service/{}",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn update_service_only_changes_given_fields() {
+    let app;
+    let client;
+    let id;
+    let response;
+    let saved;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    id = helper::create_service(&app, "spotify.com", "spotfy").await;
+
+    response = client
+        .patch(&format!("{}/service/{}", &app.address, id))
+        .json(&serde_json::json!({ "title": "spotify" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(200, response.status().as_u16());
+
+    saved = sqlx::query!(
+        "select title, url, description from daysquare.service where id = $1",
+        id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to fetch saved service.");
+
+    assert_eq!(saved.title, "spotify");
+    assert_eq!(saved.url, "spotify.com");
+    assert_eq!(saved.description, "music service");
+}
+
+#[tokio::test]
+async fn delete_service_removes_the_service() {
+    let app;
+    let client;
+    let id;
+    let mut response;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = client
+        .delete(&format!("{}/service/{}", &app.address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(204, response.status().as_u16());
+
+    response = client
+        .delete(&format!("{}/service/{}", &app.address, id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn new_service_returns_a_409_for_a_duplicate_url() {
+    let app;
+    let client;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = client
+        .post(&format!("{}/service", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("url=spotify.com&title=spotify2&description=music+service")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(409, response.status().as_u16());
+    assert_eq!(
+        response.headers()["Content-Type"],
+        "application/problem+json"
+    );
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["status"], 409);
+    assert_eq!(body["resource"], "service");
+    assert_eq!(body["field"], "url");
+}
+
+#[tokio::test]
+async fn update_service_returns_a_problem_for_malformed_json() {
+    let app;
+    let client;
+    let id;
+    let response;
+    let body: serde_json::Value;
+
+    app = helper::spawn_app().await;
+    client = reqwest::Client::new();
+    id = helper::create_service(&app, "spotify.com", "spotify").await;
+
+    response = client
+        .patch(&format!("{}/service/{}", &app.address, id))
+        .header("Content-Type", "application/json")
+        .body(r#"{"title": "#)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(400, response.status().as_u16());
+    assert_eq!(
+        response.headers()["Content-Type"],
+        "application/problem+json"
+    );
+    body = response.json().await.expect("Failed to parse body.");
+    assert_eq!(body["status"], 400);
+}
+
+#[tokio::test]
+async fn get_service_returns_a_problem_for_a_malformed_id() {
+    let app;
+    let response;
+
+    app = helper::spawn_app().await;
+
+    response = reqwest::Client::new()
+        .get(&format!("{}/service/not-a-uuid", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(400, response.status().as_u16());
+    assert_eq!(
+        response.headers()["Content-Type"],
+        "application/problem+json"
+    );
+}